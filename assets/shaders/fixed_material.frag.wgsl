@@ -7,6 +7,7 @@ struct MaterialUniforms {
     texture_scale: vec2<f32>,
     parallax_rate: vec2<f32>,
     pos: vec2<f32>,
+    offset: vec2<f32>,
 };
 
 @group(2) @binding(0) var<uniform> material_uniforms: MaterialUniforms;
@@ -16,7 +17,7 @@ struct MaterialUniforms {
 @fragment
 fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
     let parallax = material_uniforms.pos * (material_uniforms.parallax_rate - vec2(1.0)); //* material_uniforms.parallax_rate;
-    let scaled_uv = (mesh.world_position.xy + parallax) / material_uniforms.texture_scale;
+    let scaled_uv = (mesh.world_position.xy + parallax + material_uniforms.offset) / material_uniforms.texture_scale;
     let wrapped_uv = fract(scaled_uv); // Ensure the texture coordinates wrap around
 
     let base_color = textureSample(base_color_texture, base_color_sampler, wrapped_uv);
@@ -0,0 +1,183 @@
+//! Distance to a target going around the walls, used to score search nodes.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    data::level::{LevelData, TrackData},
+    polygon, track,
+};
+
+/// Wall rects are grown by this much when marking cells as blocked, so the field doesn't route
+/// the wheel through gaps it can't fit in.
+const WHEEL_CLEARANCE: f32 = 0.5;
+/// Room left around the level bounds, the robot can fly a bit past the outer walls.
+const MARGIN: f32 = 20.0;
+
+pub struct DistanceField {
+    origin: Vec2,
+    cell_size: f32,
+    width: usize,
+    height: usize,
+    /// Steps to the target, `None` for blocked or unreachable cells.
+    distances: Vec<Option<u32>>,
+    target: Vec2,
+}
+
+impl DistanceField {
+    /// Floods the free cells of `level` starting from the cells covered by `target`.
+    pub fn new(level: &LevelData, target: Rect, cell_size: f32) -> Self {
+        // walls in their own space, (inverse rotation, rect around the origin)
+        let walls: Vec<(Vec2, Rot2, Rect)> = level
+            .walls
+            .values()
+            .map(|wall| {
+                let rect = Rect::from(wall.rect);
+                (
+                    rect.center(),
+                    Rot2::radians(-wall.angle),
+                    Rect::from_center_size(Vec2::ZERO, rect.size()).inflate(WHEEL_CLEARANCE),
+                )
+            })
+            .collect();
+        let polygons: Vec<Vec<Vec2>> = level
+            .polygon_walls
+            .values()
+            .map(|wall| wall.world_points())
+            .filter(|points| points.len() >= 3)
+            .collect();
+        // loops are ridden through, only the other tracks block the way
+        let tracks: Vec<TrackData> = level
+            .tracks
+            .values()
+            .filter(|track| !track.is_loop())
+            .map(|track| TrackData {
+                radius: track.radius - WHEEL_CLEARANCE,
+                thickness: track.thickness + 2.0 * WHEEL_CLEARANCE,
+                ..*track
+            })
+            .collect();
+        let bounds = walls
+            .iter()
+            .map(|(center, _, rect)| {
+                // the rect diagonal covers any rotation
+                Rect::from_center_half_size(*center, Vec2::splat(rect.half_size().length()))
+            })
+            .chain(polygons.iter().map(|points| {
+                points.iter().fold(
+                    Rect::from_center_size(points[0], Vec2::ZERO),
+                    |rect, point| rect.union_point(*point),
+                )
+            }))
+            .chain(tracks.iter().map(|track| {
+                Rect::from_center_half_size(
+                    track.center.into(),
+                    Vec2::splat(track.radius + track.thickness),
+                )
+            }))
+            .fold(target, |bounds, wall| bounds.union(wall))
+            .inflate(MARGIN);
+        let width = (bounds.width() / cell_size).ceil() as usize;
+        let height = (bounds.height() / cell_size).ceil() as usize;
+        let mut field = Self {
+            origin: bounds.min,
+            cell_size,
+            width,
+            height,
+            distances: vec![None; width * height],
+            target: target.center(),
+        };
+
+        let blocked: Vec<bool> = (0..width * height)
+            .map(|index| {
+                let center = field.center(index % width, index / width);
+                walls.iter().any(|(wall_center, rotation, rect)| {
+                    rect.contains(*rotation * (center - *wall_center))
+                }) || polygons
+                    .iter()
+                    .any(|points| polygon::contains(points, center))
+                    || tracks
+                        .iter()
+                        .any(|track| track::contains(track, center - Vec2::from(track.center)))
+            })
+            .collect();
+        let mut queue = VecDeque::new();
+        for index in 0..width * height {
+            if target.contains(field.center(index % width, index / width)) {
+                field.distances[index] = Some(0);
+                queue.push_back(index);
+            }
+        }
+        while let Some(index) = queue.pop_front() {
+            let distance = field.distances[index].unwrap_or_default() + 1;
+            let (x, y) = (index % width, index / width);
+            let neighbours = [
+                (x > 0).then(|| index - 1),
+                (x + 1 < width).then(|| index + 1),
+                (y > 0).then(|| index - width),
+                (y + 1 < height).then(|| index + width),
+            ];
+            for next in neighbours.into_iter().flatten() {
+                if !blocked[next] && field.distances[next].is_none() {
+                    field.distances[next] = Some(distance);
+                    queue.push_back(next);
+                }
+            }
+        }
+        field
+    }
+
+    fn center(&self, x: usize, y: usize) -> Vec2 {
+        self.origin + (Vec2::new(x as f32, y as f32) + 0.5) * self.cell_size
+    }
+
+    /// Distance from `pos` to the target, in world units.
+    /// Falls back to a penalized straight line when `pos` is off the flooded cells.
+    pub fn distance(&self, pos: Vec2) -> f32 {
+        let cell = ((pos - self.origin) / self.cell_size).floor();
+        let flooded = (cell.x >= 0.0
+            && cell.y >= 0.0
+            && (cell.x as usize) < self.width
+            && (cell.y as usize) < self.height)
+            .then(|| self.distances[cell.y as usize * self.width + cell.x as usize])
+            .flatten();
+        match flooded {
+            Some(distance) => distance as f32 * self.cell_size,
+            None => {
+                let longest = (self.width + self.height) as f32 * self.cell_size;
+                longest + pos.distance(self.target)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        config::DEFAULT_SURFACE,
+        level::{my_vec2, MyRect, WallData},
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn test_field_goes_around_walls() {
+        let mut level = LevelData::with_spawn(Vec2::ZERO);
+        // a wall between the start and the target, open at the top
+        level.walls.insert(
+            Uuid::new_v4(),
+            WallData {
+                rect: MyRect::new(my_vec2(-1.0, -30.0), my_vec2(1.0, 10.0)),
+                angle: 0.0,
+                surface: DEFAULT_SURFACE.to_string(),
+            },
+        );
+        let target = Rect::from_center_size(Vec2::new(10.0, 0.0), Vec2::splat(2.0));
+        let field = DistanceField::new(&level, target, 1.0);
+        let start = Vec2::new(-10.0, 0.0);
+        // has to climb over the wall, 10 units up and back down
+        assert!(field.distance(start) > start.distance(target.center()) + 10.0);
+        assert!(field.distance(Vec2::new(10.0, 0.0)) < 1.0);
+    }
+}
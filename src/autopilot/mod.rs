@@ -0,0 +1,108 @@
+//! An autopilot that proves a level can be beaten.
+//!
+//! The level and the robot are rebuilt in a headless rapier world ([`sim`]), then a beam
+//! search over lean/jump inputs ([`search`]) drives the robot from one checkpoint to the next
+//! on a fixed timestep. This runs without a bevy app, only the math types of bevy are used, so
+//! it can be used from the `autopilot` binary as well as from the in-game bot.
+
+use std::{fmt, time::Duration};
+
+use uuid::Uuid;
+
+use crate::data::{config::GameConfig, level::LevelData};
+
+pub mod field;
+pub mod route;
+pub mod search;
+pub mod sim;
+
+pub use route::{level_route, Waypoint, WaypointKind};
+pub use search::{plan_segment, SegmentPlan};
+pub use sim::{BotAction, Sim, BOT_DT};
+
+#[derive(Debug, Clone)]
+pub struct SegmentReport {
+    pub from: String,
+    pub to: String,
+    pub success: bool,
+    pub time: Duration,
+    pub limbs_lost: u8,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BotReport {
+    pub segments: Vec<SegmentReport>,
+}
+
+impl BotReport {
+    pub fn success(&self) -> bool {
+        !self.segments.is_empty() && self.segments.iter().all(|s| s.success)
+    }
+}
+
+impl fmt::Display for SegmentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<4} {} -> {}  time: {:.2}s  limbs lost: {}",
+            if self.success { "ok" } else { "FAIL" },
+            self.from,
+            self.to,
+            self.time.as_secs_f32(),
+            self.limbs_lost
+        )
+    }
+}
+
+impl fmt::Display for BotReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            writeln!(f, "{}", segment)?;
+        }
+        let passed = self.segments.iter().filter(|s| s.success).count();
+        write!(f, "{}/{} segments completed", passed, self.segments.len())
+    }
+}
+
+/// Runs the bot over every segment of the level, in route order.
+/// Limbs lost in a segment are carried over to the next one, like a real run.
+pub fn run_level(level: &LevelData, config: &GameConfig) -> BotReport {
+    let route = level_route(level);
+    let mut report = BotReport::default();
+    let mut lost = (false, false);
+    for pair in route.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        let sim = Sim::new(level, config, from.spawn_pos(), lost);
+        let plan = plan_segment(level, &sim, to.target_rect(config), config);
+        report.segments.push(SegmentReport {
+            from: from.label(),
+            to: to.label(),
+            success: plan.reached,
+            time: plan.duration(),
+            limbs_lost: plan.limbs_lost(lost),
+        });
+        if plan.reached {
+            lost = plan.lost;
+        }
+    }
+    report
+}
+
+/// The kinds of objects of `level` that [`sim`] leaves out. Routes planned through a level
+/// using them can't be trusted.
+pub fn unsupported_objects(level: &LevelData) -> Vec<&'static str> {
+    [
+        ("moving platforms", level.platforms.is_empty()),
+        ("doors", level.doors.is_empty()),
+        ("repair kits", level.repair_kits.is_empty()),
+    ]
+    .into_iter()
+    .filter(|(_, empty)| !empty)
+    .map(|(name, _)| name)
+    .collect()
+}
+
+/// Short label used in reports, the first block of a uuid is plenty to find it in a level file.
+pub(crate) fn short_uuid(uuid: &Uuid) -> String {
+    uuid.to_string()[..8].to_string()
+}
@@ -0,0 +1,137 @@
+//! The order in which the bot visits checkpoints.
+
+use bevy::{math::vec2, prelude::*};
+use uuid::Uuid;
+
+use crate::data::{config::GameConfig, level::LevelData};
+
+use super::short_uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaypointKind {
+    Spawn,
+    Checkpoint(Uuid),
+    Goal(Uuid),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Waypoint {
+    pub kind: WaypointKind,
+    pub pos: Vec2,
+}
+
+impl Waypoint {
+    /// Where the robot appears when starting from this waypoint, mirrors respawning at a
+    /// checkpoint.
+    pub fn spawn_pos(&self) -> Vec2 {
+        match self.kind {
+            WaypointKind::Spawn => self.pos,
+            WaypointKind::Checkpoint(_) | WaypointKind::Goal(_) => self.pos + vec2(0.0, 1.0),
+        }
+    }
+
+    /// The area the torso has to touch for this waypoint to be reached.
+    pub fn target_rect(&self, config: &GameConfig) -> Rect {
+        match self.kind {
+            WaypointKind::Spawn => Rect::from_center_size(self.pos, Vec2::splat(2.0)),
+            WaypointKind::Checkpoint(_) => Rect::from_center_size(
+                self.pos + Vec2::from(config.checkpoint.collider.pos),
+                config.checkpoint.collider.size.into(),
+            ),
+            WaypointKind::Goal(_) => Rect::from_center_size(
+                self.pos + Vec2::from(config.goal.collider.pos),
+                config.goal.collider.size.into(),
+            ),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self.kind {
+            WaypointKind::Spawn => "spawn".to_string(),
+            WaypointKind::Checkpoint(uuid) => format!("checkpoint {}", short_uuid(&uuid)),
+            WaypointKind::Goal(uuid) => format!("goal {}", short_uuid(&uuid)),
+        }
+    }
+}
+
+/// Spawn, then every checkpoint, then the goal.
+///
+/// Checkpoints are stored unordered, so the route hops to the closest checkpoint not visited
+/// yet. When there are several goals, the one closest to the last checkpoint ends the route.
+pub fn level_route(level: &LevelData) -> Vec<Waypoint> {
+    let spawn = Waypoint {
+        kind: WaypointKind::Spawn,
+        pos: level.player_spawn.into(),
+    };
+    let mut checkpoints: Vec<Waypoint> = level
+        .checkpoints
+        .iter()
+        .map(|(uuid, checkpoint)| Waypoint {
+            kind: WaypointKind::Checkpoint(*uuid),
+            pos: checkpoint.pos.into(),
+        })
+        .collect();
+    let goals = level.goals.iter().map(|(uuid, goal)| Waypoint {
+        kind: WaypointKind::Goal(*uuid),
+        pos: goal.pos.into(),
+    });
+
+    let mut route = vec![spawn];
+    while !checkpoints.is_empty() {
+        let last = route[route.len() - 1].pos;
+        let (index, _) = checkpoints
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.pos.distance(last).total_cmp(&b.pos.distance(last)))
+            .unwrap();
+        route.push(checkpoints.swap_remove(index));
+    }
+    let last = route[route.len() - 1].pos;
+    route.extend(goals.min_by(|a, b| a.pos.distance(last).total_cmp(&b.pos.distance(last))));
+    route
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::level::{my_vec2, CheckpointData, GoalData};
+
+    fn level() -> LevelData {
+        LevelData::with_spawn(Vec2::ZERO)
+    }
+
+    #[test]
+    fn test_route_visits_closest_checkpoint_first() {
+        let mut level = level();
+        level.goals.insert(
+            Uuid::new_v4(),
+            GoalData {
+                pos: my_vec2(-100.0, 0.0),
+            },
+        );
+        for x in [-10.0, -90.0, -50.0] {
+            level.checkpoints.insert(
+                Uuid::new_v4(),
+                CheckpointData {
+                    pos: my_vec2(x, 0.0),
+                },
+            );
+        }
+        let xs: Vec<f32> = level_route(&level).iter().map(|w| w.pos.x).collect();
+        assert_eq!(xs, vec![0.0, -10.0, -50.0, -90.0, -100.0]);
+    }
+
+    #[test]
+    fn test_route_without_goal() {
+        let mut level = level();
+        level.checkpoints.insert(
+            Uuid::new_v4(),
+            CheckpointData {
+                pos: my_vec2(5.0, 0.0),
+            },
+        );
+        let route = level_route(&level);
+        assert_eq!(route.len(), 2);
+        assert_eq!(route[0].kind, WaypointKind::Spawn);
+    }
+}
@@ -0,0 +1,170 @@
+//! Beam search over the robot inputs.
+//!
+//! Each searched input is held for `bot.action_steps` fixed steps. At every depth the best
+//! `bot.beam_width` states are kept, scored by their distance to the target around the walls
+//! ([`DistanceField`]) plus a penalty per lost limb. States are bucketed on a coarse grid so the
+//! beam doesn't fill up with copies of the same state, and cells reached at an earlier depth
+//! only get the slots left over, so the bot keeps exploring when the direct way is blocked.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::data::{config::GameConfig, level::LevelData};
+
+use super::{
+    field::DistanceField,
+    sim::{BotAction, Sim, BOT_DT},
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct SegmentPlan {
+    /// One action per fixed step.
+    pub actions: Vec<BotAction>,
+    pub reached: bool,
+    /// Lost (left, right) arms at the end of the plan.
+    pub lost: (bool, bool),
+}
+
+impl SegmentPlan {
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.actions.len() as f32 * BOT_DT)
+    }
+
+    /// Limbs lost during the plan, not counting the ones already missing at `start`.
+    pub fn limbs_lost(&self, start: (bool, bool)) -> u8 {
+        (self.lost.0 && !start.0) as u8 + (self.lost.1 && !start.1) as u8
+    }
+}
+
+#[derive(Clone)]
+struct Node {
+    sim: Sim,
+    /// Actions held for `action_steps` each, the first step may jump.
+    moves: Vec<BotAction>,
+    score: f32,
+}
+
+type Cell = (i32, i32, bool);
+
+fn cell(sim: &Sim, cell_size: f32) -> Cell {
+    let pos = (sim.wheel_pos() / cell_size).floor();
+    (pos.x as i32, pos.y as i32, sim.wheel_velocity().x >= 0.0)
+}
+
+fn expand(moves: &[BotAction], steps: usize) -> Vec<BotAction> {
+    moves
+        .iter()
+        .flat_map(|action| {
+            (0..steps).map(move |i| BotAction {
+                lean: action.lean,
+                jump: action.jump && i == 0,
+            })
+        })
+        .collect()
+}
+
+/// Searches inputs that bring the torso of `start` into `target`.
+/// When the target can't be reached in time, the plan that got the closest is returned.
+pub fn plan_segment(
+    level: &LevelData,
+    start: &Sim,
+    target: Rect,
+    config: &GameConfig,
+) -> SegmentPlan {
+    let bot = config.bot;
+    let field = DistanceField::new(level, target, bot.cell_size);
+    let start_lost = start.lost;
+    let score = |sim: &Sim| {
+        let lost = (sim.lost.0 && !start_lost.0) as u8 + (sim.lost.1 && !start_lost.1) as u8;
+        field.distance(sim.wheel_pos()) + lost as f32 * bot.limb_penalty
+    };
+    let max_depth = (bot.max_segment_time / (bot.action_steps as f32 * BOT_DT)).ceil() as usize;
+
+    let mut best = Node {
+        sim: start.clone(),
+        moves: vec![],
+        score: score(start),
+    };
+    let mut beam = vec![best.clone()];
+    let mut visited: HashSet<Cell> = HashSet::new();
+    visited.insert(cell(start, bot.cell_size));
+
+    for _ in 0..max_depth {
+        let mut children: Vec<(Cell, Node)> = vec![];
+        for node in &beam {
+            let on_ground = node.sim.on_ground();
+            for lean in [-1, 0, 1] {
+                for jump in [false, true] {
+                    if jump && !on_ground {
+                        continue;
+                    }
+                    let action = BotAction { lean, jump };
+                    let mut sim = node.sim.clone();
+                    let mut moves = node.moves.clone();
+                    moves.push(action);
+                    for i in 0..bot.action_steps {
+                        sim.step(BotAction {
+                            lean,
+                            jump: jump && i == 0,
+                        });
+                        if sim.dead {
+                            break;
+                        }
+                        if sim.torso_touches(target) {
+                            let mut actions = expand(&node.moves, bot.action_steps);
+                            actions.extend(expand(&[action], bot.action_steps).drain(..=i));
+                            return SegmentPlan {
+                                actions,
+                                reached: true,
+                                lost: sim.lost,
+                            };
+                        }
+                    }
+                    if sim.dead {
+                        continue;
+                    }
+                    let node = Node {
+                        score: score(&sim),
+                        sim,
+                        moves,
+                    };
+                    children.push((cell(&node.sim, bot.cell_size), node));
+                }
+            }
+        }
+        if children.is_empty() {
+            break;
+        }
+        children.sort_by(|a, b| a.1.score.total_cmp(&b.1.score));
+
+        let mut taken: HashSet<Cell> = HashSet::new();
+        let mut next = vec![];
+        let mut revisits = vec![];
+        for (key, node) in children {
+            if !taken.insert(key) {
+                continue;
+            }
+            if visited.contains(&key) {
+                revisits.push(node);
+            } else {
+                next.push(node);
+            }
+        }
+        next.extend(revisits);
+        next.truncate(bot.beam_width);
+        for node in &next {
+            visited.insert(cell(&node.sim, bot.cell_size));
+            if node.score < best.score {
+                best = node.clone();
+            }
+        }
+        beam = next;
+    }
+
+    SegmentPlan {
+        actions: expand(&best.moves, bot.action_steps),
+        reached: false,
+        lost: best.sim.lost,
+    }
+}
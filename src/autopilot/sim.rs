@@ -0,0 +1,571 @@
+//! A headless copy of the game physics.
+//!
+//! Bodies, joints, masses and collision groups mirror `spawn::player::on_spawn_player`,
+//! `spawn::wall::on_spawn_wall` and `spawn::track::on_spawn_track`, and [`Sim::step`] applies
+//! inputs the same way `spawn::player::calc_forces` does, switches loop halves like
+//! `spawn::track::switch_loop_halves` and checks hazards like `hazard::check_player_collision`.
+//! Keep them in sync when tuning the robot.
+//!
+//! Moving platforms are left out, their position depends on the game time the segment is played
+//! at, which isn't known when planning. Doors are left out for the same reason, they depend on
+//! the switches pressed before. Repair kits are left out too, plans only count on the arms the
+//! robot starts the segment with.
+
+use std::{f32::consts::PI, sync::Mutex};
+
+use bevy::{
+    math::vec2,
+    prelude::{default, Rect, Vec2},
+};
+use bevy_rapier2d::rapier::{
+    parry::{query::intersection_test, shape::Cuboid},
+    prelude::*,
+};
+
+use crate::{
+    data::{
+        config::{GameConfig, SurfaceConfig, DEFAULT_SURFACE},
+        level::{LevelData, TrackData},
+    },
+    game::physics::ObjectGroup,
+    polygon::triangulate,
+    track::{loop_half_range, next_loop_half, outline, LoopHalf},
+};
+
+/// The fixed timestep the bot simulates and drives the game at.
+pub const BOT_DT: f32 = 1.0 / 60.0;
+
+/// One step worth of input, the same inputs a player has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BotAction {
+    /// -1 leans left, 1 leans right.
+    pub lean: i8,
+    /// Jump on this step, only has an effect when the wheel touches the ground.
+    pub jump: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SimArm {
+    collider: ColliderHandle,
+    joint: Option<ImpulseJointHandle>,
+    left: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SimPlayer {
+    wheel: RigidBodyHandle,
+    wheel_collider: ColliderHandle,
+    tube: RigidBodyHandle,
+    torso: RigidBodyHandle,
+    torso_collider: ColliderHandle,
+    arms: [Option<SimArm>; 2],
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SimLoop {
+    track: TrackData,
+    entry: ColliderHandle,
+    exit: ColliderHandle,
+    half: LoopHalf,
+}
+
+/// Records the strongest contact force seen by each collider during a step.
+#[derive(Default)]
+struct ForceCollector(Mutex<Vec<(ColliderHandle, ColliderHandle, f32)>>);
+
+impl EventHandler for ForceCollector {
+    fn handle_collision_event(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _event: CollisionEvent,
+        _contact_pair: Option<&ContactPair>,
+    ) {
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        dt: Real,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        contact_pair: &ContactPair,
+        total_force_magnitude: Real,
+    ) {
+        let event = ContactForceEvent::from_contact_pair(dt, contact_pair, total_force_magnitude);
+        if let Ok(mut forces) = self.0.lock() {
+            forces.push((event.collider1, event.collider2, event.max_force_magnitude));
+        }
+    }
+}
+
+pub struct Sim {
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    ccd_solver: CCDSolver,
+    pipeline: PhysicsPipeline,
+    params: IntegrationParameters,
+    gravity: Vector<Real>,
+    player: SimPlayer,
+    loops: Vec<SimLoop>,
+    /// Collider rects of the hazards.
+    hazards: Vec<Rect>,
+    config: GameConfig,
+    /// Lost (left, right) arms.
+    pub lost: (bool, bool),
+    pub dead: bool,
+    pub steps: usize,
+}
+
+impl Clone for Sim {
+    fn clone(&self) -> Self {
+        Self {
+            bodies: self.bodies.clone(),
+            colliders: self.colliders.clone(),
+            impulse_joints: self.impulse_joints.clone(),
+            multibody_joints: self.multibody_joints.clone(),
+            islands: self.islands.clone(),
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            ccd_solver: CCDSolver::new(),
+            pipeline: PhysicsPipeline::new(),
+            params: self.params,
+            gravity: self.gravity,
+            player: self.player,
+            loops: self.loops.clone(),
+            hazards: self.hazards.clone(),
+            config: self.config.clone(),
+            lost: self.lost,
+            dead: self.dead,
+            steps: self.steps,
+        }
+    }
+}
+
+fn groups(members: u32, filters: u32) -> InteractionGroups {
+    InteractionGroups::new(
+        Group::from_bits_retain(members),
+        Group::from_bits_retain(filters),
+    )
+}
+
+/// Same combine rules as `spawn::wall::surface_physics`.
+fn with_surface(collider: ColliderBuilder, surface: &SurfaceConfig) -> ColliderBuilder {
+    collider
+        .friction(surface.friction)
+        .friction_combine_rule(CoefficientCombineRule::Multiply)
+        .restitution(surface.restitution)
+        .restitution_combine_rule(CoefficientCombineRule::Max)
+}
+
+fn vector(v: Vec2) -> Vector<Real> {
+    vector![v.x, v.y]
+}
+
+/// Tracks have the default surface.
+fn track_collider(track: &TrackData, from: f32, to: f32, config: &GameConfig) -> ColliderBuilder {
+    let points: Vec<_> = outline(track, from, to)
+        .into_iter()
+        .map(|p| point![p.x, p.y])
+        .collect();
+    let len = points.len() as u32;
+    let indices = (0..len).map(|i| [i, (i + 1) % len]).collect();
+    with_surface(
+        ColliderBuilder::polyline(points, Some(indices)).translation(vector(track.center.into())),
+        config.surface(DEFAULT_SURFACE),
+    )
+}
+
+fn loop_half_groups(half: LoopHalf, active: LoopHalf) -> InteractionGroups {
+    if half == active {
+        groups(ObjectGroup::WALL, Group::all().bits())
+    } else {
+        groups(ObjectGroup::WALL, 0)
+    }
+}
+
+impl Sim {
+    /// Builds the level geometry and spawns the robot at `location`.
+    pub fn new(level: &LevelData, config: &GameConfig, location: Vec2, lost: (bool, bool)) -> Self {
+        let mut sim = Self {
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            islands: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            ccd_solver: CCDSolver::new(),
+            pipeline: PhysicsPipeline::new(),
+            params: IntegrationParameters {
+                dt: BOT_DT,
+                ..default()
+            },
+            gravity: vector![0.0, -9.81],
+            player: SimPlayer {
+                wheel: RigidBodyHandle::invalid(),
+                wheel_collider: ColliderHandle::invalid(),
+                tube: RigidBodyHandle::invalid(),
+                torso: RigidBodyHandle::invalid(),
+                torso_collider: ColliderHandle::invalid(),
+                arms: [None, None],
+            },
+            loops: vec![],
+            hazards: level
+                .hazards
+                .values()
+                .map(|hazard| config.hazard(hazard.kind).collider_rect(hazard.pos.into()))
+                .collect(),
+            config: config.clone(),
+            lost,
+            dead: false,
+            steps: 0,
+        };
+        for wall in level.walls.values() {
+            let rect: Rect = wall.rect.into();
+            sim.colliders.insert(with_surface(
+                ColliderBuilder::cuboid(rect.width() / 2.0, rect.height() / 2.0)
+                    .translation(vector(rect.center()))
+                    .rotation(wall.angle)
+                    .collision_groups(groups(ObjectGroup::WALL, Group::all().bits())),
+                config.surface(&wall.surface),
+            ));
+        }
+        for wall in level.polygon_walls.values() {
+            let points = wall.local_points();
+            let Some(triangles) = triangulate(&points) else {
+                continue;
+            };
+            let shapes = triangles
+                .iter()
+                .map(|[a, b, c]| {
+                    (
+                        Isometry::identity(),
+                        SharedShape::triangle(
+                            point![points[*a].x, points[*a].y],
+                            point![points[*b].x, points[*b].y],
+                            point![points[*c].x, points[*c].y],
+                        ),
+                    )
+                })
+                .collect();
+            sim.colliders.insert(with_surface(
+                ColliderBuilder::compound(shapes)
+                    .translation(vector(wall.pos.into()))
+                    .rotation(wall.angle)
+                    .collision_groups(groups(ObjectGroup::WALL, Group::all().bits())),
+                config.surface(&wall.surface),
+            ));
+        }
+        for track in level.tracks.values() {
+            if track.is_loop() {
+                let [entry, exit] = [LoopHalf::Entry, LoopHalf::Exit].map(|half| {
+                    let (from, to) = loop_half_range(half);
+                    sim.colliders.insert(
+                        track_collider(track, from, to, config)
+                            .collision_groups(loop_half_groups(half, LoopHalf::Entry)),
+                    )
+                });
+                sim.loops.push(SimLoop {
+                    track: *track,
+                    entry,
+                    exit,
+                    half: LoopHalf::Entry,
+                });
+            } else {
+                sim.colliders.insert(
+                    track_collider(track, 0.0, 1.0, config)
+                        .collision_groups(groups(ObjectGroup::WALL, Group::all().bits())),
+                );
+            }
+        }
+        sim.spawn_player(location);
+        sim
+    }
+
+    fn spawn_player(&mut self, location: Vec2) {
+        let config = &self.config;
+        let wall_only = groups(ObjectGroup::PLAYER, ObjectGroup::WALL);
+
+        let wheel = self.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(vector(location))
+                .linear_damping(config.wheel.linear_damping)
+                .angular_damping(config.wheel.angular_damping),
+        );
+        let wheel_collider = self.colliders.insert_with_parent(
+            ColliderBuilder::ball(1.0)
+                .friction(1.0)
+                .collision_groups(groups(
+                    ObjectGroup::PLAYER + ObjectGroup::WHEEL,
+                    ObjectGroup::WALL,
+                )),
+            wheel,
+            &mut self.bodies,
+        );
+
+        let tube_length = config.tube.length;
+        let tube = self.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(vector(location + vec2(0.0, 1.0)))
+                .linear_damping(config.tube.linear_damping)
+                .angular_damping(config.tube.angular_damping),
+        );
+        self.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(0.1, tube_length / 2.0)
+                .mass(config.tube.mass)
+                .collision_groups(wall_only),
+            tube,
+            &mut self.bodies,
+        );
+        self.impulse_joints.insert(
+            wheel,
+            tube,
+            RevoluteJointBuilder::new()
+                .local_anchor1(point![0.0, 0.0])
+                .local_anchor2(point![0.0, -tube_length / 2.0]),
+            true,
+        );
+
+        let torso = self.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(vector(location + vec2(0.0, 1.0 + tube_length / 2.0)))
+                .gravity_scale(config.torso.gravity_scale),
+        );
+        let torso_collider = self.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(config.torso.width / 2.0, config.torso.height / 2.0)
+                .mass(config.torso.mass)
+                .collision_groups(wall_only)
+                .active_events(ActiveEvents::CONTACT_FORCE_EVENTS),
+            torso,
+            &mut self.bodies,
+        );
+        self.impulse_joints.insert(
+            tube,
+            torso,
+            FixedJointBuilder::new()
+                .local_anchor1(point![0.0, tube_length / 2.0])
+                .local_anchor2(point![0.0, 0.0]),
+            true,
+        );
+
+        let mut arms = [None, None];
+        for (index, (arm_config, left)) in [(config.arms.left, true), (config.arms.right, false)]
+            .into_iter()
+            .enumerate()
+        {
+            if (left && self.lost.0) || (!left && self.lost.1) {
+                continue;
+            }
+            let socket: Vec2 = arm_config.socket.point.into();
+            let body = self.bodies.insert(
+                RigidBodyBuilder::dynamic()
+                    .translation(vector(location + socket))
+                    .rotation(-PI / 2.0)
+                    .angular_damping(config.arms.angular_damping),
+            );
+            let collider = self.colliders.insert_with_parent(
+                ColliderBuilder::cuboid(config.arms.length / 2.0, config.arms.width / 2.0)
+                    .mass(config.arms.mass)
+                    .collision_groups(wall_only)
+                    .active_events(ActiveEvents::CONTACT_FORCE_EVENTS),
+                body,
+                &mut self.bodies,
+            );
+            let joint = self.impulse_joints.insert(
+                torso,
+                body,
+                RevoluteJointBuilder::new()
+                    .local_anchor1(point![socket.x, socket.y])
+                    .local_anchor2(point![-config.arms.length / 2.0, 0.0]),
+                true,
+            );
+            arms[index] = Some(SimArm {
+                collider,
+                joint: Some(joint),
+                left,
+            });
+        }
+
+        self.player = SimPlayer {
+            wheel,
+            wheel_collider,
+            tube,
+            torso,
+            torso_collider,
+            arms,
+        };
+    }
+
+    pub fn wheel_pos(&self) -> Vec2 {
+        let t = self.bodies[self.player.wheel].translation();
+        vec2(t.x, t.y)
+    }
+
+    pub fn wheel_velocity(&self) -> Vec2 {
+        let v = self.bodies[self.player.wheel].linvel();
+        vec2(v.x, v.y)
+    }
+
+    pub fn time(&self) -> f32 {
+        self.steps as f32 * BOT_DT
+    }
+
+    /// Same idea as `spawn::player::check_touch_ground`, using the contacts of the last step.
+    pub fn on_ground(&self) -> bool {
+        self.narrow_phase
+            .contact_pairs_with(self.player.wheel_collider)
+            .any(|pair| pair.has_any_active_contact)
+    }
+
+    /// Whether the torso overlaps `rect`, which is how checkpoints and goals are reached.
+    pub fn torso_touches(&self, rect: Rect) -> bool {
+        self.collider_touches(self.player.torso_collider, rect)
+    }
+
+    fn collider_touches(&self, handle: ColliderHandle, rect: Rect) -> bool {
+        let collider = &self.colliders[handle];
+        let target = Cuboid::new(vector(rect.half_size()));
+        let target_pos = Isometry::translation(rect.center().x, rect.center().y);
+        intersection_test(collider.position(), collider.shape(), &target_pos, &target)
+            .unwrap_or(false)
+    }
+
+    pub fn step(&mut self, action: BotAction) {
+        if self.dead {
+            return;
+        }
+        self.apply_action(action);
+        let collector = ForceCollector::default();
+        self.pipeline.step(
+            &self.gravity,
+            &self.params,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            None,
+            &(),
+            &collector,
+        );
+        self.steps += 1;
+        let forces = collector.0.into_inner().unwrap_or_default();
+        self.monitor_damage(&forces);
+        self.monitor_hazards();
+        self.switch_loop_halves();
+    }
+
+    fn switch_loop_halves(&mut self) {
+        let wheel = self.wheel_pos();
+        for sim_loop in &mut self.loops {
+            let local = wheel - Vec2::from(sim_loop.track.center);
+            let half = next_loop_half(&sim_loop.track, sim_loop.half, local);
+            if half == sim_loop.half {
+                continue;
+            }
+            sim_loop.half = half;
+            for (handle, collider_half) in [
+                (sim_loop.entry, LoopHalf::Entry),
+                (sim_loop.exit, LoopHalf::Exit),
+            ] {
+                self.colliders[handle].set_collision_groups(loop_half_groups(collider_half, half));
+            }
+        }
+    }
+
+    fn apply_action(&mut self, action: BotAction) {
+        let config = &self.config;
+        let torque_direction = -action.lean as f32;
+        let jump = if action.jump && self.on_ground() {
+            1.0
+        } else {
+            0.0
+        };
+        if torque_direction == 0.0 && jump == 0.0 {
+            return;
+        }
+        let wheel = &mut self.bodies[self.player.wheel];
+        wheel.apply_torque_impulse(torque_direction * config.wheel.torque_multiplier, true);
+        let tube = &mut self.bodies[self.player.tube];
+        let angvel = tube.angvel();
+        if angvel.signum() != torque_direction.signum()
+            || angvel.abs() < config.tube.max_angular_velocity
+        {
+            tube.apply_torque_impulse(torque_direction * config.tube.torque_multiplier, true);
+        }
+        for body in [self.player.wheel, self.player.tube, self.player.torso] {
+            let body = &mut self.bodies[body];
+            let linvel = *body.linvel() + vector![0.0, jump * config.jump_y_speed];
+            body.set_linvel(linvel, true);
+        }
+    }
+
+    /// Mirrors `hazard::check_player_collision`.
+    fn monitor_hazards(&mut self) {
+        let touches_hazard = |sim: &Self, handle: ColliderHandle| {
+            sim.hazards
+                .iter()
+                .any(|rect| sim.collider_touches(handle, *rect))
+        };
+        for index in 0..self.player.arms.len() {
+            let Some(arm) = self.player.arms[index] else {
+                continue;
+            };
+            let Some(joint) = arm.joint else {
+                continue;
+            };
+            if !touches_hazard(self, arm.collider) {
+                continue;
+            }
+            self.impulse_joints.remove(joint, true);
+            self.player.arms[index] = Some(SimArm { joint: None, ..arm });
+            if arm.left {
+                self.lost.0 = true;
+            } else {
+                self.lost.1 = true;
+            }
+        }
+        if touches_hazard(self, self.player.torso_collider)
+            || touches_hazard(self, self.player.wheel_collider)
+        {
+            self.dead = true;
+        }
+    }
+
+    /// Mirrors `spawn::player::monitor_damage_contacts`.
+    fn monitor_damage(&mut self, forces: &[(ColliderHandle, ColliderHandle, f32)]) {
+        for &(collider1, collider2, max_force) in forces {
+            let hit = |handle: ColliderHandle| handle == collider1 || handle == collider2;
+            if let Some(arm) = self
+                .player
+                .arms
+                .iter_mut()
+                .flatten()
+                .find(|arm| arm.joint.is_some() && hit(arm.collider))
+            {
+                if max_force > self.config.arms.detach_force {
+                    if let Some(joint) = arm.joint.take() {
+                        self.impulse_joints.remove(joint, true);
+                    }
+                    if arm.left {
+                        self.lost.0 = true;
+                    } else {
+                        self.lost.1 = true;
+                    }
+                }
+                continue;
+            }
+            if hit(self.player.torso_collider) && max_force > self.config.torso.death_force {
+                self.dead = true;
+            }
+        }
+    }
+}
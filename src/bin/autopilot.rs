@@ -0,0 +1,70 @@
+//! Plays a level headlessly and reports whether every segment can be completed.
+//!
+//! Usage: `autopilot <level.ron> [game.config.toml]`
+
+use std::{path::Path, process::ExitCode};
+
+use cycle_bot::{
+    autopilot::{run_level, unsupported_objects},
+    data::{config::GameConfig, level::LevelData},
+    LevelObjects,
+};
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(level_path) = args.next() else {
+        eprintln!("usage: autopilot <level.ron> [game.config.toml]");
+        return ExitCode::from(2);
+    };
+    let config_path = args
+        .next()
+        .unwrap_or_else(|| "assets/game.config.toml".to_string());
+
+    let config: GameConfig = match std::fs::read_to_string(&config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| toml::from_str(&s).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("could not load config {}: {}", config_path, e);
+            return ExitCode::from(2);
+        }
+    };
+    let mut level: LevelData = match std::fs::read_to_string(&level_path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| ron::from_str(&s).map_err(|e| e.to_string()))
+    {
+        Ok(level) => level,
+        Err(e) => {
+            eprintln!("could not load level {}: {}", level_path, e);
+            return ExitCode::from(2);
+        }
+    };
+    level.migrate();
+    // the level is an asset, its prefabs are next to it
+    let assets = Path::new(&level_path).parent().unwrap_or(Path::new(""));
+    match level.read_prefabs(assets) {
+        Ok(prefabs) => LevelObjects::default().expand_prefabs(&mut level, &prefabs),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    }
+    let unsupported = unsupported_objects(&level);
+    if !unsupported.is_empty() {
+        eprintln!(
+            "error: {} uses {}, the autopilot doesn't simulate them",
+            level_path,
+            unsupported.join(", ")
+        );
+        return ExitCode::from(2);
+    }
+
+    let report = run_level(&level, &config);
+    println!("{}", report);
+    if report.success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
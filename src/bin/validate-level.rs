@@ -0,0 +1,49 @@
+//! Checks a level file for structural problems without opening a window.
+//!
+//! Usage: `validate-level <level.ron>`
+
+use std::{path::Path, process::ExitCode};
+
+use cycle_bot::{
+    data::{level::LevelData, validate::validate_level},
+    LevelObjects,
+};
+
+fn main() -> ExitCode {
+    let Some(level_path) = std::env::args().nth(1) else {
+        eprintln!("usage: validate-level <level.ron>");
+        return ExitCode::from(2);
+    };
+    let mut level: LevelData = match std::fs::read_to_string(&level_path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| ron::from_str(&s).map_err(|e| e.to_string()))
+    {
+        Ok(level) => level,
+        Err(e) => {
+            eprintln!("could not load level {}: {}", level_path, e);
+            return ExitCode::from(2);
+        }
+    };
+    level.migrate();
+    // the level is an asset, its prefabs are next to it
+    let assets = Path::new(&level_path).parent().unwrap_or(Path::new(""));
+    match level.read_prefabs(assets) {
+        Ok(prefabs) => LevelObjects::default().expand_prefabs(&mut level, &prefabs),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    }
+
+    let issues = validate_level(&level);
+    for issue in &issues {
+        println!("error: {}", issue);
+    }
+    if issues.is_empty() {
+        println!("{}: ok", level_path);
+        ExitCode::SUCCESS
+    } else {
+        println!("{}: {} problem(s) found", level_path, issues.len());
+        ExitCode::FAILURE
+    }
+}
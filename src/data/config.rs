@@ -1,7 +1,9 @@
-use bevy::{math::vec2, prelude::*};
+use bevy::{math::vec2, prelude::*, utils::HashMap};
+
+use super::level::{HazardKind, SwitchKind};
 // use bevy_common_assets::toml::TomlAssetPlugin;
 
-#[derive(serde::Deserialize, Asset, TypePath, Resource, Clone, Copy)]
+#[derive(serde::Deserialize, Asset, TypePath, Resource, Clone)]
 pub struct GameConfig {
     pub wheel: WheelConfig,
     pub tube: TubeConfig,
@@ -13,16 +15,23 @@ pub struct GameConfig {
     pub arms: ArmsConfig,
     pub eyes: EyesConfig,
     pub editor: EditorConfig,
-    pub background: BackgroundConfig,
-    pub background2: BackgroundConfig,
+    /// Parallax layers, from back to front. Levels can replace them with their own.
+    pub backgrounds: Vec<BackgroundConfig>,
     pub wall: WallConfig,
+    pub surfaces: HashMap<String, SurfaceConfig>,
     pub checkpoint: CheckpointConfig,
     pub goal: GoalConfig,
-    pub space_tutorial: SpaceTutorialConfig,
-    pub arrow_tutorial: ArrowTutorialConfig,
+    pub hazards: HazardsConfig,
+    pub repair_kit: RepairKitConfig,
+    pub bolt: BoltConfig,
+    pub door: DoorConfig,
+    pub switches: SwitchesConfig,
+    pub sign: SignConfig,
     pub arrow: ArrowConfig,
     pub game_time: GameTimeConfig,
+    pub particles: ParticlesConfig,
     pub audio: AudioConfig,
+    pub bot: BotConfig,
 }
 
 #[derive(serde::Deserialize, Resource, Clone, Copy)]
@@ -46,6 +55,32 @@ pub struct TubeConfig {
 pub struct CameraConfig {
     // pub playing_scale_divisor: f32,
     pub units_per_window_height: f32,
+    /// Seconds for the camera to get halfway to where it is going.
+    pub half_life: f32,
+    /// Seconds of the velocity of the wheel the camera looks ahead by.
+    pub look_ahead: f32,
+    /// Furthest the camera looks ahead of the wheel.
+    pub max_look_ahead: f32,
+    /// Speed of the wheel at which the camera is zoomed out the most.
+    pub zoom_speed: f32,
+    /// How much the camera zooms out at `zoom_speed`, 1.0 doesn't zoom.
+    pub max_zoom_out: f32,
+    /// Whether the screen shakes, until turned off in the pause menu.
+    pub shake: bool,
+    /// Offset and angle, in radians, of the camera shaking with a full trauma.
+    pub max_shake_offset: f32,
+    pub max_shake_angle: f32,
+    pub shake_frequency: f32,
+    /// Trauma lost per second, the shake is the square of the trauma.
+    pub trauma_decay: f32,
+    /// Contact force of a hit adding a full trauma.
+    pub impact_trauma_force: f32,
+    /// Hits softer than this don't shake the camera, rolling over bumps stays steady.
+    pub min_shake_force: f32,
+    /// Trauma added when the robot breaks.
+    pub death_trauma: f32,
+    /// Distance from the edges of a camera zone over which it blends in.
+    pub zone_blend: f32,
 }
 
 #[derive(serde::Deserialize, Clone, Copy)]
@@ -85,7 +120,7 @@ pub struct SocketConfig {
     pub point: PointConfig,
 }
 
-#[derive(serde::Deserialize, Clone, Copy)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default)]
 pub struct PointConfig {
     pub x: f32,
     pub y: f32,
@@ -115,6 +150,16 @@ pub struct HeadConfig {
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    /// Tilt of the torso from upright, in radians, past which the robot panics.
+    pub panic_tilt: f32,
+    /// Falling speed of the torso past which the robot panics.
+    pub panic_fall_speed: f32,
+    /// Seconds the robot looks hurt after losing an arm.
+    pub hurt_time: f32,
+    /// Seconds the robot looks happy after reaching a checkpoint.
+    pub happy_time: f32,
+    /// Tint of the head while hurt.
+    pub hurt_tint: ColorConfig,
 }
 #[derive(serde::Deserialize, Clone, Copy)]
 pub struct EyesConfig {
@@ -122,14 +167,41 @@ pub struct EyesConfig {
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    /// How far the eyes move toward the direction of travel.
+    pub look_distance: f32,
+    /// Speed at which the eyes are moved by the whole `look_distance`.
+    pub look_speed: f32,
+    /// Height of the eyes for each expression, relative to `height`.
+    pub focused: f32,
+    pub hurt: f32,
+    pub happy: f32,
+    /// Size of the eyes while panicking, relative to their size.
+    pub panic: f32,
 }
 
-#[derive(serde::Deserialize, Clone, Copy)]
+/// A tiled image covering the screen behind the level.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct BackgroundConfig {
-    pub scale_x: f32,
-    pub scale_y: f32,
-    pub parallax_x: f32,
-    pub parallax_y: f32,
+    /// Path of the image, relative to `assets`.
+    pub image: String,
+    /// Size of one tile of the image.
+    pub scale: PointConfig,
+    /// How much the image follows the level, 1 moves with it and 0 stays on screen.
+    pub parallax: PointConfig,
+    #[serde(default = "white")]
+    pub tint: ColorConfig,
+    pub z: f32,
+    /// Speed the image scrolls at on its own, in world units per second.
+    #[serde(default)]
+    pub velocity: PointConfig,
+}
+
+fn white() -> ColorConfig {
+    ColorConfig {
+        r: 1.0,
+        g: 1.0,
+        b: 1.0,
+    }
 }
 
 #[derive(serde::Deserialize, Clone, Copy)]
@@ -138,10 +210,71 @@ pub struct WallConfig {
     pub scale_y: f32,
 }
 
+/// Name of the surface walls use when they don't name one, or name one that doesn't exist.
+pub const DEFAULT_SURFACE: &str = "default";
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct SurfaceConfig {
+    /// Multiplied with the wheel friction.
+    pub friction: f32,
+    /// The bounciest of the wall and the robot part is used.
+    pub restitution: f32,
+    pub tint: ColorConfig,
+    /// Path of the tiled texture, relative to `assets`.
+    pub texture: String,
+    #[serde(default)]
+    pub sound: SurfaceSoundConfig,
+}
+
+/// How the wheel sounds rolling and landing on a surface.
+#[derive(serde::Deserialize, Clone, Copy, Debug)]
+pub struct SurfaceSoundConfig {
+    /// Playback speed of the sounds.
+    pub pitch: f32,
+    /// Multiplied with the volume of the sounds.
+    pub volume: f32,
+}
+
+impl Default for SurfaceSoundConfig {
+    fn default() -> Self {
+        Self {
+            pitch: 1.0,
+            volume: 1.0,
+        }
+    }
+}
+
+impl GameConfig {
+    /// The surface named `name`, or the default one.
+    pub fn surface(&self, name: &str) -> &SurfaceConfig {
+        self.surfaces
+            .get(name)
+            .or_else(|| self.surfaces.get(DEFAULT_SURFACE))
+            .expect("the config should have a default surface")
+    }
+
+    pub fn hazard(&self, kind: HazardKind) -> &HazardConfig {
+        match kind {
+            HazardKind::Spikes => &self.hazards.spikes,
+            HazardKind::Saw => &self.hazards.saw,
+            HazardKind::Lava => &self.hazards.lava,
+        }
+    }
+
+    pub fn switch(&self, kind: SwitchKind) -> &SwitchConfig {
+        match kind {
+            SwitchKind::Lever => &self.switches.lever,
+            SwitchKind::PressurePlate => &self.switches.pressure_plate,
+        }
+    }
+}
+
 #[derive(serde::Deserialize, Clone, Copy)]
 pub struct EditorConfig {
     pub camera_speed: f32,
     pub grid_size: f32,
+    /// Size in the level of one image pixel, for the new decorations.
+    pub decoration_pixel_size: f32,
 }
 
 #[derive(serde::Deserialize, Clone, Copy)]
@@ -158,13 +291,17 @@ pub struct ColliderConfig {
 }
 
 #[derive(serde::Deserialize, Clone, Copy)]
-pub struct SpaceTutorialConfig {
-    pub size: PointConfig,
-}
-
-#[derive(serde::Deserialize, Clone, Copy)]
-pub struct ArrowTutorialConfig {
-    pub size: PointConfig,
+pub struct SignConfig {
+    /// Signs fade in when the torso is closer than this to their center.
+    pub view_distance: f32,
+    /// Seconds to fade fully in or out.
+    pub fade_duration: f32,
+    /// Height of a line of text, in world units.
+    pub text_height: f32,
+    pub text_color: ColorConfig,
+    /// Behind text and prompts.
+    pub background_color: ColorConfig,
+    pub background_alpha: f32,
 }
 
 #[derive(serde::Deserialize, Clone, Copy)]
@@ -181,6 +318,64 @@ pub struct GoalConfig {
     pub collider: ColliderConfig,
 }
 
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct RepairKitConfig {
+    pub size: PointConfig,
+    pub collider: ColliderConfig,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct BoltConfig {
+    pub size: PointConfig,
+    pub collider: ColliderConfig,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct DoorConfig {
+    /// In units per second.
+    pub speed: f32,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct SwitchesConfig {
+    /// The least force the wheel has to push on the ground with to hold a plate down, the robot
+    /// resting on the wheel pushes with about 31.
+    pub plate_force: f32,
+    pub lever: SwitchConfig,
+    pub pressure_plate: SwitchConfig,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct SwitchConfig {
+    pub size: PointConfig,
+    /// Relative to the bottom center of the sprite.
+    pub collider: ColliderConfig,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct HazardsConfig {
+    pub spikes: HazardConfig,
+    pub saw: HazardConfig,
+    pub lava: HazardConfig,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct HazardConfig {
+    pub size: PointConfig,
+    /// Relative to the bottom center of the sprite.
+    pub collider: ColliderConfig,
+}
+
+impl HazardConfig {
+    /// The deadly area of a hazard placed at `pos`.
+    pub fn collider_rect(&self, pos: Vec2) -> Rect {
+        Rect::from_center_size(
+            pos + Vec2::from(self.collider.pos),
+            self.collider.size.into(),
+        )
+    }
+}
+
 #[derive(serde::Deserialize, Clone, Copy)]
 pub struct ArrowConfig {
     pub size: PointConfig,
@@ -192,17 +387,96 @@ pub struct GameTimeConfig {
 }
 
 #[derive(serde::Deserialize, Clone, Copy)]
+pub struct ParticlesConfig {
+    /// Fly from the contact point of the hits loud enough to clonk.
+    pub sparks: EmitterConfig,
+    /// Kicked up under the wheel while accelerating on the ground.
+    pub dust: EmitterConfig,
+    /// Burst out when an arm comes off or the torso breaks.
+    pub debris: EmitterConfig,
+    /// Seconds between two puffs of dust.
+    pub dust_interval: f32,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct EmitterConfig {
+    /// Particles emitted at once, scaled by the strength of what emits them.
+    pub count: u32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+    /// Angle around the direction of the emission the particles spread over, in radians.
+    pub spread: f32,
+    /// Seconds a particle lives, it shrinks and fades out meanwhile.
+    pub lifetime: f32,
+    pub size: f32,
+    /// Downward acceleration of the particles.
+    pub gravity: f32,
+    /// Fastest spin of the particles, in radians per second.
+    #[serde(default)]
+    pub spin: f32,
+    pub color: ColorConfig,
+    /// Draws the particles as bolts instead of squares of `color`.
+    #[serde(default)]
+    pub bolts: bool,
+}
+
+#[derive(serde::Deserialize, Clone)]
 pub struct AudioConfig {
+    /// Initial gains of the buses of the mixer.
+    pub master_volume: f32,
     pub soundtrack_volume: f32,
     pub sfx_volume: f32,
+    pub ui_volume: f32,
+    /// Gain of the music during the death and victory overlays.
+    pub duck: f32,
+    /// How fast the music gain moves toward or away from `duck`, per second.
+    pub duck_speed: f32,
+    /// Soundtrack of the levels that don't name one, from the soundtrack manifest.
+    pub soundtrack: String,
+    /// Volume of the ambient loop, relative to the soundtrack.
+    pub ambient: f32,
+    /// Seconds for a soundtrack to fade out while the next one fades in.
+    pub crossfade: f32,
+    /// Distance from the camera at which world sounds start to fade.
+    pub spatial_distance: f32,
+    /// Distance between the ears of the camera, sounds further than that to a side are heard
+    /// from that side only.
+    pub ear_gap: f32,
+    /// Contact force at which an impact sound is at full volume.
+    pub impact_force: f32,
+    /// Radians the wheel turns on the ground between two rolling steps.
+    pub roll_step: f32,
+    /// Angular speed of the wheel at which the rolling steps are at full volume.
+    pub roll_speed: f32,
+    /// Falling speed at which the landing thump is at full volume.
+    pub landing_speed: f32,
+    /// Landings quieter than that, from 0 to 1, make no sound.
+    pub min_landing: f32,
+    /// Intensity of the adaptive music added by each checkpoint reached, the drums come in
+    /// first and the lead once it reaches 0.5.
+    pub checkpoint_intensity: f32,
+    /// Seconds in the air after which the drums and the lead drop out.
+    pub airborne_mute: f32,
+    /// Soundtrack of the manifest played once when an arm is lost.
+    pub limb_sting: Option<String>,
     pub engine: f32,
     pub engine_acc: f32,
     pub engine_dec: f32,
     pub button: f32,
     pub jump: f32,
     pub clonk: f32,
+    pub step: f32,
 }
 #[derive(serde::Deserialize, Clone, Copy)]
+pub struct BotConfig {
+    pub beam_width: usize,
+    pub action_steps: usize,
+    pub max_segment_time: f32,
+    pub cell_size: f32,
+    pub limb_penalty: f32,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug)]
 pub struct ColorConfig {
     pub r: f32,
     pub g: f32,
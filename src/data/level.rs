@@ -1,26 +1,173 @@
+use std::path::Path;
+
 use bevy::{math::vec2, prelude::*, utils::HashMap};
 use uuid::Uuid;
 
-#[derive(serde::Deserialize, serde::Serialize, Asset, Resource, Clone, TypePath, Debug)]
+use super::config::{BackgroundConfig, DEFAULT_SURFACE};
+
+/// Asset path of the level, also the key of its [`super::records::Records`].
+pub const LEVEL_PATH: &str = "game.level.ron";
+
+#[derive(
+    serde::Deserialize, serde::Serialize, Asset, Resource, Clone, TypePath, Debug, Default,
+)]
 pub struct LevelData {
     pub walls: HashMap<Uuid, WallData>,
+    #[serde(default)]
+    pub polygon_walls: HashMap<Uuid, PolygonWallData>,
+    #[serde(default)]
+    pub tracks: HashMap<Uuid, TrackData>,
+    #[serde(default)]
+    pub platforms: HashMap<Uuid, PlatformData>,
+    #[serde(default)]
+    pub hazards: HashMap<Uuid, HazardData>,
+    #[serde(default)]
+    pub repair_kits: HashMap<Uuid, RepairKitData>,
+    #[serde(default)]
+    pub bolts: HashMap<Uuid, BoltData>,
+    #[serde(default)]
+    pub triggers: HashMap<Uuid, TriggerData>,
+    #[serde(default)]
+    pub doors: HashMap<Uuid, DoorData>,
+    #[serde(default)]
+    pub switches: HashMap<Uuid, SwitchData>,
+    #[serde(default)]
+    pub signs: HashMap<Uuid, SignData>,
+    #[serde(default)]
+    pub decorations: HashMap<Uuid, DecorationData>,
+    #[serde(default)]
+    pub prefabs: HashMap<Uuid, PrefabInstanceData>,
+    /// The objects spawned from the prefab instances, by the uuid of their instance. They are
+    /// saved in their prefab rather than in the level.
+    #[serde(skip)]
+    pub prefab_objects: HashMap<Uuid, Uuid>,
     pub checkpoints: HashMap<Uuid, CheckpointData>,
-    pub arrow_tutorials: HashMap<Uuid, ArrowTutorialData>,
-    pub space_tutorials: HashMap<Uuid, SpaceTutorialData>,
+    /// Tutorials from before signs, turned into signs by [`LevelData::migrate`].
+    #[serde(default, skip_serializing)]
+    arrow_tutorials: HashMap<Uuid, TutorialData>,
+    #[serde(default, skip_serializing)]
+    space_tutorials: HashMap<Uuid, TutorialData>,
     pub goals: HashMap<Uuid, GoalData>,
     pub arrows: HashMap<Uuid, ArrowData>,
     pub player_spawn: MyVec2,
+    /// Replaces the parallax layers of the config.
+    #[serde(default)]
+    pub backgrounds: Option<Vec<BackgroundConfig>>,
+    /// Name of the soundtrack in the soundtrack manifest, replaces the one of the config.
+    #[serde(default)]
+    pub soundtrack: Option<String>,
+    /// Name of a loop played under the soundtrack, from the soundtrack manifest.
+    #[serde(default)]
+    pub ambient: Option<String>,
+    /// Soundtrack played once the goal is reached, the level soundtrack keeps playing if there
+    /// is none.
+    #[serde(default)]
+    pub victory_soundtrack: Option<String>,
+    /// Areas where the camera stops simply following the robot.
+    #[serde(default)]
+    pub camera_zones: HashMap<Uuid, CameraZoneData>,
+    /// The camera doesn't show beyond this rect, if the level has one.
+    #[serde(default)]
+    pub bounds: Option<MyRect>,
 }
 
 impl LevelData {
+    /// Upgrades a level written by an older version of the game, call it after loading.
+    pub fn migrate(&mut self) {
+        let tutorials = [
+            (&mut self.arrow_tutorials, SignImage::ArrowTutorial),
+            (&mut self.space_tutorials, SignImage::SpaceTutorial),
+        ];
+        for (tutorials, image) in tutorials {
+            for (uuid, tutorial) in tutorials.drain() {
+                self.signs.insert(
+                    uuid,
+                    SignData {
+                        pos: tutorial.pos,
+                        size: image.native_size().into(),
+                        content: SignContent::Image(image),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Reads the prefabs the instances of the level use from the `assets` directory, for the
+    /// tools working on a level file. The game loads them as assets.
+    pub fn read_prefabs(&self, assets: &Path) -> Result<HashMap<String, LevelData>, String> {
+        let mut prefabs = HashMap::new();
+        for instance in self.prefabs.values() {
+            if prefabs.contains_key(&instance.prefab) {
+                continue;
+            }
+            let path = assets.join(prefab_path(&instance.prefab));
+            let prefab: LevelData = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|s| ron::from_str(&s).map_err(|e| e.to_string()))
+                .map_err(|e| format!("could not load prefab {}: {}", path.display(), e))?;
+            if !prefab.prefabs.is_empty() {
+                return Err(format!(
+                    "prefab {} holds prefab instances, prefabs can't be nested",
+                    path.display()
+                ));
+            }
+            prefabs.insert(instance.prefab.clone(), prefab);
+        }
+        Ok(prefabs)
+    }
+
+    /// An empty level for tests, the robot spawns at `player_spawn`.
+    #[cfg(test)]
+    pub fn with_spawn(player_spawn: Vec2) -> Self {
+        Self {
+            player_spawn: player_spawn.into(),
+            ..default()
+        }
+    }
+
     #[cfg(feature = "dev")]
     pub fn save(&self) {
         info!("Saving level data");
+        self.write(LEVEL_PATH);
+    }
+
+    /// Writes the objects of a prefab, relative to its origin.
+    #[cfg(feature = "dev")]
+    pub fn save_prefab(&self, name: &str) {
+        info!("Saving prefab {name}");
+        let path = prefab_path(name);
+        if let Some(dir) = std::path::Path::new("assets").join(&path).parent() {
+            std::fs::create_dir_all(dir).expect("Unable to create the prefab directory");
+        }
+        self.write(&path);
+    }
+
+    #[cfg(feature = "dev")]
+    fn write(&self, path: &str) {
         let s = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap();
-        std::fs::write("assets/game.level.ron", s).expect("Unable to write file");
+        std::fs::write(format!("assets/{path}"), s).expect("Unable to write file");
     }
 }
 
+/// Asset path of the prefab `name`. A prefab is a level holding only the objects of the prefab,
+/// placed relative to its origin.
+pub fn prefab_path(name: &str) -> String {
+    format!("prefabs/{name}.prefab.ron")
+}
+
+/// The uuid of the copy of the prefab object `object` made by the instance `instance`. It is the
+/// same every time the level is loaded, so links between the objects of a prefab still hold.
+pub fn prefab_object_id(instance: Uuid, object: Uuid) -> Uuid {
+    Uuid::new_v5(&instance, object.as_bytes())
+}
+
+/// A copy of the prefab `prefab`, with its origin at `pos`.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct PrefabInstanceData {
+    pub prefab: String,
+    pub pos: MyVec2,
+}
+
 #[derive(Resource, Debug)]
 pub struct LevelDataHandle(pub Handle<LevelData>);
 
@@ -34,26 +181,328 @@ pub struct ArrowData {
     pub angle: f32,
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct WallData {
     pub rect: MyRect,
+    /// Rotation around the rect center, in radians.
+    #[serde(default)]
+    pub angle: f32,
+    /// Name of one of the surfaces in the config.
+    #[serde(default = "default_surface")]
+    pub surface: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct PolygonWallData {
+    pub pos: MyVec2,
+    /// Points relative to `pos`, in either winding.
+    pub points: Vec<MyVec2>,
+    #[serde(default)]
+    pub angle: f32,
+    #[serde(default = "default_surface")]
+    pub surface: String,
+}
+
+fn default_surface() -> String {
+    DEFAULT_SURFACE.to_string()
+}
+
+impl PolygonWallData {
+    /// Points relative to `pos`, not rotated.
+    pub fn local_points(&self) -> Vec<Vec2> {
+        self.points.iter().map(|point| Vec2::from(*point)).collect()
+    }
+
+    pub fn world_points(&self) -> Vec<Vec2> {
+        let rotation = Rot2::radians(self.angle);
+        self.local_points()
+            .into_iter()
+            .map(|point| Vec2::from(self.pos) + rotation * point)
+            .collect()
+    }
 }
 
+/// A curved piece of track, the band between `radius` and `radius + thickness` around `center`.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct TrackData {
+    pub center: MyVec2,
+    /// Radius of the inner edge, the side the robot rides on inside a loop.
+    pub radius: f32,
+    /// In radians, counter-clockwise from the x axis. The track runs from `start_angle` to
+    /// `end_angle`, clockwise when `end_angle` is the smaller one.
+    pub start_angle: f32,
+    pub end_angle: f32,
+    pub thickness: f32,
+}
+
+impl TrackData {
+    /// Signed angle covered by the track, negative when it runs clockwise.
+    pub fn span(&self) -> f32 {
+        self.end_angle - self.start_angle
+    }
+
+    /// A track going most of the way around is ridden through instead of over or under, see
+    /// [`crate::track::next_loop_half`].
+    pub fn is_loop(&self) -> bool {
+        self.span().abs() >= 1.5 * std::f32::consts::PI
+    }
+}
+
+/// A wall moving along a path, see [`crate::platform::position`].
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct PlatformData {
+    pub size: MyVec2,
+    /// Positions of the platform center, it starts at the first one.
+    pub waypoints: Vec<MyVec2>,
+    /// Average speed in units per second.
+    pub speed: f32,
+    #[serde(default)]
+    pub easing: PlatformEasing,
+    #[serde(default)]
+    pub mode: PlatformMode,
+    #[serde(default = "default_surface")]
+    pub surface: String,
+}
+
+impl PlatformData {
+    pub fn points(&self) -> Vec<Vec2> {
+        self.waypoints
+            .iter()
+            .map(|point| Vec2::from(*point))
+            .collect()
+    }
+}
+
+/// How the speed changes between two waypoints.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlatformEasing {
+    #[default]
+    Linear,
+    /// Speeds up after a waypoint and slows down before the next one.
+    InOut,
+}
+
+/// What the platform does after the last waypoint.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlatformMode {
+    /// Goes straight back to the first waypoint.
+    #[default]
+    Loop,
+    /// Goes back through the waypoints in reverse.
+    PingPong,
+}
+
+/// Severs the arms and kills the robot on contact, however soft.
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug)]
-pub struct GoalData {
+pub struct HazardData {
+    pub kind: HazardKind,
+    /// Bottom center of the hazard.
     pub pos: MyVec2,
 }
 
+#[derive(
+    serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect,
+)]
+pub enum HazardKind {
+    Spikes,
+    Saw,
+    Lava,
+}
+
+/// Gives back a lost arm when the torso touches it.
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug)]
-pub struct ArrowTutorialData {
+pub struct RepairKitData {
+    /// Bottom center of the kit.
     pub pos: MyVec2,
 }
+
+/// Collectible, counted on the victory screen.
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug)]
-pub struct SpaceTutorialData {
+pub struct BoltData {
+    /// Bottom center of the bolt.
     pub pos: MyVec2,
 }
 
+/// An invisible area running actions when the torso enters or leaves it.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct TriggerData {
+    pub rect: MyRect,
+    #[serde(default)]
+    pub on_enter: Vec<TriggerAction>,
+    #[serde(default)]
+    pub on_exit: Vec<TriggerAction>,
+}
+
+impl TriggerData {
+    pub fn actions(&self) -> impl Iterator<Item = &TriggerAction> {
+        self.on_enter.iter().chain(&self.on_exit)
+    }
+}
+
+/// Overrides how the camera follows the robot while it is inside `rect`. The overrides blend
+/// in over the `zone_blend` distance of the camera config from the edges of the rect.
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug)]
+pub struct CameraZoneData {
+    pub rect: MyRect,
+    /// Frames this point instead of the robot, for a big jump.
+    #[serde(default)]
+    pub fixed: Option<MyVec2>,
+    /// Keeps the camera on this x, in a vertical shaft.
+    #[serde(default)]
+    pub lock_x: Option<f32>,
+    /// Keeps the camera on this y.
+    #[serde(default)]
+    pub lock_y: Option<f32>,
+    /// Replaces the zoom, above 1.0 shows more of the level.
+    #[serde(default)]
+    pub zoom: Option<f32>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub enum TriggerAction {
+    /// Shows `text` at the bottom of the screen for `seconds`.
+    Message {
+        text: String,
+        seconds: f32,
+    },
+    Sound(TriggerSound),
+    /// Looks at `pos` for `seconds` before following the robot again.
+    Camera {
+        pos: MyVec2,
+        seconds: f32,
+    },
+    /// Activates the checkpoint with this uuid.
+    Checkpoint(Uuid),
+    /// Opens the door with this uuid if it's closed, closes it otherwise.
+    Door(Uuid),
+    Victory,
+    /// Crossfades to the soundtrack of this name.
+    Music(String),
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriggerSound {
+    Beep,
+    Jump,
+    Clonk,
+}
+
+/// A wall sliding by `open_offset` when opened, see [`SwitchData`].
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct DoorData {
+    /// Where the door is when closed.
+    pub rect: MyRect,
+    pub open_offset: MyVec2,
+    /// Whether the door starts open.
+    #[serde(default)]
+    pub open: bool,
+    #[serde(default = "default_surface")]
+    pub surface: String,
+}
+
+/// Opens or closes the linked doors.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct SwitchData {
+    pub kind: SwitchKind,
+    /// Bottom center of the switch.
+    pub pos: MyVec2,
+    pub doors: Vec<Uuid>,
+}
+
+#[derive(
+    serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect,
+)]
+pub enum SwitchKind {
+    /// Toggles its doors each time the torso touches it.
+    Lever,
+    /// Toggles its doors while the wheel rests on it.
+    PressurePlate,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug)]
+pub struct GoalData {
+    pub pos: MyVec2,
+}
+
+/// A sign fading in when the robot comes near.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct SignData {
+    /// Center of the sign.
+    pub pos: MyVec2,
+    pub size: MyVec2,
+    pub content: SignContent,
+}
+
+/// An image drawn in the level, with no collider.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct DecorationData {
+    /// Path of the image, relative to `assets/images`.
+    pub image: String,
+    /// Center of the image.
+    pub pos: MyVec2,
+    /// Size of the image in the level, before rotation.
+    pub size: MyVec2,
+    #[serde(default)]
+    pub angle: f32,
+    #[serde(default)]
+    pub tint: MyColor,
+    #[serde(default)]
+    pub flip_x: bool,
+    #[serde(default)]
+    pub flip_y: bool,
+    #[serde(default)]
+    pub layer: DecorationLayer,
+    /// Drawing order within the layer, higher in front.
+    #[serde(default)]
+    pub order: i32,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecorationLayer {
+    /// Behind the walls and the robot.
+    #[default]
+    Background,
+    /// In front of everything but the UI.
+    Foreground,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub enum SignContent {
+    Text(String),
+    Image(SignImage),
+    /// The keys currently bound to an input.
+    Prompt(InputPrompt),
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum SignImage {
+    ArrowTutorial,
+    SpaceTutorial,
+}
+
+impl SignImage {
+    /// The size the image was drawn for, in world units.
+    pub fn native_size(self) -> Vec2 {
+        match self {
+            SignImage::ArrowTutorial => vec2(8.0, 4.0),
+            SignImage::SpaceTutorial => vec2(16.0, 4.0),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputPrompt {
+    Lean,
+    Jump,
+    Respawn,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, Debug)]
+struct TutorialData {
+    pos: MyVec2,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Default)]
 pub struct MyVec2 {
     x: f32,
     y: f32,
@@ -81,6 +530,32 @@ impl From<MyVec2> for Vec2 {
     }
 }
 
+/// A color with alpha, its components from 0 to 1.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct MyColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Default for MyColor {
+    fn default() -> Self {
+        Self {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 1.0,
+        }
+    }
+}
+
+impl From<MyColor> for Color {
+    fn from(value: MyColor) -> Self {
+        Color::srgba(value.r, value.g, value.b, value.a)
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug)]
 pub struct MyRect {
     min: MyVec2,
@@ -110,3 +585,57 @@ impl From<Rect> for MyRect {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_tutorials() {
+        let mut level: LevelData = ron::from_str(
+            r#"(walls: {}, checkpoints: {}, goals: {}, arrows: {}, player_spawn: (x: 0.0, y: 0.0),
+                arrow_tutorials: {"00000000-0000-0000-0000-000000000001": (pos: (x: 1.0, y: 2.0))},
+                space_tutorials: {"00000000-0000-0000-0000-000000000002": (pos: (x: 3.0, y: 4.0))})"#,
+        )
+        .unwrap();
+        level.migrate();
+        let sign = &level.signs[&Uuid::from_u128(1)];
+        assert_eq!(sign.content, SignContent::Image(SignImage::ArrowTutorial));
+        assert_eq!(Vec2::from(sign.pos), vec2(1.0, 2.0));
+        assert_eq!(
+            level.signs[&Uuid::from_u128(2)].content,
+            SignContent::Image(SignImage::SpaceTutorial)
+        );
+        // the tutorials are written back as signs
+        let saved = ron::to_string(&level).unwrap();
+        assert!(!saved.contains("tutorials"));
+    }
+
+    #[test]
+    fn test_decoration_defaults() {
+        let decoration: DecorationData =
+            ron::from_str(r#"(image: "ducky.png", pos: (x: 1.0, y: 2.0), size: (x: 3.0, y: 4.0))"#)
+                .unwrap();
+        assert_eq!(decoration.layer, DecorationLayer::Background);
+        assert_eq!(decoration.tint, MyColor::default());
+        assert!(!decoration.flip_x && !decoration.flip_y);
+        assert_eq!(decoration.order, 0);
+    }
+
+    #[test]
+    fn test_prefab_object_id() {
+        let instance = Uuid::from_u128(0xf0);
+        let a = prefab_object_id(instance, Uuid::from_u128(1));
+        assert_eq!(a, prefab_object_id(instance, Uuid::from_u128(1)));
+        assert_ne!(a, prefab_object_id(instance, Uuid::from_u128(2)));
+        assert_ne!(
+            a,
+            prefab_object_id(Uuid::from_u128(0xf1), Uuid::from_u128(1))
+        );
+        // xor-ing the two would give the same uuid for these
+        assert_ne!(
+            prefab_object_id(Uuid::from_u128(0xf0), Uuid::from_u128(0x0f)),
+            prefab_object_id(Uuid::from_u128(0x0f), Uuid::from_u128(0xf0))
+        );
+    }
+}
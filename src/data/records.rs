@@ -0,0 +1,84 @@
+//! Best results per level, kept between runs. Native builds write them to `records.ron` in the
+//! working directory, on the web they only last for the session.
+
+use bevy::{prelude::*, utils::HashMap};
+
+#[cfg(not(target_family = "wasm"))]
+const RECORDS_PATH: &str = "records.ron";
+
+#[derive(serde::Deserialize, serde::Serialize, Resource, Default, Debug, Clone, PartialEq)]
+pub struct Records {
+    /// Keyed by the level asset path.
+    pub levels: HashMap<String, LevelRecord>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelRecord {
+    /// Most bolts collected in a single run.
+    pub bolts: usize,
+}
+
+impl Records {
+    /// Starts from scratch when there is no records file or it can't be read.
+    pub fn load() -> Self {
+        #[cfg(not(target_family = "wasm"))]
+        if let Ok(s) = std::fs::read_to_string(RECORDS_PATH) {
+            match ron::from_str(&s) {
+                Ok(records) => return records,
+                Err(err) => warn!("Ignoring unreadable records: {err}"),
+            }
+        }
+        Self::default()
+    }
+
+    pub fn save(&self) {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let s = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .expect("Records serialize");
+            if let Err(err) = std::fs::write(RECORDS_PATH, s) {
+                warn!("Unable to save records: {err}");
+            }
+        }
+    }
+
+    pub fn best_bolts(&self, level: &str) -> usize {
+        self.levels.get(level).map_or(0, |record| record.bolts)
+    }
+
+    /// Keeps `bolts` if it beats the best for `level`, and tells whether it did.
+    pub fn record_bolts(&mut self, level: &str, bolts: usize) -> bool {
+        let record = self.levels.entry(level.to_string()).or_default();
+        if bolts > record.bolts {
+            record.bolts = bolts;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_best_bolt_count_per_level() {
+        let mut records = Records::default();
+        assert_eq!(records.best_bolts("a.level.ron"), 0);
+        assert!(records.record_bolts("a.level.ron", 3));
+        assert!(!records.record_bolts("a.level.ron", 2));
+        assert!(!records.record_bolts("a.level.ron", 3));
+        assert!(records.record_bolts("b.level.ron", 1));
+        assert_eq!(records.best_bolts("a.level.ron"), 3);
+        assert_eq!(records.best_bolts("b.level.ron"), 1);
+    }
+
+    #[test]
+    fn round_trips_through_ron() {
+        let mut records = Records::default();
+        records.record_bolts("game.level.ron", 12);
+        let s = ron::to_string(&records).unwrap();
+        assert_eq!(ron::from_str::<Records>(&s).unwrap(), records);
+    }
+}
@@ -0,0 +1,503 @@
+//! Structural checks on level data, used by the `validate-level` binary.
+
+use std::{f32::consts::TAU, fmt};
+
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use super::level::{LevelData, TriggerAction};
+use crate::{
+    polygon::{self, triangulate},
+    track,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelIssue {
+    /// No goal and no trigger ending the level.
+    NoGoal,
+    ZeroAreaWall(Uuid),
+    /// Fewer than 3 points, no area, or edges crossing each other.
+    InvalidPolygonWall(Uuid),
+    /// No radius, no thickness, or covering no angle or more than a full turn.
+    InvalidTrack(Uuid),
+    /// No area, no waypoints, or a negative speed.
+    InvalidPlatform(Uuid),
+    /// No area, or a zoom that isn't positive.
+    InvalidCameraZone(Uuid),
+    /// The player spawns outside of the level bounds.
+    SpawnOutOfBounds,
+    /// Two rect walls overlap, polygon walls and tracks aren't compared.
+    OverlappingWalls(Uuid, Uuid),
+    CheckpointInWall {
+        checkpoint: Uuid,
+        wall: Uuid,
+    },
+    GoalInWall {
+        goal: Uuid,
+        wall: Uuid,
+    },
+    SpawnInWall(Uuid),
+    /// A trigger activates a checkpoint that doesn't exist.
+    UnknownCheckpoint {
+        trigger: Uuid,
+        checkpoint: Uuid,
+    },
+    /// A switch or a trigger is linked to a door that doesn't exist.
+    UnknownDoor {
+        from: Uuid,
+        door: Uuid,
+    },
+    /// The same uuid is used in several maps.
+    DuplicateUuid {
+        uuid: Uuid,
+        maps: Vec<&'static str>,
+    },
+}
+
+impl fmt::Display for LevelIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelIssue::NoGoal => write!(f, "level has no goal"),
+            LevelIssue::ZeroAreaWall(wall) => write!(f, "wall {} has no area", wall),
+            LevelIssue::InvalidPolygonWall(wall) => {
+                write!(f, "polygon wall {} can't be triangulated", wall)
+            }
+            LevelIssue::InvalidTrack(track) => write!(f, "track {} has an invalid shape", track),
+            LevelIssue::InvalidPlatform(platform) => {
+                write!(f, "platform {} has an invalid size or path", platform)
+            }
+            LevelIssue::InvalidCameraZone(zone) => {
+                write!(f, "camera zone {} has an invalid size or zoom", zone)
+            }
+            LevelIssue::SpawnOutOfBounds => write!(f, "player spawn is out of the level bounds"),
+            LevelIssue::OverlappingWalls(a, b) => write!(f, "walls {} and {} overlap", a, b),
+            LevelIssue::CheckpointInWall { checkpoint, wall } => {
+                write!(f, "checkpoint {} is inside wall {}", checkpoint, wall)
+            }
+            LevelIssue::GoalInWall { goal, wall } => {
+                write!(f, "goal {} is inside wall {}", goal, wall)
+            }
+            LevelIssue::SpawnInWall(wall) => write!(f, "player spawn is inside wall {}", wall),
+            LevelIssue::UnknownCheckpoint {
+                trigger,
+                checkpoint,
+            } => write!(
+                f,
+                "trigger {} activates unknown checkpoint {}",
+                trigger, checkpoint
+            ),
+            LevelIssue::UnknownDoor { from, door } => {
+                write!(f, "{} is linked to unknown door {}", from, door)
+            }
+            LevelIssue::DuplicateUuid { uuid, maps } => {
+                write!(f, "uuid {} is used in {}", uuid, maps.join(", "))
+            }
+        }
+    }
+}
+
+/// The corners of a wall rect rotated by `angle` around its center, counter-clockwise.
+fn corners(rect: Rect, angle: f32) -> [Vec2; 4] {
+    let rotation = Rot2::radians(angle);
+    let half = rect.half_size();
+    [
+        Vec2::new(-half.x, -half.y),
+        Vec2::new(half.x, -half.y),
+        Vec2::new(half.x, half.y),
+        Vec2::new(-half.x, half.y),
+    ]
+    .map(|corner| rect.center() + rotation * corner)
+}
+
+/// Separating axis test between two convex quads, walls only touching don't overlap.
+fn overlap(a: &[Vec2; 4], b: &[Vec2; 4]) -> bool {
+    const EPSILON: f32 = 1e-4;
+    let edges = |quad: &[Vec2; 4]| [quad[1] - quad[0], quad[2] - quad[1]];
+    edges(a).into_iter().chain(edges(b)).all(|edge| {
+        let axis = edge.perp().normalize_or_zero();
+        let project = |quad: &[Vec2; 4]| {
+            quad.iter()
+                .map(|corner| corner.dot(axis))
+                .fold((f32::MAX, f32::MIN), |(min, max), d| {
+                    (min.min(d), max.max(d))
+                })
+        };
+        let ((min_a, max_a), (min_b, max_b)) = (project(a), project(b));
+        max_a.min(max_b) - min_a.max(min_b) > EPSILON
+    })
+}
+
+/// Returns every issue found in `level`, in a stable order.
+pub fn validate_level(level: &LevelData) -> Vec<LevelIssue> {
+    let mut issues = vec![];
+    let mut triggers: Vec<_> = level.triggers.iter().collect();
+    triggers.sort_by_key(|(uuid, _)| **uuid);
+    let victory_trigger = triggers.iter().any(|(_, trigger)| {
+        trigger
+            .actions()
+            .any(|action| *action == TriggerAction::Victory)
+    });
+    if level.goals.is_empty() && !victory_trigger {
+        issues.push(LevelIssue::NoGoal);
+    }
+
+    let mut walls: Vec<(Uuid, Rect, [Vec2; 4])> = level
+        .walls
+        .iter()
+        .map(|(uuid, wall)| {
+            let rect: Rect = wall.rect.into();
+            (*uuid, rect, corners(rect, wall.angle))
+        })
+        .collect();
+    walls.sort_by_key(|(uuid, _, _)| *uuid);
+    for (uuid, rect, _) in &walls {
+        if rect.is_empty() {
+            issues.push(LevelIssue::ZeroAreaWall(*uuid));
+        }
+    }
+    for (i, (a, _, corners_a)) in walls.iter().enumerate() {
+        for (b, _, corners_b) in &walls[i + 1..] {
+            if overlap(corners_a, corners_b) {
+                issues.push(LevelIssue::OverlappingWalls(*a, *b));
+            }
+        }
+    }
+    let mut polygon_walls: Vec<(Uuid, Vec<Vec2>)> = level
+        .polygon_walls
+        .iter()
+        .map(|(uuid, wall)| (*uuid, wall.world_points()))
+        .collect();
+    polygon_walls.sort_by_key(|(uuid, _)| *uuid);
+    for (uuid, points) in &polygon_walls {
+        if triangulate(points).is_none() {
+            issues.push(LevelIssue::InvalidPolygonWall(*uuid));
+        }
+    }
+
+    let mut tracks: Vec<_> = level.tracks.iter().collect();
+    tracks.sort_by_key(|(uuid, _)| **uuid);
+    for (uuid, track) in &tracks {
+        let span = track.span().abs();
+        if track.radius <= 0.0 || track.thickness <= 0.0 || span == 0.0 || span > TAU + 1e-4 {
+            issues.push(LevelIssue::InvalidTrack(**uuid));
+        }
+    }
+
+    let mut platforms: Vec<_> = level.platforms.iter().collect();
+    platforms.sort_by_key(|(uuid, _)| **uuid);
+    for (uuid, platform) in platforms {
+        let size = Vec2::from(platform.size);
+        if size.x <= 0.0 || size.y <= 0.0 || platform.waypoints.is_empty() || platform.speed < 0.0 {
+            issues.push(LevelIssue::InvalidPlatform(*uuid));
+        }
+    }
+
+    let mut camera_zones: Vec<_> = level.camera_zones.iter().collect();
+    camera_zones.sort_by_key(|(uuid, _)| **uuid);
+    for (uuid, zone) in camera_zones {
+        if zone.rect.to_rect().is_empty() || zone.zoom.is_some_and(|zoom| zoom <= 0.0) {
+            issues.push(LevelIssue::InvalidCameraZone(*uuid));
+        }
+    }
+    if let Some(bounds) = level.bounds {
+        if !bounds.to_rect().contains(level.player_spawn.into()) {
+            issues.push(LevelIssue::SpawnOutOfBounds);
+        }
+    }
+
+    // strict containment, a point on the edge of a wall is standing on it, not inside it
+    let wall_containing = |point: Vec2| {
+        walls
+            .iter()
+            .find(|(_, _, corners)| {
+                (0..4)
+                    .all(|i| (corners[(i + 1) % 4] - corners[i]).perp_dot(point - corners[i]) > 0.0)
+            })
+            .map(|(uuid, _, _)| *uuid)
+            .or_else(|| {
+                polygon_walls
+                    .iter()
+                    .find(|(_, points)| polygon::contains(points, point))
+                    .map(|(uuid, _)| *uuid)
+            })
+            .or_else(|| {
+                tracks
+                    .iter()
+                    .find(|(_, data)| {
+                        let local = point - Vec2::from(data.center);
+                        let distance = local.length();
+                        track::contains(data, local)
+                            && distance > data.radius
+                            && distance < data.radius + data.thickness
+                    })
+                    .map(|(uuid, _)| **uuid)
+            })
+    };
+    let mut checkpoints: Vec<_> = level.checkpoints.iter().collect();
+    checkpoints.sort_by_key(|(uuid, _)| **uuid);
+    for (uuid, checkpoint) in checkpoints {
+        if let Some(wall) = wall_containing(checkpoint.pos.into()) {
+            issues.push(LevelIssue::CheckpointInWall {
+                checkpoint: *uuid,
+                wall,
+            });
+        }
+    }
+    let mut goals: Vec<_> = level.goals.iter().collect();
+    goals.sort_by_key(|(uuid, _)| **uuid);
+    for (uuid, goal) in goals {
+        if let Some(wall) = wall_containing(goal.pos.into()) {
+            issues.push(LevelIssue::GoalInWall { goal: *uuid, wall });
+        }
+    }
+    if let Some(wall) = wall_containing(level.player_spawn.into()) {
+        issues.push(LevelIssue::SpawnInWall(wall));
+    }
+    for (uuid, trigger) in &triggers {
+        for action in trigger.actions() {
+            match action {
+                TriggerAction::Checkpoint(checkpoint)
+                    if !level.checkpoints.contains_key(checkpoint) =>
+                {
+                    issues.push(LevelIssue::UnknownCheckpoint {
+                        trigger: **uuid,
+                        checkpoint: *checkpoint,
+                    });
+                }
+                TriggerAction::Door(door) if !level.doors.contains_key(door) => {
+                    issues.push(LevelIssue::UnknownDoor {
+                        from: **uuid,
+                        door: *door,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut switches: Vec<_> = level.switches.iter().collect();
+    switches.sort_by_key(|(uuid, _)| **uuid);
+    for (uuid, switch) in switches {
+        for door in &switch.doors {
+            if !level.doors.contains_key(door) {
+                issues.push(LevelIssue::UnknownDoor {
+                    from: *uuid,
+                    door: *door,
+                });
+            }
+        }
+    }
+
+    let maps: [(&'static str, Vec<Uuid>); 17] = [
+        ("walls", level.walls.keys().copied().collect()),
+        (
+            "polygon_walls",
+            level.polygon_walls.keys().copied().collect(),
+        ),
+        ("tracks", level.tracks.keys().copied().collect()),
+        ("platforms", level.platforms.keys().copied().collect()),
+        ("hazards", level.hazards.keys().copied().collect()),
+        ("repair_kits", level.repair_kits.keys().copied().collect()),
+        ("bolts", level.bolts.keys().copied().collect()),
+        ("triggers", level.triggers.keys().copied().collect()),
+        ("doors", level.doors.keys().copied().collect()),
+        ("switches", level.switches.keys().copied().collect()),
+        ("signs", level.signs.keys().copied().collect()),
+        ("decorations", level.decorations.keys().copied().collect()),
+        ("prefabs", level.prefabs.keys().copied().collect()),
+        ("checkpoints", level.checkpoints.keys().copied().collect()),
+        ("goals", level.goals.keys().copied().collect()),
+        ("arrows", level.arrows.keys().copied().collect()),
+        ("camera_zones", level.camera_zones.keys().copied().collect()),
+    ];
+    let mut uses: Vec<(Uuid, &'static str)> = maps
+        .iter()
+        .flat_map(|(name, uuids)| uuids.iter().map(move |uuid| (*uuid, *name)))
+        .collect();
+    uses.sort();
+    for group in uses.chunk_by(|a, b| a.0 == b.0) {
+        if group.len() > 1 {
+            issues.push(LevelIssue::DuplicateUuid {
+                uuid: group[0].0,
+                maps: group.iter().map(|(_, name)| *name).collect(),
+            });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        config::DEFAULT_SURFACE,
+        level::{
+            my_vec2, CameraZoneData, CheckpointData, GoalData, MyRect, SwitchData, SwitchKind,
+            TriggerData, WallData,
+        },
+    };
+
+    fn level() -> LevelData {
+        LevelData::with_spawn(Vec2::new(0.0, 1.0))
+    }
+
+    fn wall(min: (f32, f32), max: (f32, f32)) -> WallData {
+        WallData {
+            rect: MyRect::new(my_vec2(min.0, min.1), my_vec2(max.0, max.1)),
+            angle: 0.0,
+            surface: DEFAULT_SURFACE.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_valid_level() {
+        let mut level = level();
+        level
+            .walls
+            .insert(Uuid::new_v4(), wall((-10.0, -1.0), (10.0, 0.0)));
+        level
+            .walls
+            .insert(Uuid::new_v4(), wall((10.0, -1.0), (20.0, 0.0)));
+        level.goals.insert(
+            Uuid::new_v4(),
+            GoalData {
+                pos: my_vec2(15.0, 0.0),
+            },
+        );
+        assert_eq!(validate_level(&level), vec![]);
+    }
+
+    #[test]
+    fn test_rotated_wall_overlap() {
+        let mut level = level();
+        level.goals.insert(
+            Uuid::new_v4(),
+            GoalData {
+                pos: my_vec2(50.0, 0.0),
+            },
+        );
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        level.walls.insert(a, wall((-10.0, -1.0), (10.0, 0.0)));
+        // 2 units away, only overlaps the floor once rotated
+        let mut ramp = wall((-5.0, 2.0), (5.0, 3.0));
+        level.walls.insert(b, ramp.clone());
+        assert_eq!(validate_level(&level), vec![]);
+        ramp.angle = 0.5;
+        level.walls.insert(b, ramp);
+        let (a, b) = if a < b { (a, b) } else { (b, a) };
+        assert_eq!(
+            validate_level(&level),
+            vec![LevelIssue::OverlappingWalls(a, b)]
+        );
+    }
+
+    #[test]
+    fn test_broken_level() {
+        let mut level = level();
+        let floor = Uuid::new_v4();
+        let checkpoint = Uuid::new_v4();
+        level.walls.insert(floor, wall((-10.0, -1.0), (10.0, 5.0)));
+        level.checkpoints.insert(
+            checkpoint,
+            CheckpointData {
+                pos: my_vec2(3.0, 3.0),
+            },
+        );
+        level.goals.insert(
+            floor,
+            GoalData {
+                pos: my_vec2(20.0, 0.0),
+            },
+        );
+        let issues = validate_level(&level);
+        assert!(issues.contains(&LevelIssue::CheckpointInWall {
+            checkpoint,
+            wall: floor
+        }));
+        assert!(issues.contains(&LevelIssue::SpawnInWall(floor)));
+        assert!(issues.contains(&LevelIssue::DuplicateUuid {
+            uuid: floor,
+            maps: vec!["goals", "walls"],
+        }));
+    }
+
+    #[test]
+    fn test_triggers() {
+        let mut level = level();
+        let (trigger, checkpoint) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut data = TriggerData {
+            rect: MyRect::new(my_vec2(10.0, 0.0), my_vec2(12.0, 4.0)),
+            on_enter: vec![TriggerAction::Victory],
+            on_exit: vec![],
+        };
+        level.triggers.insert(trigger, data.clone());
+        assert_eq!(validate_level(&level), vec![]);
+        data.on_enter = vec![TriggerAction::Checkpoint(checkpoint)];
+        level.triggers.insert(trigger, data);
+        assert_eq!(
+            validate_level(&level),
+            vec![
+                LevelIssue::NoGoal,
+                LevelIssue::UnknownCheckpoint {
+                    trigger,
+                    checkpoint
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_camera_zones() {
+        let mut level = level();
+        level.goals.insert(
+            Uuid::new_v4(),
+            GoalData {
+                pos: my_vec2(15.0, 0.0),
+            },
+        );
+        let zone = Uuid::new_v4();
+        let mut data = CameraZoneData {
+            rect: MyRect::new(my_vec2(5.0, 0.0), my_vec2(10.0, 20.0)),
+            fixed: None,
+            lock_x: Some(7.5),
+            lock_y: None,
+            zoom: Some(1.5),
+        };
+        level.camera_zones.insert(zone, data);
+        level.bounds = Some(MyRect::new(my_vec2(-5.0, -5.0), my_vec2(30.0, 30.0)));
+        assert_eq!(validate_level(&level), vec![]);
+        data.zoom = Some(0.0);
+        level.camera_zones.insert(zone, data);
+        level.bounds = Some(MyRect::new(my_vec2(5.0, 5.0), my_vec2(30.0, 30.0)));
+        assert_eq!(
+            validate_level(&level),
+            vec![
+                LevelIssue::InvalidCameraZone(zone),
+                LevelIssue::SpawnOutOfBounds
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_door() {
+        let mut level = level();
+        level.goals.insert(
+            Uuid::new_v4(),
+            GoalData {
+                pos: my_vec2(15.0, 0.0),
+            },
+        );
+        let (switch, door) = (Uuid::new_v4(), Uuid::new_v4());
+        level.switches.insert(
+            switch,
+            SwitchData {
+                kind: SwitchKind::Lever,
+                pos: my_vec2(5.0, 0.0),
+                doors: vec![door],
+            },
+        );
+        assert_eq!(
+            validate_level(&level),
+            vec![LevelIssue::UnknownDoor { from: switch, door }]
+        );
+    }
+}
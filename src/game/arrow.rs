@@ -1,19 +1,33 @@
-use std::time::Duration;
+use std::{f32::consts::TAU, time::Duration};
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use uuid::Uuid;
 
-use crate::{data::config::GameConfig, screen::Screen};
+use crate::{
+    data::{
+        config::GameConfig,
+        level::{ArrowData, LevelData},
+    },
+    screen::Screen,
+};
 
 use super::{
     assets::{HandleMap, ImageKey},
     atlas_animation::AtlasAnimation,
     entity_id::EntityId,
     entity_type::EntityType,
+    level_object::{LevelObject, ObjectKind, ObjectShape, RegisterLevelObject},
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.observe(on_spawn_arrow);
+    app.observe(on_spawn_arrow).register_level_object(
+        ObjectKind::<ArrowData>::new(ObjectShape::Sprite)
+            .rotatable()
+            .menu_entry("Arrow", |point| ArrowData {
+                pos: point.into(),
+                angle: 0.0,
+            }),
+    );
 }
 
 #[derive(Debug, Event)]
@@ -26,6 +40,36 @@ pub struct SpawnArrow {
 #[derive(Debug, Component)]
 pub struct Arrow;
 
+impl LevelObject for ArrowData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.arrows
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.arrows
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnArrow {
+            uuid,
+            pos: data.pos.into(),
+            angle: data.angle,
+        });
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+
+    fn rotate(&mut self, delta: f32) {
+        self.angle = (self.angle + delta).rem_euclid(TAU);
+    }
+
+    fn angle(&self) -> Option<f32> {
+        Some(self.angle)
+    }
+}
+
 pub fn on_spawn_arrow(
     trigger: Trigger<SpawnArrow>,
     mut cmd: Commands,
@@ -41,7 +85,7 @@ pub fn on_spawn_arrow(
     cmd.spawn((
         Arrow,
         EntityId(trigger.event().uuid),
-        EntityType::Arrow,
+        EntityType::of::<ArrowData>(),
         SpriteBundle {
             sprite: Sprite {
                 custom_size: Some(config.arrow.size.into()),
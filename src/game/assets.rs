@@ -3,6 +3,7 @@ use bevy::{
     render::texture::{ImageLoaderSettings, ImageSampler},
     utils::HashMap,
 };
+use bevy_common_assets::ron::RonAssetPlugin;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<HandleMap<ImageKey>>();
@@ -11,8 +12,9 @@ pub(super) fn plugin(app: &mut App) {
     app.register_type::<HandleMap<SfxKey>>();
     app.init_resource::<HandleMap<SfxKey>>();
 
+    // filled from the manifest during the loading screen
     app.register_type::<HandleMap<SoundtrackKey>>();
-    app.init_resource::<HandleMap<SoundtrackKey>>();
+    app.add_plugins(RonAssetPlugin::<SoundtrackManifest>::new(&["manifest.ron"]));
 
     app.register_type::<HandleMap<FontKey>>();
     app.init_resource::<HandleMap<FontKey>>();
@@ -23,14 +25,20 @@ pub enum ImageKey {
     Wheel,
     Torso,
     Head,
+    HeadPanic,
     Arm,
     Eyes,
     Wall,
-    Background,
-    Background2,
     CheckpointPost,
     CheckpointLight,
     Goal,
+    Spikes,
+    Saw,
+    Lava,
+    RepairKit,
+    Bolt,
+    Lever,
+    PressurePlate,
     SpaceTutorial,
     ArrowTutorial,
     ArrowSet,
@@ -62,6 +70,10 @@ impl FromWorld for HandleMap<ImageKey> {
                 ImageKey::Head,
                 asset_server.load_with_settings("images/head.png", nearest),
             ),
+            (
+                ImageKey::HeadPanic,
+                asset_server.load_with_settings("images/head_panic.png", nearest),
+            ),
             (
                 ImageKey::Arm,
                 asset_server.load_with_settings("images/arm.png", nearest),
@@ -74,14 +86,6 @@ impl FromWorld for HandleMap<ImageKey> {
                 ImageKey::Wall,
                 asset_server.load_with_settings("images/wall.png", nearest),
             ),
-            (
-                ImageKey::Background,
-                asset_server.load_with_settings("images/background.png", nearest),
-            ),
-            (
-                ImageKey::Background2,
-                asset_server.load_with_settings("images/background2.png", nearest),
-            ),
             (
                 ImageKey::CheckpointPost,
                 asset_server.load_with_settings("images/checkpoint_post.png", nearest),
@@ -94,6 +98,34 @@ impl FromWorld for HandleMap<ImageKey> {
                 ImageKey::Goal,
                 asset_server.load_with_settings("images/goal.png", nearest),
             ),
+            (
+                ImageKey::Spikes,
+                asset_server.load_with_settings("images/spikes.png", nearest),
+            ),
+            (
+                ImageKey::Saw,
+                asset_server.load_with_settings("images/saw.png", nearest),
+            ),
+            (
+                ImageKey::Lava,
+                asset_server.load_with_settings("images/lava.png", nearest),
+            ),
+            (
+                ImageKey::RepairKit,
+                asset_server.load_with_settings("images/repair_kit.png", nearest),
+            ),
+            (
+                ImageKey::Bolt,
+                asset_server.load_with_settings("images/bolt.png", nearest),
+            ),
+            (
+                ImageKey::Lever,
+                asset_server.load_with_settings("images/lever.png", nearest),
+            ),
+            (
+                ImageKey::PressurePlate,
+                asset_server.load_with_settings("images/pressure_plate.png", nearest),
+            ),
             (
                 ImageKey::SpaceTutorial,
                 asset_server.load_with_settings("images/space_tutorial.png", nearest),
@@ -164,23 +196,74 @@ impl FromWorld for HandleMap<SfxKey> {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
-pub enum SoundtrackKey {
-    MainSong,
-}
+/// A soundtrack, by its name in the [`SoundtrackManifest`].
+#[derive(Clone, Eq, PartialEq, Hash, Reflect, Component, Debug)]
+pub struct SoundtrackKey(pub String);
 
 impl AssetKey for SoundtrackKey {
     type Asset = AudioSource;
 }
 
-impl FromWorld for HandleMap<SoundtrackKey> {
-    fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
-        [(
-            SoundtrackKey::MainSong,
-            asset_server.load("audio/soundtracks/main_song.ogg"),
-        )]
-        .into()
+/// Asset path of the [`SoundtrackManifest`].
+pub const SOUNDTRACK_MANIFEST_PATH: &str = "audio/soundtracks.manifest.ron";
+
+/// The soundtracks and ambient loops levels can name, with their asset paths.
+#[derive(serde::Deserialize, Asset, TypePath, Debug)]
+pub struct SoundtrackManifest {
+    pub soundtracks: HashMap<String, String>,
+    /// Soundtracks split into stems, their layers follow the progress in the level.
+    #[serde(default)]
+    pub stems: HashMap<String, StemPaths>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct StemPaths {
+    pub base: String,
+    pub drums: String,
+    pub lead: String,
+}
+
+#[derive(Resource, Debug)]
+pub struct SoundtrackManifestHandle(pub Handle<SoundtrackManifest>);
+
+impl HandleMap<SoundtrackKey> {
+    /// Starts loading every soundtrack of the manifest.
+    pub fn from_manifest(manifest: &SoundtrackManifest, asset_server: &AssetServer) -> Self {
+        Self(
+            manifest
+                .soundtracks
+                .iter()
+                .map(|(name, path)| (SoundtrackKey(name.clone()), asset_server.load(path.clone())))
+                .collect(),
+        )
+    }
+}
+
+/// The stems of the soundtracks split into stems, in the order of `Stem::ALL`.
+#[derive(Resource, Debug)]
+pub struct SoundtrackStems(pub HashMap<SoundtrackKey, [Handle<AudioSource>; 3]>);
+
+impl SoundtrackStems {
+    /// Starts loading the stems of the manifest.
+    pub fn from_manifest(manifest: &SoundtrackManifest, asset_server: &AssetServer) -> Self {
+        Self(
+            manifest
+                .stems
+                .iter()
+                .map(|(name, paths)| {
+                    let stems = [&paths.base, &paths.drums, &paths.lead]
+                        .map(|path| asset_server.load(path.clone()));
+                    (SoundtrackKey(name.clone()), stems)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn all_loaded(&self, asset_server: &AssetServer) -> bool {
+        self.0
+            .values()
+            .flatten()
+            .all(|x| asset_server.is_loaded_with_dependencies(x))
     }
 }
 
@@ -0,0 +1,129 @@
+//! Adaptive music: the layers of a soundtrack split into stems follow the run. The drums and
+//! then the lead come in as checkpoints are reached, both drop out while the robot is in the air
+//! for long, and losing a limb plays a sting over the music.
+
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+    utils::HashSet,
+};
+use uuid::Uuid;
+
+use crate::{
+    data::config::GameConfig,
+    game::{
+        assets::{HandleMap, SoundtrackKey},
+        checkpoint::ReachCheckpoint,
+        spawn::player::{LostLimbs, Player, PlayerOnGround},
+        GameState,
+    },
+    screen::Screen,
+    AppSet,
+};
+
+use super::{
+    mixer::{Bus, Mixer, SoundVolume},
+    stems::{Stem, StemMixGains},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MusicIntensity>()
+        .observe(on_reach_checkpoint)
+        .add_systems(OnExit(Screen::Playing), reset_intensity)
+        .add_systems(
+            Update,
+            (
+                (track_airborne, limb_sting).run_if(in_state(GameState::Playing)),
+                mix_stems,
+            )
+                .chain()
+                .in_set(AppSet::Update)
+                .run_if(in_state(Screen::Playing)),
+        );
+}
+
+/// What the adaptive music follows.
+#[derive(Resource, Debug, Default)]
+struct MusicIntensity {
+    /// The checkpoints reached during the run.
+    reached: HashSet<Uuid>,
+    /// Seconds since the wheel left the ground.
+    airborne: f32,
+}
+
+fn on_reach_checkpoint(trigger: Trigger<ReachCheckpoint>, mut intensity: ResMut<MusicIntensity>) {
+    intensity.reached.insert(trigger.event().0 .0);
+}
+
+fn reset_intensity(mut intensity: ResMut<MusicIntensity>) {
+    *intensity = default();
+}
+
+fn track_airborne(
+    mut intensity: ResMut<MusicIntensity>,
+    q_player: Query<Has<PlayerOnGround>, With<Player>>,
+    time: Res<Time>,
+) {
+    match q_player.get_single() {
+        Ok(false) => intensity.airborne += time.delta_seconds(),
+        _ => intensity.airborne = 0.0,
+    }
+}
+
+/// Plays the sting of the config when an arm is lost.
+fn limb_sting(
+    mut cmd: Commands,
+    lost_limbs: Res<LostLimbs>,
+    mut previous: Local<LostLimbs>,
+    soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    config: Res<GameConfig>,
+    mixer: Res<Mixer>,
+) {
+    let lost = (lost_limbs.left && !previous.left) || (lost_limbs.right && !previous.right);
+    *previous = *lost_limbs;
+    let Some(sting) = config.audio.limb_sting.as_ref().filter(|_| lost) else {
+        return;
+    };
+    let Some(source) = soundtrack_handles.get(&SoundtrackKey(sting.clone())) else {
+        warn!("No soundtrack {sting} in the manifest");
+        return;
+    };
+    cmd.spawn((
+        AudioSourceBundle {
+            source: source.clone_weak(),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new(mixer.volume(Bus::Music)),
+                ..default()
+            },
+        },
+        Bus::Music,
+        SoundVolume(1.0),
+    ));
+}
+
+fn mix_stems(
+    intensity: Res<MusicIntensity>,
+    config: Res<GameConfig>,
+    q_mixes: Query<&StemMixGains>,
+) {
+    let level =
+        (intensity.reached.len() as f32 * config.audio.checkpoint_intensity).clamp(0.0, 1.0);
+    let (drums, lead) = if intensity.airborne > config.audio.airborne_mute {
+        (0.0, 0.0)
+    } else {
+        ((level * 2.0).min(1.0), (level * 2.0 - 1.0).max(0.0))
+    };
+    for gains in &q_mixes {
+        for stem in Stem::ALL {
+            gains.0.set(
+                stem,
+                match stem {
+                    Stem::Base => 1.0,
+                    Stem::Drums => drums,
+                    Stem::Lead => lead,
+                },
+            );
+        }
+    }
+}
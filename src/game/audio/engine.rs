@@ -2,7 +2,7 @@ use bevy::prelude::*;
 
 use crate::{data::config::GameConfig, game::assets::SfxKey, screen::Screen, AppSet};
 
-use super::sfx::PlaySfx;
+use super::{mixer::SoundVolume, sfx::PlaySfx};
 
 pub(super) fn plugin(app: &mut App) {
     app.insert_resource(EngineSpeed(0.0))
@@ -35,13 +35,13 @@ fn on_accelerate_engine(
 fn update_engine(
     mut engine_speed: ResMut<EngineSpeed>,
     config: Res<GameConfig>,
-    q_audio_sink: Query<(&AudioSink, &SfxKey)>,
+    mut q_audio_sink: Query<(&AudioSink, &mut SoundVolume, &SfxKey)>,
     time: Res<Time>,
 ) {
-    for (sink, key) in &q_audio_sink {
+    for (sink, mut volume, key) in &mut q_audio_sink {
         if key == &SfxKey::Engine {
             sink.set_speed(1.0 + engine_speed.0 * 2.0);
-            sink.set_volume(config.audio.engine * config.audio.sfx_volume * engine_speed.0);
+            volume.0 = config.audio.engine * engine_speed.0;
         }
     }
     engine_speed.0 = (engine_speed.0 - config.audio.engine_dec * time.delta_seconds()).max(0.0);
@@ -0,0 +1,127 @@
+use bevy::{
+    audio::{AudioSinkPlayback, SpatialAudioSink},
+    prelude::*,
+};
+
+use crate::{data::config::GameConfig, game::GameState};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Bus>()
+        .init_resource::<Mixer>()
+        .add_systems(
+            Update,
+            (
+                init_mixer.run_if(resource_added::<GameConfig>),
+                duck_music.run_if(resource_exists::<GameConfig>),
+            ),
+        )
+        // after the sounds set their own volume
+        .add_systems(
+            PostUpdate,
+            (apply_mixer::<AudioSink>, apply_mixer::<SpatialAudioSink>),
+        );
+}
+
+/// The channels of the [`Mixer`]. Every sound is on one of the buses, the master gain applies to
+/// all of them.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Reflect)]
+pub enum Bus {
+    Master,
+    Music,
+    Sfx,
+    Ui,
+}
+
+/// Volume of a sound before the gain of its [`Bus`].
+#[derive(Component, Debug)]
+pub struct SoundVolume(pub f32);
+
+/// Gains of the buses, changes apply to the sounds already playing.
+#[derive(Resource, Debug)]
+pub struct Mixer {
+    master: f32,
+    music: f32,
+    sfx: f32,
+    ui: f32,
+    /// Lowers the music while an overlay is shown, 1.0 when not ducked.
+    ducking: f32,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 1.0,
+            sfx: 1.0,
+            ui: 1.0,
+            ducking: 1.0,
+        }
+    }
+}
+
+impl Mixer {
+    pub fn gain(&self, bus: Bus) -> f32 {
+        match bus {
+            Bus::Master => self.master,
+            Bus::Music => self.music,
+            Bus::Sfx => self.sfx,
+            Bus::Ui => self.ui,
+        }
+    }
+
+    pub fn set_gain(&mut self, bus: Bus, gain: f32) {
+        let gain = gain.clamp(0.0, 1.0);
+        match bus {
+            Bus::Master => self.master = gain,
+            Bus::Music => self.music = gain,
+            Bus::Sfx => self.sfx = gain,
+            Bus::Ui => self.ui = gain,
+        }
+    }
+
+    /// The factor applied to the [`SoundVolume`] of the sounds on `bus`.
+    pub fn volume(&self, bus: Bus) -> f32 {
+        match bus {
+            Bus::Master => self.master,
+            Bus::Music => self.master * self.music * self.ducking,
+            Bus::Sfx | Bus::Ui => self.master * self.gain(bus),
+        }
+    }
+}
+
+fn init_mixer(mut mixer: ResMut<Mixer>, config: Res<GameConfig>) {
+    mixer.set_gain(Bus::Master, config.audio.master_volume);
+    mixer.set_gain(Bus::Music, config.audio.soundtrack_volume);
+    mixer.set_gain(Bus::Sfx, config.audio.sfx_volume);
+    mixer.set_gain(Bus::Ui, config.audio.ui_volume);
+}
+
+/// Lowers the music during the death and victory overlays.
+fn duck_music(
+    mut mixer: ResMut<Mixer>,
+    config: Res<GameConfig>,
+    game_state: Option<Res<State<GameState>>>,
+    // on the same clock as the soundtrack fades
+    time: Res<Time<Real>>,
+) {
+    let ducked = game_state
+        .is_some_and(|state| matches!(state.get(), GameState::Death | GameState::Victory));
+    let target = if ducked { config.audio.duck } else { 1.0 };
+    if mixer.ducking != target {
+        let step = config.audio.duck_speed * time.delta_seconds();
+        let ducking = mixer.ducking;
+        mixer.ducking = ducking + (target - ducking).clamp(-step, step);
+    }
+}
+
+/// World sounds play through a [`SpatialAudioSink`], the others through an [`AudioSink`].
+fn apply_mixer<S: Component + AudioSinkPlayback>(
+    mixer: Res<Mixer>,
+    q_sounds: Query<(Ref<S>, &Bus, Ref<SoundVolume>)>,
+) {
+    for (sink, bus, volume) in &q_sounds {
+        if mixer.is_changed() || sink.is_added() || volume.is_changed() {
+            sink.set_volume(volume.0 * mixer.volume(*bus));
+        }
+    }
+}
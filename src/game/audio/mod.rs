@@ -1,9 +1,21 @@
+pub mod adaptive;
 pub mod engine;
+pub mod mixer;
 pub mod sfx;
 pub mod soundtrack;
+pub mod stems;
+pub mod surface;
 
 use bevy::prelude::*;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins((sfx::plugin, soundtrack::plugin, engine::plugin));
+    app.add_plugins((
+        mixer::plugin,
+        sfx::plugin,
+        soundtrack::plugin,
+        engine::plugin,
+        surface::plugin,
+        stems::plugin,
+        adaptive::plugin,
+    ));
 }
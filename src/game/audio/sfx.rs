@@ -1,17 +1,32 @@
 use bevy::{
-    audio::{PlaybackMode, Volume},
+    audio::{PlaybackMode, SpatialListener, SpatialScale, Volume},
     prelude::*,
 };
-use rand::{thread_rng, Rng};
-// use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, thread_rng, Rng};
 
 use crate::{
     data::config::GameConfig,
     game::assets::{HandleMap, SfxKey},
+    MainCamera,
 };
 
+use super::mixer::{Bus, Mixer, SoundVolume};
+
 pub(super) fn plugin(app: &mut App) {
-    app.observe(play_sfx);
+    app.observe(play_sfx)
+        .add_systems(Update, add_listener.run_if(resource_added::<GameConfig>));
+}
+
+/// World sounds are heard from the camera.
+fn add_listener(
+    mut cmd: Commands,
+    q_camera: Query<Entity, With<MainCamera>>,
+    config: Res<GameConfig>,
+) {
+    for camera in &q_camera {
+        cmd.entity(camera)
+            .insert(SpatialListener::new(config.audio.ear_gap));
+    }
 }
 
 fn play_sfx(
@@ -19,52 +34,76 @@ fn play_sfx(
     mut commands: Commands,
     sfx_handles: Res<HandleMap<SfxKey>>,
     config: Res<GameConfig>,
+    mixer: Res<Mixer>,
 ) {
-    let sfx_key = match trigger.event() {
-        PlaySfx::Key(key) => *key,
-        // PlaySfx::RandomStep => random_step(),
+    let (sfx_key, bus, pos, intensity, speed) = match trigger.event() {
+        PlaySfx::Key(key) => (*key, Bus::Sfx, None, 1.0, None),
+        PlaySfx::Ui(key) => (*key, Bus::Ui, None, 1.0, None),
+        PlaySfx::At(key, pos, intensity) => (*key, Bus::Sfx, Some(*pos), *intensity, None),
+        PlaySfx::AtSpeed(key, pos, intensity, speed) => {
+            (*key, Bus::Sfx, Some(*pos), *intensity, Some(*speed))
+        }
     };
-    commands.spawn((
+    let intensity = intensity.clamp(0.0, 1.0);
+    let volume = intensity
+        * match sfx_key {
+            SfxKey::Engine => 0.0,
+            SfxKey::ButtonHover | SfxKey::ButtonPress => config.audio.button,
+            SfxKey::Jump => config.audio.jump,
+            SfxKey::Clonk => config.audio.clonk,
+            SfxKey::Step1 | SfxKey::Step2 | SfxKey::Step3 | SfxKey::Step4 => config.audio.step,
+        };
+    // the camera is far above the level, only the distance in the plane counts
+    let scale = 1.0 / config.audio.spatial_distance;
+    let mut sound = commands.spawn((
         AudioSourceBundle {
             source: sfx_handles[&sfx_key].clone_weak(),
             settings: PlaybackSettings {
-                volume: Volume::new(
-                    config.audio.sfx_volume
-                        * match sfx_key {
-                            SfxKey::Engine => 0.0,
-                            SfxKey::ButtonHover | SfxKey::ButtonPress => config.audio.button,
-                            SfxKey::Jump => config.audio.jump,
-                            SfxKey::Clonk => config.audio.clonk,
-                            _ => 1.0,
-                        },
-                ),
+                volume: Volume::new(volume * mixer.volume(bus)),
                 mode: if sfx_key == SfxKey::Engine {
                     PlaybackMode::Loop
                 } else {
                     PlaybackMode::Despawn
                 },
-                speed: if sfx_key == SfxKey::Clonk {
-                    thread_rng().gen_range(0.5..1.2)
-                } else {
-                    1.0
-                },
+                speed: speed.unwrap_or_else(|| {
+                    if sfx_key == SfxKey::Clonk {
+                        thread_rng().gen_range(0.5..1.2)
+                    } else {
+                        1.0
+                    }
+                }),
+                spatial: pos.is_some(),
+                spatial_scale: Some(SpatialScale(Vec3::new(scale, scale, 0.0))),
                 ..default()
             },
         },
         sfx_key,
+        bus,
+        SoundVolume(volume),
     ));
+    if let Some(pos) = pos {
+        sound.insert(TransformBundle::from_transform(
+            Transform::from_translation(pos.extend(0.0)),
+        ));
+    }
 }
 
 /// Trigger this event to play a single sound effect.
 #[derive(Event)]
 pub enum PlaySfx {
     Key(SfxKey),
-    // RandomStep,
+    /// A sound of the interface, on the UI bus.
+    Ui(SfxKey),
+    /// A sound of the world at a position, panned and fading with its distance to the camera.
+    /// The intensity, from 0 to 1, scales its volume.
+    At(SfxKey, Vec2, f32),
+    /// Like `At`, played `speed` times faster, higher pitched.
+    AtSpeed(SfxKey, Vec2, f32, f32),
 }
 
-// fn random_step() -> SfxKey {
-//     [SfxKey::Step1, SfxKey::Step2, SfxKey::Step3, SfxKey::Step4]
-//         .choose(&mut rand::thread_rng())
-//         .copied()
-//         .unwrap()
-// }
+pub fn random_step() -> SfxKey {
+    [SfxKey::Step1, SfxKey::Step2, SfxKey::Step3, SfxKey::Step4]
+        .choose(&mut rand::thread_rng())
+        .copied()
+        .unwrap()
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use bevy::{
     audio::{PlaybackMode, Volume},
     prelude::*,
@@ -5,68 +7,188 @@ use bevy::{
 
 use crate::{
     data::config::GameConfig,
-    game::assets::{HandleMap, SoundtrackKey},
+    game::assets::{HandleMap, SoundtrackKey, SoundtrackStems},
+};
+
+use super::{
+    mixer::{Bus, SoundVolume},
+    stems::{StemGains, StemMix, StemMixGains},
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<IsSoundtrack>();
     app.observe(play_soundtrack);
-    app.observe(adjust_soundtrack_volume);
+    // soundtracks keep fading out after leaving the screen that played them
+    app.add_systems(Update, fade_soundtracks);
 }
 
 fn play_soundtrack(
     trigger: Trigger<PlaySoundtrack>,
     mut cmd: Commands,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
-    soundtrack_query: Query<Entity, With<IsSoundtrack>>,
+    soundtrack_stems: Res<SoundtrackStems>,
+    audio_sources: Res<Assets<AudioSource>>,
+    mut stem_mixes: ResMut<Assets<StemMix>>,
+    soundtrack_query: Query<(Entity, &IsSoundtrack, &SoundtrackKey, Option<&Fade>)>,
     config: Res<GameConfig>,
-    // gv: Res<GlobalVolume>,
 ) {
-    // warn!("Global volume: {}", gv.volume.get());
-    for entity in &soundtrack_query {
-        cmd.entity(entity).despawn_recursive();
+    let (channel, soundtrack_key) = match trigger.event() {
+        PlaySoundtrack::Key(key) => (SoundtrackChannel::Music, Some(key)),
+        PlaySoundtrack::Ambient(key) => (SoundtrackChannel::Ambient, key.as_ref()),
+        PlaySoundtrack::Disable => {
+            for (entity, _, _, fade) in &soundtrack_query {
+                fade_out(&mut cmd, entity, fade, config.audio.crossfade);
+            }
+            return;
+        }
+    };
+    let playing: Vec<_> = soundtrack_query
+        .iter()
+        .filter(|(_, is_soundtrack, _, fade)| {
+            is_soundtrack.0 == channel && !fade.is_some_and(|fade| fade.out)
+        })
+        .collect();
+    if playing
+        .iter()
+        .any(|(_, _, key, _)| Some(*key) == soundtrack_key)
+    {
+        return;
+    }
+    for (entity, _, _, fade) in playing {
+        fade_out(&mut cmd, entity, fade, config.audio.crossfade);
     }
 
-    let soundtrack_key = match trigger.event() {
-        PlaySoundtrack::Key(key) => *key,
-        PlaySoundtrack::Disable => return,
+    let Some(soundtrack_key) = soundtrack_key else {
+        return;
     };
-    cmd.spawn((
-        AudioSourceBundle {
-            source: soundtrack_handles[&soundtrack_key].clone_weak(),
+    let mut soundtrack = if let Some(stems) = soundtrack_stems.0.get(soundtrack_key) {
+        let gains = Arc::new(StemGains::default());
+        let mix = StemMix {
+            stems: stems
+                .iter()
+                .filter_map(|handle| audio_sources.get(handle).cloned())
+                .collect(),
+            gains: gains.clone(),
+        };
+        cmd.spawn((
+            AudioSourceBundle {
+                source: stem_mixes.add(mix),
+                // the mix loops on its own
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Once,
+                    volume: Volume::new(0.0),
+                    ..default()
+                },
+            },
+            StemMixGains(gains),
+        ))
+    } else if let Some(source) = soundtrack_handles.get(soundtrack_key) {
+        cmd.spawn(AudioSourceBundle {
+            source: source.clone_weak(),
             settings: PlaybackSettings {
                 mode: PlaybackMode::Loop,
-                volume: Volume::new(config.audio.soundtrack_volume),
+                volume: Volume::new(0.0),
                 ..default()
             },
-        },
-        IsSoundtrack,
+        })
+    } else {
+        warn!("No soundtrack {} in the manifest", soundtrack_key.0);
+        return;
+    };
+    soundtrack.insert((
+        IsSoundtrack(channel),
+        soundtrack_key.clone(),
+        Bus::Music,
+        SoundVolume(0.0),
+        Fade::fade_in(config.audio.crossfade),
     ));
-    cmd.trigger(AdjustSoundtrackVolume(config.audio.soundtrack_volume));
 }
 
-#[derive(Event)]
-pub struct AdjustSoundtrackVolume(pub f32);
-
-fn adjust_soundtrack_volume(
-    trigger: Trigger<AdjustSoundtrackVolume>,
-    q_audio_sink: Query<&AudioSink, With<IsSoundtrack>>,
+/// Fades soundtracks in and out, despawning them once faded out.
+fn fade_soundtracks(
+    mut cmd: Commands,
+    mut q_fading: Query<(Entity, &IsSoundtrack, &mut SoundVolume, &mut Fade), With<AudioSink>>,
+    config: Option<Res<GameConfig>>,
+    // the audio plays in real time, the virtual clock can be slowed down and is clamped after
+    // a hitch
+    time: Res<Time<Real>>,
 ) {
-    for audio_sink in &q_audio_sink {
-        audio_sink.set_volume(trigger.event().0);
+    let Some(config) = config else {
+        return;
+    };
+    for (entity, is_soundtrack, mut volume, mut fade) in &mut q_fading {
+        fade.timer.tick(time.delta());
+        let fraction = fade.timer.fraction();
+        let level = if fade.out { 1.0 - fraction } else { fraction };
+        volume.0 = is_soundtrack.0.volume(&config) * level;
+        if fade.timer.finished() {
+            if fade.out {
+                cmd.entity(entity).despawn_recursive();
+            } else {
+                cmd.entity(entity).remove::<Fade>();
+            }
+        }
     }
 }
 
 /// Trigger this event to play or disable the soundtrack.
-/// Playing a new soundtrack will overwrite the previous one.
+/// Playing a new soundtrack crossfades from the previous one, playing the same one again keeps
+/// it going.
 /// Soundtracks will loop.
 #[derive(Event)]
 pub enum PlaySoundtrack {
     Key(SoundtrackKey),
+    /// Plays a loop under the soundtrack, `None` fades out the current one.
+    Ambient(Option<SoundtrackKey>),
+    /// Fades out the soundtrack and the ambient loop.
     Disable,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Reflect)]
+enum SoundtrackChannel {
+    Music,
+    Ambient,
+}
+
+impl SoundtrackChannel {
+    /// Volume of the channel, before the gain of the music bus.
+    fn volume(self, config: &GameConfig) -> f32 {
+        match self {
+            SoundtrackChannel::Music => 1.0,
+            SoundtrackChannel::Ambient => config.audio.ambient,
+        }
+    }
+}
+
 /// Marker component for the soundtrack entity so we can find it later.
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-struct IsSoundtrack;
+struct IsSoundtrack(SoundtrackChannel);
+
+/// A soundtrack fading in or out.
+#[derive(Component, Debug)]
+struct Fade {
+    timer: Timer,
+    out: bool,
+}
+
+impl Fade {
+    fn fade_in(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            out: false,
+        }
+    }
+}
+
+/// Fades out the soundtrack from its current volume, which is lower if it was still fading in.
+fn fade_out(cmd: &mut Commands, entity: Entity, fade: Option<&Fade>, seconds: f32) {
+    if fade.is_some_and(|fade| fade.out) {
+        return;
+    }
+    let mut timer = Timer::from_seconds(seconds, TimerMode::Once);
+    if let Some(fade) = fade {
+        timer.set_elapsed(timer.duration().mul_f32(1.0 - fade.timer.fraction()));
+    }
+    cmd.entity(entity).insert(Fade { timer, out: true });
+}
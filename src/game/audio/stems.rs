@@ -0,0 +1,229 @@
+//! Soundtracks split into stems. The stems are decoded and mixed together sample by sample, so
+//! they start and loop in sync, and the gain of each stem can change while it plays.
+
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use bevy::{
+    audio::{AddAudioSource, Decodable, Source},
+    prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_audio_source::<StemMix>();
+}
+
+/// Seconds for a stem to reach a new gain.
+const GAIN_FADE: f32 = 1.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stem {
+    Base,
+    Drums,
+    Lead,
+}
+
+impl Stem {
+    pub const ALL: [Stem; 3] = [Stem::Base, Stem::Drums, Stem::Lead];
+}
+
+/// The gains of the stems of a playing [`StemMix`].
+#[derive(Debug)]
+pub struct StemGains([AtomicU32; 3]);
+
+impl Default for StemGains {
+    fn default() -> Self {
+        // only the base plays until the adaptive music says otherwise
+        let gains = Self(Default::default());
+        gains.set(Stem::Base, 1.0);
+        gains
+    }
+}
+
+impl StemGains {
+    pub fn set(&self, stem: Stem, gain: f32) {
+        self.0[stem as usize].store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    fn get(&self, index: usize) -> f32 {
+        f32::from_bits(self.0[index].load(Ordering::Relaxed))
+    }
+}
+
+/// A soundtrack mixed from its stems, in the order of [`Stem::ALL`]. The stems should share
+/// their channels and sample rate, the base one sets the length of the loop.
+#[derive(Asset, TypePath)]
+pub struct StemMix {
+    pub stems: Vec<AudioSource>,
+    pub gains: Arc<StemGains>,
+}
+
+/// Gives access to the gains of a playing [`StemMix`].
+#[derive(Component, Debug)]
+pub struct StemMixGains(pub Arc<StemGains>);
+
+impl Decodable for StemMix {
+    type DecoderItem = f32;
+    type Decoder = StemMixDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        let stems = self.stems.clone();
+        StemMixDecoder::new(
+            move || stems.iter().map(Decodable::decoder).collect(),
+            self.gains.clone(),
+        )
+    }
+}
+
+/// Loops forever, the gains wouldn't apply to the repeats of a looping sink.
+pub struct StemMixDecoder<S = <AudioSource as Decodable>::Decoder> {
+    /// Decodes the stems from their start, again each time the mix loops.
+    open: Box<dyn Fn() -> Vec<S> + Send>,
+    decoders: Vec<S>,
+    gains: Arc<StemGains>,
+    /// The gains reached, moving toward the ones asked to avoid clicks.
+    current: [f32; 3],
+    gain_step: f32,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<S: Source<Item = i16>> StemMixDecoder<S> {
+    fn new(open: impl Fn() -> Vec<S> + Send + 'static, gains: Arc<StemGains>) -> Self {
+        let decoders = open();
+        let channels = decoders.first().map_or(2, |decoder| decoder.channels());
+        let sample_rate = decoders
+            .first()
+            .map_or(44100, |decoder| decoder.sample_rate());
+        Self {
+            open: Box::new(open),
+            decoders,
+            current: std::array::from_fn(|i| gains.get(i)),
+            gains,
+            gain_step: 1.0 / (GAIN_FADE * sample_rate as f32 * channels as f32),
+            channels,
+            sample_rate,
+        }
+    }
+
+    fn restart(&mut self) {
+        self.decoders = (self.open)();
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for StemMixDecoder<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let base = match self.decoders.first_mut()?.next() {
+            Some(sample) => sample,
+            None => {
+                self.restart();
+                self.decoders.first_mut()?.next()?
+            }
+        };
+        let mut mixed = 0.0;
+        for (i, decoder) in self
+            .decoders
+            .iter_mut()
+            .enumerate()
+            .take(self.current.len())
+        {
+            let sample = if i == 0 { Some(base) } else { decoder.next() };
+            let target = self.gains.get(i);
+            self.current[i] += (target - self.current[i]).clamp(-self.gain_step, self.gain_step);
+            if let Some(sample) = sample {
+                mixed += f32::from(sample) / i16::MAX as f32 * self.current[i];
+            }
+        }
+        Some(mixed)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for StemMixDecoder<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A mono stem at 4 Hz.
+    struct Samples(std::vec::IntoIter<i16>);
+
+    impl Iterator for Samples {
+        type Item = i16;
+
+        fn next(&mut self) -> Option<i16> {
+            self.0.next()
+        }
+    }
+
+    impl Source for Samples {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn sample_rate(&self) -> u32 {
+            4
+        }
+
+        fn total_duration(&self) -> Option<std::time::Duration> {
+            None
+        }
+    }
+
+    fn mix(stems: Vec<Vec<i16>>, gains: Arc<StemGains>) -> StemMixDecoder<Samples> {
+        StemMixDecoder::new(
+            move || {
+                stems
+                    .iter()
+                    .map(|stem| Samples(stem.clone().into_iter()))
+                    .collect()
+            },
+            gains,
+        )
+    }
+
+    #[test]
+    fn test_stems_loop_in_sync() {
+        let gains = Arc::new(StemGains::default());
+        gains.set(Stem::Drums, 1.0);
+        // the drums run longer than the base, they restart with it anyway
+        let full = i16::MAX;
+        let mix = mix(vec![vec![full, 0, 0], vec![0, full, 0, full]], gains);
+        let samples: Vec<f32> = mix.take(6).collect();
+        assert_eq!(samples, vec![1.0, 1.0, 0.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_gain_ramp() {
+        let gains = Arc::new(StemGains::default());
+        let mut mix = mix(vec![vec![i16::MAX; 8]], gains.clone());
+        assert_eq!(mix.next(), Some(1.0));
+        // a second at 4 Hz, the gain reaches its target in 4 samples
+        gains.set(Stem::Base, 0.0);
+        let samples: Vec<f32> = mix.take(5).collect();
+        assert_eq!(samples, vec![0.75, 0.5, 0.25, 0.0, 0.0]);
+    }
+}
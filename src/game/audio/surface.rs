@@ -0,0 +1,141 @@
+//! Sounds of the wheel on the ground: steps ticking as it rolls, faster and louder with its
+//! angular speed, and a thump when it lands. The surface under the wheel sets their pitch and
+//! volume.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    data::{
+        config::{GameConfig, SurfaceSoundConfig, DEFAULT_SURFACE},
+        level::LevelData,
+    },
+    game::{
+        assets::SfxKey,
+        entity_id::EntityId,
+        entity_type::EntityType,
+        level_object::LevelObjects,
+        spawn::player::{Player, PlayerOnGround, Wheel},
+        GameState,
+    },
+    AppSet,
+};
+
+use super::sfx::{random_step, PlaySfx};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (roll_sound, landing_sound)
+            .in_set(AppSet::Update)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
+
+/// Speed of the landing thump, lower than the clonks of the hits.
+const THUMP_SPEED: f32 = 0.6;
+
+/// Finds the surface of the ground under the wheel.
+#[derive(SystemParam)]
+struct GroundSurface<'w, 's> {
+    q_ids: Query<
+        'w,
+        's,
+        (
+            Option<&'static EntityId>,
+            Option<&'static EntityType>,
+            Option<&'static Parent>,
+        ),
+    >,
+    objects: Res<'w, LevelObjects>,
+    level: Res<'w, LevelData>,
+    config: Res<'w, GameConfig>,
+}
+
+impl GroundSurface<'_, '_> {
+    /// The sound of the surface of the object `collider` belongs to.
+    fn sound(&self, collider: Entity) -> &SurfaceSoundConfig {
+        // the colliders of an object belong to the closest parent with an id
+        let mut entity = Some(collider);
+        let mut surface = None;
+        while let Some((id, e_type, parent)) = entity.and_then(|e| self.q_ids.get(e).ok()) {
+            if let (Some(id), Some(e_type)) = (id, e_type) {
+                surface = self
+                    .objects
+                    .get(e_type)
+                    .and_then(|kind| kind.surface(&self.level, id.0));
+                break;
+            }
+            entity = parent.map(Parent::get);
+        }
+        &self
+            .config
+            .surface(surface.unwrap_or(DEFAULT_SURFACE))
+            .sound
+    }
+}
+
+/// Ticks a step each time the wheel turns by `roll_step` on the ground.
+fn roll_sound(
+    mut cmd: Commands,
+    // how far the wheel turned since the last step, in radians
+    mut turned: Local<f32>,
+    q_on_ground: Query<&PlayerOnGround>,
+    q_wheel: Query<(&Velocity, &GlobalTransform), With<Wheel>>,
+    ground: GroundSurface,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let (Ok(on_ground), Ok((velocity, gt))) = (q_on_ground.get_single(), q_wheel.get_single())
+    else {
+        *turned = 0.0;
+        return;
+    };
+    let angular_speed = velocity.angvel.abs();
+    *turned += angular_speed * time.delta_seconds();
+    if *turned < config.audio.roll_step {
+        return;
+    }
+    *turned = turned.rem_euclid(config.audio.roll_step);
+    let sound = ground.sound(on_ground.0);
+    cmd.trigger(PlaySfx::AtSpeed(
+        random_step(),
+        gt.translation().xy(),
+        (angular_speed / config.audio.roll_speed).min(1.0) * sound.volume,
+        sound.pitch,
+    ));
+}
+
+/// Thumps when the wheel touches the ground, louder the faster it was falling.
+fn landing_sound(
+    mut cmd: Commands,
+    // downward speed of the wheel while in the air
+    mut fall_speed: Local<f32>,
+    q_landed: Query<&PlayerOnGround, Added<PlayerOnGround>>,
+    q_airborne: Query<(), (With<Player>, Without<PlayerOnGround>)>,
+    q_wheel: Query<(&Velocity, &GlobalTransform), With<Wheel>>,
+    ground: GroundSurface,
+    config: Res<GameConfig>,
+) {
+    let Ok((velocity, gt)) = q_wheel.get_single() else {
+        *fall_speed = 0.0;
+        return;
+    };
+    if let Ok(on_ground) = q_landed.get_single() {
+        let sound = ground.sound(on_ground.0);
+        let intensity = *fall_speed / config.audio.landing_speed * sound.volume;
+        if intensity > config.audio.min_landing {
+            cmd.trigger(PlaySfx::AtSpeed(
+                SfxKey::Clonk,
+                gt.translation().xy(),
+                intensity,
+                THUMP_SPEED * sound.pitch,
+            ));
+        }
+    }
+    *fall_speed = if q_airborne.is_empty() {
+        0.0
+    } else {
+        (-velocity.linvel.y).max(0.0)
+    };
+}
@@ -1,30 +1,74 @@
+//! Parallax layers covering the screen behind the level, from the config or the level.
+
 use bevy::{
-    color::palettes::css::{GRAY, WHITE},
     math::vec2,
     prelude::*,
+    render::texture::{ImageLoaderSettings, ImageSampler},
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashMap,
     window::PrimaryWindow,
 };
 
-use crate::{data::config::GameConfig, screen::Screen, AppSet};
-
-use super::{
-    assets::{HandleMap, ImageKey},
-    fixed_material::FixedMaterial,
+use crate::{
+    data::{
+        config::{BackgroundConfig, GameConfig},
+        level::LevelData,
+    },
+    screen::Screen,
+    AppSet,
 };
 
+use super::fixed_material::FixedMaterial;
+
 pub(super) fn plugin(app: &mut App) {
-    app.observe(spawn_background).add_systems(
-        Update,
-        (scale_to_screen, update_material_pos)
-            .chain()
-            .in_set(AppSet::Update)
-            .run_if(in_state(Screen::Playing)),
-    );
+    app.init_resource::<BackgroundImages>()
+        .observe(spawn_background)
+        .add_systems(
+            Update,
+            (scale_to_screen, update_material_pos)
+                .chain()
+                .in_set(AppSet::Update)
+                .run_if(in_state(Screen::Playing)),
+        );
+}
+
+/// The images of the background layers by path, loaded on the loading screen and kept alive so
+/// the level never shows an empty background.
+#[derive(Resource, Default)]
+pub struct BackgroundImages(HashMap<String, Handle<Image>>);
+
+impl BackgroundImages {
+    /// Starts loading the images of `layers` that aren't loaded yet.
+    pub fn load(&mut self, asset_server: &AssetServer, layers: &[BackgroundConfig]) {
+        for layer in layers {
+            self.get_or_load(asset_server, &layer.image);
+        }
+    }
+
+    fn get_or_load(&mut self, asset_server: &AssetServer, image: &str) -> Handle<Image> {
+        let image = image.to_string();
+        self.0
+            .entry(image.clone())
+            .or_insert_with(|| {
+                asset_server.load_with_settings(image, |settings: &mut ImageLoaderSettings| {
+                    settings.sampler = ImageSampler::nearest();
+                })
+            })
+            .clone()
+    }
+
+    pub fn all_loaded(&self, asset_server: &AssetServer) -> bool {
+        self.0
+            .values()
+            .all(|x| asset_server.is_loaded_with_dependencies(x))
+    }
 }
 
 #[derive(Component)]
-pub struct Background;
+pub struct Background {
+    /// Speed the image scrolls at on its own.
+    velocity: Vec2,
+}
 
 #[derive(Event, Debug)]
 pub struct SpawnBackground;
@@ -33,41 +77,34 @@ fn spawn_background(
     _trigger: Trigger<SpawnBackground>,
     mut cmd: Commands,
     mut materials: ResMut<Assets<FixedMaterial>>,
-    image_handles: Res<HandleMap<ImageKey>>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<BackgroundImages>,
     mut meshes: ResMut<Assets<Mesh>>,
     config: Res<GameConfig>,
+    level: Res<LevelData>,
 ) {
-    cmd.spawn((
-        Background,
-        StateScoped(Screen::Playing),
-        MaterialMesh2dBundle {
-            material: materials.add(FixedMaterial::new(
-                GRAY,
-                //WHITE,
-                image_handles[&ImageKey::Background].clone_weak(),
-                vec2(config.background.scale_x, config.background.scale_y),
-                vec2(config.background.parallax_x, config.background.parallax_y),
-            )),
-            mesh: Mesh2dHandle(meshes.add(Rectangle::from_size(vec2(1.0, 1.0)))),
-            transform: Transform::from_translation(Vec3::new(0.0, 0.0, -10.0)),
-            ..default()
-        },
-    ));
-    cmd.spawn((
-        Background,
-        StateScoped(Screen::Playing),
-        MaterialMesh2dBundle {
-            material: materials.add(FixedMaterial::new(
-                WHITE,
-                image_handles[&ImageKey::Background2].clone_weak(),
-                vec2(config.background2.scale_x, config.background2.scale_y),
-                vec2(config.background2.parallax_x, config.background2.parallax_y),
-            )),
-            mesh: Mesh2dHandle(meshes.add(Rectangle::from_size(vec2(1.0, 1.0)))),
-            transform: Transform::from_translation(Vec3::new(0.0, 0.0, -11.0)),
-            ..default()
-        },
-    ));
+    let mesh = Mesh2dHandle(meshes.add(Rectangle::from_size(vec2(1.0, 1.0))));
+    for layer in level.backgrounds.as_ref().unwrap_or(&config.backgrounds) {
+        // loaded already, unless the editor changed the layers
+        let texture = images.get_or_load(&asset_server, &layer.image);
+        cmd.spawn((
+            Background {
+                velocity: layer.velocity.into(),
+            },
+            StateScoped(Screen::Playing),
+            MaterialMesh2dBundle {
+                material: materials.add(FixedMaterial::new(
+                    Color::from(Srgba::from(layer.tint)),
+                    texture,
+                    layer.scale.into(),
+                    layer.parallax.into(),
+                )),
+                mesh: mesh.clone(),
+                transform: Transform::from_translation(Vec3::new(0.0, 0.0, layer.z)),
+                ..default()
+            },
+        ));
+    }
 }
 
 fn scale_to_screen(
@@ -92,12 +129,19 @@ fn scale_to_screen(
 }
 
 fn update_material_pos(
-    q_background: Query<(&Transform, &Handle<FixedMaterial>), With<Background>>,
+    time: Res<Time>,
+    q_background: Query<(&Background, &Transform, &Handle<FixedMaterial>)>,
     mut materials: ResMut<Assets<FixedMaterial>>,
 ) {
-    for (tr, handle) in &q_background {
+    for (background, tr, handle) in &q_background {
         if let Some(mat) = materials.get_mut(handle) {
             mat.uniforms.pos = tr.translation.truncate();
+            if background.velocity != Vec2::ZERO {
+                // wrapped to a tile so it stays precise
+                mat.uniforms.offset = (mat.uniforms.offset
+                    - background.velocity * time.delta_seconds())
+                .rem_euclid(mat.uniforms.texture_scale);
+            }
         }
     }
 }
@@ -0,0 +1,180 @@
+//! Bolts are collected by rolling or falling through them. They are counted on the victory
+//! screen, respawning at a checkpoint puts back the ones collected after reaching it.
+
+use bevy::{
+    prelude::*,
+    sprite::Anchor,
+    utils::{HashMap, HashSet},
+};
+use bevy_rapier2d::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    data::{
+        config::GameConfig,
+        level::{BoltData, LevelData},
+    },
+    screen::Screen,
+    AppSet,
+};
+
+use super::{
+    assets::{HandleMap, ImageKey, SfxKey},
+    audio::sfx::PlaySfx,
+    entity_id::EntityId,
+    entity_type::EntityType,
+    level_object::{LevelObject, ObjectKind, ObjectShape, RegisterLevelObject},
+    physics::{coll_groups, ObjectGroup},
+    spawn::player::{Torso, Wheel},
+    GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_spawn_bolt)
+        .init_resource::<CollectedBolts>()
+        .add_systems(
+            Update,
+            check_player_collision
+                .in_set(AppSet::TickTimers)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            show_uncollected
+                .in_set(AppSet::Update)
+                .run_if(resource_changed::<CollectedBolts>),
+        )
+        .register_level_object(
+            ObjectKind::<BoltData>::new(ObjectShape::Sprite)
+                .menu_entry("Bolt", |point| BoltData { pos: point.into() }),
+        );
+}
+
+/// The bolts collected during this attempt.
+#[derive(Debug, Resource, Default, Clone, PartialEq, Eq)]
+pub struct CollectedBolts(pub HashSet<Uuid>);
+
+#[derive(Debug, Event)]
+pub struct SpawnBolt(pub Uuid, pub BoltData);
+
+impl LevelObject for BoltData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.bolts
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.bolts
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnBolt(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct Bolt;
+
+#[derive(Debug, Component)]
+pub struct BoltCollider;
+
+fn on_spawn_bolt(
+    trigger: Trigger<SpawnBolt>,
+    mut cmd: Commands,
+    config: Res<GameConfig>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    collected: Res<CollectedBolts>,
+) {
+    let SpawnBolt(uuid, data) = trigger.event();
+    cmd.spawn((
+        Bolt,
+        EntityId(*uuid),
+        EntityType::of::<BoltData>(),
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(config.bolt.size.into()),
+                anchor: Anchor::BottomCenter,
+                ..default()
+            },
+            texture: image_handles[&ImageKey::Bolt].clone_weak(),
+            transform: Transform::from_translation(Vec2::from(data.pos).extend(-1.0)),
+            visibility: bolt_visibility(collected.0.contains(uuid)),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ))
+    .with_children(|cmd| {
+        cmd.spawn((
+            BoltCollider,
+            TransformBundle::from_transform(Transform::from_translation(
+                Vec2::from(config.bolt.collider.pos).extend(0.0),
+            )),
+            coll_groups(ObjectGroup::PICKUP, ObjectGroup::PLAYER),
+            Sensor,
+            Collider::cuboid(
+                config.bolt.collider.size.x / 2.0,
+                config.bolt.collider.size.y / 2.0,
+            ),
+        ));
+    });
+}
+
+fn bolt_visibility(collected: bool) -> Visibility {
+    if collected {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    }
+}
+
+fn check_player_collision(
+    mut cmd: Commands,
+    rapier_context: Res<RapierContext>,
+    q_parts: Query<(&GlobalTransform, &Collider), Or<(With<Torso>, With<Wheel>)>>,
+    q_bolt_colliders: Query<&Parent, With<BoltCollider>>,
+    q_bolts: Query<&EntityId, With<Bolt>>,
+    mut collected: ResMut<CollectedBolts>,
+) {
+    let mut touched = Vec::new();
+    for (part_tr, part_collider) in &q_parts {
+        let part_tr = part_tr.compute_transform();
+        let shape_pos = part_tr.translation.xy();
+        let shape_rot = part_tr.rotation.to_axis_angle().1;
+        let filter =
+            QueryFilter::default().groups(coll_groups(ObjectGroup::PLAYER, ObjectGroup::PICKUP));
+        rapier_context.intersections_with_shape(
+            shape_pos,
+            shape_rot,
+            part_collider,
+            filter,
+            |entity| {
+                let eid = q_bolt_colliders
+                    .get(entity)
+                    .and_then(|parent| q_bolts.get(parent.get()));
+                if let Ok(eid) = eid {
+                    if !collected.0.contains(&eid.0) {
+                        touched.push(eid.0);
+                    }
+                }
+                true
+            },
+        );
+    }
+    for uuid in touched {
+        if collected.0.insert(uuid) {
+            cmd.trigger(PlaySfx::Key(SfxKey::ButtonHover));
+        }
+    }
+}
+
+fn show_uncollected(
+    collected: Res<CollectedBolts>,
+    mut q_bolts: Query<(&EntityId, &mut Visibility), With<Bolt>>,
+) {
+    for (eid, mut visibility) in &mut q_bolts {
+        *visibility = bolt_visibility(collected.0.contains(&eid.0));
+    }
+}
@@ -0,0 +1,196 @@
+//! Dev-only in-game bot, F9 toggles it.
+//!
+//! In `GameState::BotSetup` the robot is respawned and the next segment of the level route is
+//! planned with the [`autopilot`](crate::autopilot) in a background task. The planned inputs
+//! are then fed to [`PlayerControls`] one per fixed step, and the next segment is planned once
+//! they run out.
+
+use bevy::{
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task},
+};
+use bevy_rapier2d::plugin::{RapierConfiguration, TimestepMode};
+
+use crate::{
+    autopilot::{
+        level_route, plan_segment, unsupported_objects, BotAction, SegmentPlan, SegmentReport, Sim,
+        WaypointKind, BOT_DT,
+    },
+    data::{config::GameConfig, level::LevelData},
+    AppSet,
+};
+
+use super::{
+    checkpoint::CurrentActiveCheckpoint,
+    spawn::player::{record_player_input, PlayerControls, Respawn},
+    start_rapier, stop_rapier, GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::BotSetup), (stop_rapier, start_planning))
+        .add_systems(OnExit(GameState::BotSetup), start_rapier)
+        .add_systems(OnEnter(GameState::Victory), stop_bot)
+        .add_systems(OnEnter(GameState::Death), stop_bot)
+        .add_systems(
+            Update,
+            (
+                toggle_bot.run_if(input_just_pressed(KeyCode::F9)),
+                poll_plan.run_if(in_state(GameState::BotSetup)),
+                drive
+                    .in_set(AppSet::RecordInput)
+                    .after(record_player_input)
+                    .run_if(in_state(GameState::Playing).and_then(resource_exists::<BotDriver>)),
+            ),
+        );
+}
+
+/// The segment being planned.
+#[derive(Resource)]
+struct BotPlanning(Task<(SegmentReport, SegmentPlan)>);
+
+/// Replays a plan, one action per fixed step.
+#[derive(Resource)]
+struct BotDriver {
+    actions: Vec<BotAction>,
+    cursor: usize,
+    timestep: TimestepMode,
+}
+
+fn toggle_bot(
+    mut cmd: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    state: Res<State<GameState>>,
+    driver: Option<Res<BotDriver>>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    if *state == GameState::BotSetup || driver.is_some() {
+        cmd.remove_resource::<BotPlanning>();
+        if let Some(driver) = driver {
+            release(&mut cmd, &driver, &mut rapier_config);
+        }
+        next_state.set(GameState::Playing);
+    } else {
+        next_state.set(GameState::BotSetup);
+    }
+}
+
+fn start_planning(
+    mut cmd: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    level: Res<LevelData>,
+    config: Res<GameConfig>,
+    active_checkpoint: Res<CurrentActiveCheckpoint>,
+) {
+    let unsupported = unsupported_objects(&level);
+    if !unsupported.is_empty() {
+        warn!(
+            "bot: the level uses {}, the autopilot doesn't simulate them",
+            unsupported.join(", ")
+        );
+        next_state.set(GameState::Playing);
+        return;
+    }
+    cmd.trigger(Respawn);
+    let current = match active_checkpoint.0 {
+        Some(ref checkpoint) => WaypointKind::Checkpoint(checkpoint.eid.0),
+        None => WaypointKind::Spawn,
+    };
+    let lost = active_checkpoint
+        .0
+        .as_ref()
+        .map(|checkpoint| (checkpoint.lost_limbs.left, checkpoint.lost_limbs.right))
+        .unwrap_or_default();
+    let route = level_route(&level);
+    let Some(index) = route.iter().position(|waypoint| waypoint.kind == current) else {
+        warn!("bot: current checkpoint is not on the level route");
+        next_state.set(GameState::Playing);
+        return;
+    };
+    let Some(to) = route.get(index + 1).copied() else {
+        info!("bot: no segment left to play");
+        next_state.set(GameState::Playing);
+        return;
+    };
+    let from = route[index];
+    let level = level.clone();
+    let config = config.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let sim = Sim::new(&level, &config, from.spawn_pos(), lost);
+        let plan = plan_segment(&level, &sim, to.target_rect(&config), &config);
+        let report = SegmentReport {
+            from: from.label(),
+            to: to.label(),
+            success: plan.reached,
+            time: plan.duration(),
+            limbs_lost: plan.limbs_lost(lost),
+        };
+        (report, plan)
+    });
+    cmd.insert_resource(BotPlanning(task));
+}
+
+fn poll_plan(
+    mut cmd: Commands,
+    mut planning: Option<ResMut<BotPlanning>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    let Some(ref mut planning) = planning else {
+        return;
+    };
+    let Some((report, plan)) = block_on(future::poll_once(&mut planning.0)) else {
+        return;
+    };
+    cmd.remove_resource::<BotPlanning>();
+    if plan.reached {
+        info!("bot: {}", report);
+        cmd.insert_resource(BotDriver {
+            actions: plan.actions,
+            cursor: 0,
+            timestep: rapier_config.timestep_mode,
+        });
+        rapier_config.timestep_mode = TimestepMode::Fixed {
+            dt: BOT_DT,
+            substeps: 1,
+        };
+    } else {
+        warn!("bot: {}", report);
+    }
+    next_state.set(GameState::Playing);
+}
+
+fn drive(
+    mut cmd: Commands,
+    mut driver: ResMut<BotDriver>,
+    mut controls: ResMut<PlayerControls>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    let Some(action) = driver.actions.get(driver.cursor).copied() else {
+        release(&mut cmd, &driver, &mut rapier_config);
+        next_state.set(GameState::BotSetup);
+        return;
+    };
+    driver.cursor += 1;
+    *controls = PlayerControls {
+        lean: action.lean as f32,
+        jump: action.jump,
+    };
+}
+
+fn stop_bot(
+    mut cmd: Commands,
+    driver: Option<Res<BotDriver>>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    cmd.remove_resource::<BotPlanning>();
+    if let Some(driver) = driver {
+        release(&mut cmd, &driver, &mut rapier_config);
+    }
+}
+
+fn release(cmd: &mut Commands, driver: &BotDriver, rapier_config: &mut RapierConfiguration) {
+    rapier_config.timestep_mode = driver.timestep;
+    cmd.remove_resource::<BotDriver>();
+}
@@ -1,20 +1,47 @@
 use std::time::Duration;
 
-use bevy::{prelude::*, render::camera::ScalingMode, window::WindowResized};
+use bevy::{math::vec2, prelude::*, render::camera::ScalingMode, window::WindowResized};
+use bevy_rapier2d::prelude::*;
 
-use crate::{data::config::GameConfig, lerp::smooth_lerp, screen::Screen, AppSet, MainCamera};
+use crate::{
+    data::{config::GameConfig, level::LevelData},
+    lerp::smooth_lerp,
+    screen::Screen,
+    AppSet, MainCamera,
+};
 
-use super::GameState;
+use super::{spawn::player::Wheel, GameState};
 
 pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SpeedZoom>()
+        .init_resource::<CameraShake>()
+        .init_resource::<ScreenShake>();
     app.observe(on_center_camera)
+        .observe(on_focus_camera)
         .observe(on_zoom_camera)
+        .observe(on_shake_camera)
         .observe(on_init_camera);
     app.add_systems(OnEnter(GameState::Playing), init_camera);
+    app.add_systems(OnExit(Screen::Playing), (clear_focus, clear_shake));
+    app.add_systems(
+        Update,
+        init_screen_shake.run_if(resource_added::<GameConfig>),
+    );
+    app.add_systems(
+        Update,
+        tick_focus
+            .in_set(AppSet::TickTimers)
+            .run_if(in_state(Screen::Playing)),
+    );
     // app.add_system(Update, )
     app.add_systems(
         Update,
-        (check_window_size, update_camera_transform)
+        (
+            check_window_size,
+            zoom_with_speed,
+            shake_camera,
+            update_camera_transform,
+        )
             .chain()
             .in_set(AppSet::Update)
             .run_if(in_state(Screen::Playing)),
@@ -24,6 +51,16 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Event)]
 pub struct CenterCamera(pub Vec2);
 
+/// Looks at `pos` for `duration`, ignoring [`CenterCamera`] meanwhile.
+#[derive(Debug, Event)]
+pub struct FocusCamera {
+    pub pos: Vec2,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Resource)]
+struct CameraFocus(Timer);
+
 #[allow(dead_code)]
 #[derive(Event)]
 pub enum ZoomCamera {
@@ -40,14 +77,70 @@ pub struct CameraTargetScaleDivisor(pub f32);
 #[derive(Debug, Event)]
 pub struct InitCamera;
 
+/// Zooms out while the robot goes fast, dividing the scale divisor.
+#[derive(Debug, Resource)]
+struct SpeedZoom(f32);
+
+impl Default for SpeedZoom {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Adds trauma to the camera, from 0 to 1, making it shake.
+#[derive(Debug, Event)]
+pub struct ShakeCamera(pub f32);
+
+#[derive(Debug, Resource, Default)]
+struct CameraShake {
+    trauma: f32,
+    /// Moved from where the camera follows this frame.
+    offset: Vec2,
+    angle: f32,
+    /// The offset of the camera transform, not part of where it is going.
+    applied: Vec2,
+}
+
+/// Whether the screen shakes, some players get sick of it.
+#[derive(Debug, Resource)]
+pub struct ScreenShake(pub bool);
+
+impl Default for ScreenShake {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
 fn on_center_camera(
     trigger: Trigger<CenterCamera>,
     mut cmd: Commands, // window_query: Query<&Window, With<PrimaryWindow>>,
+    focus: Option<Res<CameraFocus>>,
 ) {
+    if focus.is_some() {
+        return;
+    }
     let pos = trigger.event().0;
     cmd.insert_resource(CameraDestination(pos));
 }
 
+fn on_focus_camera(trigger: Trigger<FocusCamera>, mut cmd: Commands) {
+    let FocusCamera { pos, duration } = *trigger.event();
+    cmd.insert_resource(CameraDestination(pos));
+    cmd.insert_resource(CameraFocus(Timer::new(duration, TimerMode::Once)));
+}
+
+fn tick_focus(mut cmd: Commands, time: Res<Time>, focus: Option<ResMut<CameraFocus>>) {
+    if let Some(mut focus) = focus {
+        if focus.0.tick(time.delta()).finished() {
+            cmd.remove_resource::<CameraFocus>();
+        }
+    }
+}
+
+fn clear_focus(mut cmd: Commands) {
+    cmd.remove_resource::<CameraFocus>();
+}
+
 fn on_zoom_camera(
     trigger: Trigger<ZoomCamera>,
     mut scale_divisor: ResMut<CameraTargetScaleDivisor>,
@@ -59,36 +152,159 @@ fn on_zoom_camera(
     }
 }
 
-const CAMERA_TRANSFORM_HALF_TIME: Duration = Duration::from_millis(100);
+fn on_shake_camera(trigger: Trigger<ShakeCamera>, mut shake: ResMut<CameraShake>) {
+    shake.trauma = (shake.trauma + trigger.event().0).min(1.0);
+}
+
+fn clear_shake(mut shake: ResMut<CameraShake>) {
+    shake.trauma = 0.0;
+}
+
+fn init_screen_shake(mut screen_shake: ResMut<ScreenShake>, config: Res<GameConfig>) {
+    screen_shake.0 = config.camera.shake;
+}
+
+/// Zooms out with the speed of the wheel, back in when the robot isn't driven.
+fn zoom_with_speed(
+    mut speed_zoom: ResMut<SpeedZoom>,
+    q_wheel: Query<&Velocity, With<Wheel>>,
+    game_state: Res<State<GameState>>,
+    config: Res<GameConfig>,
+) {
+    let zoom = match game_state.get() {
+        // keeps the framing under the pause menu
+        GameState::Pause => return,
+        GameState::Playing => q_wheel.get_single().map_or(1.0, |velocity| {
+            let speed = (velocity.linvel.length() / config.camera.zoom_speed).min(1.0);
+            1.0 + speed * (config.camera.max_zoom_out - 1.0)
+        }),
+        _ => 1.0,
+    };
+    speed_zoom.0 = zoom;
+}
+
+/// Shakes by the square of the trauma, which wears off over time.
+fn shake_camera(
+    mut shake: ResMut<CameraShake>,
+    screen_shake: Res<ScreenShake>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    shake.trauma = (shake.trauma - config.camera.trauma_decay * time.delta_seconds()).max(0.0);
+    let amount = if screen_shake.0 {
+        shake.trauma * shake.trauma
+    } else {
+        0.0
+    };
+    // sines at unrelated frequencies make a smooth noise
+    let t = time.elapsed_seconds() * config.camera.shake_frequency;
+    let noise = |seed: f32| ((t + seed).sin() + (1.7 * t + 2.0 * seed).sin() * 0.5) / 1.5;
+    shake.offset = vec2(noise(0.0), noise(10.0)) * config.camera.max_shake_offset * amount;
+    shake.angle = noise(20.0) * config.camera.max_shake_angle * amount;
+}
+
+/// Where the camera goes and how much it zooms out once the camera zones of the level apply.
+/// Each zone weighs more the deeper inside it `target` is, the zones nest in uuid order.
+fn apply_camera_zones(level: &LevelData, blend: f32, target: Vec2, zoom: f32) -> (Vec2, f32) {
+    let mut zones: Vec<_> = level.camera_zones.iter().collect();
+    zones.sort_by_key(|(uuid, _)| **uuid);
+    let (mut framed, mut framed_zoom) = (target, zoom);
+    for (_, zone) in zones {
+        let rect = zone.rect.to_rect();
+        let depth = (target - rect.min).min(rect.max - target).min_element();
+        if depth <= 0.0 {
+            continue;
+        }
+        let t = (depth / blend).min(1.0);
+        let weight = t * t * (3.0 - 2.0 * t);
+        let mut zone_target = zone.fixed.map_or(framed, Vec2::from);
+        if let Some(x) = zone.lock_x {
+            zone_target.x = x;
+        }
+        if let Some(y) = zone.lock_y {
+            zone_target.y = y;
+        }
+        framed = framed.lerp(zone_target, weight);
+        framed_zoom += (zone.zoom.unwrap_or(framed_zoom) - framed_zoom) * weight;
+    }
+    (framed, framed_zoom)
+}
+
+/// Keeps a view of `half_size` centered on `center` inside `bounds`, a level smaller than the
+/// view is centered.
+fn clamp_to_bounds(center: Vec2, half_size: Vec2, bounds: Rect) -> Vec2 {
+    let (min, max) = (bounds.min + half_size, bounds.max - half_size);
+    let clamp = |value: f32, min: f32, max: f32, middle: f32| {
+        if min > max {
+            middle
+        } else {
+            value.clamp(min, max)
+        }
+    };
+    vec2(
+        clamp(center.x, min.x, max.x, bounds.center().x),
+        clamp(center.y, min.y, max.y, bounds.center().y),
+    )
+}
 
 fn update_camera_transform(
     time: Res<Time>,
     destination: Option<Res<CameraDestination>>,
     target_scale_divisor: Option<Res<CameraTargetScaleDivisor>>,
+    speed_zoom: Res<SpeedZoom>,
+    mut shake: ResMut<CameraShake>,
+    focus: Option<Res<CameraFocus>>,
+    level: Res<LevelData>,
+    game_state: Res<State<GameState>>,
+    config: Res<GameConfig>,
     mut q_camera: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
 ) {
+    let half_life = Duration::from_secs_f32(config.camera.half_life);
+    // the editor and the bot setup look around the whole level
+    let framed = matches!(
+        game_state.get(),
+        GameState::Playing | GameState::Pause | GameState::Death | GameState::Victory
+    );
     if let Some(destination) = destination {
-        for (mut transform, mut projection) in &mut q_camera {
-            // translation
-            let dest: Vec2 = smooth_lerp(
-                transform.translation.truncate(),
+        let (target, zoom) = if framed && focus.is_none() {
+            apply_camera_zones(
+                &level,
+                config.camera.zone_blend,
                 destination.0,
-                time.delta(),
-                CAMERA_TRANSFORM_HALF_TIME,
-            );
-
+                speed_zoom.0,
+            )
+        } else {
+            (destination.0, speed_zoom.0)
+        };
+        for (mut transform, mut projection) in &mut q_camera {
             // Zoom
-            transform.translation = dest.extend(transform.translation.z);
+            let scale = projection.scale;
             if let Some(ref divisor) = target_scale_divisor {
                 let dest = smooth_lerp(
                     1.0 / projection.scale,
-                    divisor.0,
+                    divisor.0 / zoom,
                     time.delta(),
-                    CAMERA_TRANSFORM_HALF_TIME,
+                    half_life,
                 );
                 projection.scale = 1.0 / dest;
             }
+
+            // translation
+            let mut dest: Vec2 = smooth_lerp(
+                transform.translation.truncate() - shake.applied,
+                target,
+                time.delta(),
+                half_life,
+            );
+            if let Some(bounds) = level.bounds.filter(|_| framed) {
+                // the area is updated from the scale later in the frame
+                let half_size = projection.area.half_size() / scale * projection.scale;
+                dest = clamp_to_bounds(dest, half_size, bounds.to_rect());
+            }
+            transform.translation = (dest + shake.offset).extend(transform.translation.z);
+            transform.rotation = Quat::from_rotation_z(shake.angle);
         }
+        shake.applied = shake.offset;
     }
 }
 
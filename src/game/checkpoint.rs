@@ -1,32 +1,34 @@
-use bevy::{prelude::*, sprite::Anchor};
-use bevy_rapier2d::prelude::*;
+use bevy::{prelude::*, sprite::Anchor, utils::HashMap};
 use uuid::Uuid;
 
 use crate::{
-    data::{config::GameConfig, level::CheckpointData},
+    data::{
+        config::GameConfig,
+        level::{CheckpointData, LevelData, TriggerAction},
+    },
     screen::Screen,
-    AppSet,
 };
 
 use super::{
     assets::{HandleMap, ImageKey},
+    bolt::CollectedBolts,
     entity_id::EntityId,
     entity_type::EntityType,
-    physics::{coll_groups, ObjectGroup},
-    spawn::player::{LostLimbs, Torso},
-    GameState,
+    level_object::{LevelObject, ObjectKind, ObjectShape, RegisterLevelObject},
+    repair_kit::UsedRepairKits,
+    spawn::{door::ToggledDoors, player::LostLimbs},
+    trigger_volume::{trigger_volume_bundle, TriggerVolume},
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(on_spawn_checkpoint)
+        .observe(on_reach_checkpoint)
         .observe(on_activate_checkpoint)
         .observe(on_deactivate_checkpoint)
         .insert_resource(CurrentActiveCheckpoint(None))
-        .add_systems(
-            Update,
-            check_player_collision
-                .in_set(AppSet::TickTimers)
-                .run_if(in_state(GameState::Playing)),
+        .register_level_object(
+            ObjectKind::<CheckpointData>::new(ObjectShape::Sprite)
+                .menu_entry("Checkpoint", |point| CheckpointData { pos: point.into() }),
         );
 }
 
@@ -45,13 +47,38 @@ pub struct SpawnCheckpoint {
     pub data: CheckpointData,
 }
 
+impl LevelObject for CheckpointData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.checkpoints
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.checkpoints
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnCheckpoint { uuid, data });
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct ActiveCheckpoint {
     pub entity: Entity,
     pub eid: EntityId,
     pub lost_limbs: LostLimbs,
+    pub used_repair_kits: UsedRepairKits,
+    pub collected_bolts: CollectedBolts,
+    pub toggled_doors: ToggledDoors,
 }
 
+/// Makes the checkpoint the active one, unless it already is.
+#[derive(Debug, Event)]
+pub struct ReachCheckpoint(pub EntityId);
+
 #[derive(Debug, Event)]
 pub struct ActivateCheckpoint(EntityId);
 
@@ -61,36 +88,19 @@ pub struct DeactivateCheckpoint(EntityId);
 #[derive(Debug, Resource)]
 pub struct CurrentActiveCheckpoint(pub Option<ActiveCheckpoint>);
 
-pub fn check_player_collision(
+fn on_reach_checkpoint(
+    trigger: Trigger<ReachCheckpoint>,
     mut cmd: Commands,
-    rapier_context: Res<RapierContext>,
-    q_torso: Query<(&GlobalTransform, &Collider), With<Torso>>,
     active_checkpoint: Res<CurrentActiveCheckpoint>,
-    q_checkpoint_colliders: Query<&Parent, With<CheckpointCollider>>,
-    q_checkpoint: Query<&EntityId, With<Checkpoint>>,
 ) {
-    for (body_tr, body_collider) in &q_torso {
-        let shape = body_collider;
-        let body_tr = body_tr.compute_transform();
-        let shape_pos = body_tr.translation.xy();
-        let shape_rot = body_tr.rotation.to_axis_angle().1;
-        let filter = QueryFilter::default()
-            .groups(coll_groups(ObjectGroup::PLAYER, ObjectGroup::CHECKPOINT));
-        rapier_context.intersections_with_shape(shape_pos, shape_rot, shape, filter, |entity| {
-            if let Ok(parent) = q_checkpoint_colliders.get(entity) {
-                if let Ok(eid) = q_checkpoint.get(parent.get()) {
-                    if let Some(ref active_checkpoint) = active_checkpoint.0 {
-                        if active_checkpoint.entity != parent.get() {
-                            cmd.trigger(DeactivateCheckpoint(active_checkpoint.eid));
-                            cmd.trigger(ActivateCheckpoint(*eid));
-                        }
-                    } else {
-                        cmd.trigger(ActivateCheckpoint(*eid));
-                    }
-                }
-            }
-            true
-        });
+    let ReachCheckpoint(eid) = *trigger.event();
+    match active_checkpoint.0 {
+        Some(ref active_checkpoint) if active_checkpoint.eid == eid => {}
+        Some(ref active_checkpoint) => {
+            cmd.trigger(DeactivateCheckpoint(active_checkpoint.eid));
+            cmd.trigger(ActivateCheckpoint(eid));
+        }
+        None => cmd.trigger(ActivateCheckpoint(eid)),
     }
 }
 
@@ -101,6 +111,9 @@ pub fn on_activate_checkpoint(
     config: Res<GameConfig>,
     mut active_checkpoint: ResMut<CurrentActiveCheckpoint>,
     lost_limbs: Res<LostLimbs>,
+    used_repair_kits: Res<UsedRepairKits>,
+    collected_bolts: Res<CollectedBolts>,
+    toggled_doors: Res<ToggledDoors>,
 ) {
     for (e_checkpoint, &id, children) in &q_checkpoint {
         if id == trigger.event().0 {
@@ -108,6 +121,9 @@ pub fn on_activate_checkpoint(
                 eid: trigger.event().0,
                 entity: e_checkpoint,
                 lost_limbs: *lost_limbs,
+                used_repair_kits: used_repair_kits.clone(),
+                collected_bolts: collected_bolts.clone(),
+                toggled_doors: toggled_doors.clone(),
             });
             for child in children {
                 if let Ok((_light, mut sprite)) = q_lights.get_mut(*child) {
@@ -146,7 +162,7 @@ pub fn on_spawn_checkpoint(
 ) {
     cmd.spawn((
         Checkpoint,
-        EntityType::Checkpoint,
+        EntityType::of::<CheckpointData>(),
         EntityId(trigger.event().uuid),
         SpriteBundle {
             sprite: Sprite {
@@ -168,12 +184,10 @@ pub fn on_spawn_checkpoint(
             TransformBundle::from_transform(Transform::from_translation(
                 Vec2::from(config.checkpoint.collider.pos).extend(0.0),
             )),
-            coll_groups(ObjectGroup::CHECKPOINT, ObjectGroup::PLAYER),
-            Collider::cuboid(
-                config.checkpoint.collider.size.x / 2.0,
-                config.checkpoint.collider.size.y / 2.0,
+            trigger_volume_bundle(
+                TriggerVolume::on_enter(TriggerAction::Checkpoint(trigger.event().uuid)),
+                config.checkpoint.collider.size.into(),
             ),
-            // ActiveEvents::COLLISION_EVENTS,
         ));
         cmd.spawn((
             CheckpointLight,
@@ -2,6 +2,7 @@ use bevy::{color::palettes::css::WHITE_SMOKE, prelude::*, window::PrimaryWindow}
 
 use super::{
     assets::{FontKey, HandleMap},
+    keybindings::Keybindings,
     spawn::player::Respawn,
     GameState,
 };
@@ -31,10 +32,11 @@ enum Action {
 
 fn handle_key_press(
     input: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
     mut cmd: Commands,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-    if input.just_pressed(KeyCode::KeyR) {
+    if input.any_just_pressed(keybindings.respawn.iter().copied()) {
         next_game_state.set(GameState::Playing);
         cmd.trigger(Respawn);
     }
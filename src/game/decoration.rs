@@ -0,0 +1,163 @@
+//! Decorations: any image of `assets/images` placed in the level, in front of or behind the
+//! rest of it. They have no collider and take no part in the physics.
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::texture::{ImageLoaderSettings, ImageSampler},
+    utils::HashMap,
+};
+use uuid::Uuid;
+
+use crate::{
+    data::level::{DecorationData, DecorationLayer, LevelData},
+    screen::Screen,
+};
+
+use super::{
+    entity_id::EntityId,
+    entity_type::EntityType,
+    level_object::{LevelObject, ObjectEdited, ObjectKind, ObjectShape, RegisterLevelObject},
+    object_size::{ObjectSize, RepositionRect},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_spawn_decoration)
+        .observe(on_edit_decoration)
+        .observe(on_reposition_decoration)
+        // added from the image picker of the editor
+        .register_level_object(
+            ObjectKind::<DecorationData>::new(ObjectShape::Sized)
+                .resizable()
+                .rotatable(),
+        );
+}
+
+#[derive(Debug, Component)]
+pub struct Decoration;
+
+#[derive(Debug, Event)]
+pub struct SpawnDecoration(pub Uuid, pub DecorationData);
+
+impl LevelObject for DecorationData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.decorations
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.decorations
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnDecoration(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+
+    fn resize(&mut self, _from: Rect, to: Rect) {
+        self.pos = to.center().into();
+        self.size = to.size().into();
+    }
+
+    fn rotate(&mut self, delta: f32) {
+        self.angle = (self.angle + delta).rem_euclid(TAU);
+    }
+
+    fn angle(&self) -> Option<f32> {
+        Some(self.angle)
+    }
+}
+
+/// The z of the decoration, background decorations stay between the parallax backgrounds and
+/// the walls.
+fn decoration_z(data: &DecorationData) -> f32 {
+    let base = match data.layer {
+        DecorationLayer::Background => -5.0,
+        DecorationLayer::Foreground => 10.0,
+    };
+    base + data.order as f32 * 0.01
+}
+
+fn decoration_transform(data: &DecorationData) -> Transform {
+    Transform::from_translation(Vec2::from(data.pos).extend(decoration_z(data)))
+        .with_rotation(Quat::from_rotation_z(data.angle))
+}
+
+fn decoration_sprite(data: &DecorationData) -> Sprite {
+    Sprite {
+        color: data.tint.into(),
+        custom_size: Some(data.size.into()),
+        flip_x: data.flip_x,
+        flip_y: data.flip_y,
+        ..default()
+    }
+}
+
+/// Loads an image of `assets/images`, the way the other images of the game are.
+pub fn load_image(asset_server: &AssetServer, image: &str) -> Handle<Image> {
+    asset_server.load_with_settings(
+        format!("images/{image}"),
+        |settings: &mut ImageLoaderSettings| {
+            settings.sampler = ImageSampler::nearest();
+        },
+    )
+}
+
+fn on_spawn_decoration(
+    trigger: Trigger<SpawnDecoration>,
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let SpawnDecoration(uuid, data) = trigger.event();
+    cmd.spawn((
+        Decoration,
+        EntityId(*uuid),
+        EntityType::of::<DecorationData>(),
+        ObjectSize(data.size.into()),
+        SpriteBundle {
+            sprite: decoration_sprite(data),
+            texture: load_image(&asset_server, &data.image),
+            transform: decoration_transform(data),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn on_edit_decoration(
+    trigger: Trigger<ObjectEdited<DecorationData>>,
+    asset_server: Res<AssetServer>,
+    mut q_decorations: Query<
+        (
+            &mut Transform,
+            &mut Sprite,
+            &mut Handle<Image>,
+            &mut ObjectSize,
+        ),
+        With<Decoration>,
+    >,
+) {
+    let data = &trigger.event().0;
+    if let Ok((mut tr, mut sprite, mut texture, mut size)) = q_decorations.get_mut(trigger.entity())
+    {
+        *tr = decoration_transform(data);
+        *sprite = decoration_sprite(data);
+        *texture = load_image(&asset_server, &data.image);
+        size.0 = data.size.into();
+    }
+}
+
+fn on_reposition_decoration(
+    trigger: Trigger<RepositionRect>,
+    mut q_decorations: Query<(&mut Transform, &mut ObjectSize, &mut Sprite), With<Decoration>>,
+) {
+    if let Ok((mut tr, mut size, mut sprite)) = q_decorations.get_mut(trigger.entity()) {
+        let rect = trigger.event().rect;
+        size.0 = rect.size();
+        sprite.custom_size = Some(rect.size());
+        tr.translation = rect.center().extend(tr.translation.z);
+    }
+}
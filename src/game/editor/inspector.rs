@@ -0,0 +1,107 @@
+//! Panel with the properties of the selected object that can't be edited with the pointer.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    data::{config::GameConfig, level::LevelData},
+    game::{
+        entity_id::EntityId, entity_type::EntityType, level_object::LevelObjects,
+        spawn::wall::ChangeSurface, GameState,
+    },
+    ui::prelude::*,
+};
+
+use super::tool::pointer::selected::CurrentSelected;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            update_inspector
+                .run_if(resource_changed::<CurrentSelected>.or_else(resource_changed::<LevelData>)),
+            handle_surface_button,
+        )
+            .chain()
+            .run_if(in_state(GameState::Editing)),
+    );
+}
+
+#[derive(Component)]
+struct Inspector;
+
+#[derive(Component, Debug)]
+struct SurfaceButton(String);
+
+fn selected_surface<'a>(
+    entity: Option<Entity>,
+    q_entity: &Query<(&EntityType, &EntityId)>,
+    objects: &LevelObjects,
+    level: &'a LevelData,
+) -> Option<&'a str> {
+    let (e_type, e_id) = q_entity.get(entity?).ok()?;
+    objects.get(e_type)?.surface(level, e_id.0)
+}
+
+fn update_inspector(
+    mut cmd: Commands,
+    current_selected: Res<CurrentSelected>,
+    q_inspector: Query<Entity, With<Inspector>>,
+    q_entity: Query<(&EntityType, &EntityId)>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    level: Res<LevelData>,
+    objects: Res<LevelObjects>,
+    config: Res<GameConfig>,
+) {
+    for inspector in &q_inspector {
+        cmd.entity(inspector).despawn_recursive();
+    }
+    let Some(current) = selected_surface(current_selected.0, &q_entity, &objects, &level) else {
+        return;
+    };
+    let mut surfaces: Vec<_> = config.surfaces.keys().collect();
+    surfaces.sort();
+    for window in &q_window {
+        let font_size = window.height() / 40.;
+        cmd.ui_right_root()
+            .insert((Inspector, StateScoped(GameState::Editing)))
+            .with_children(|cmd| {
+                cmd.text("surface");
+                for surface in &surfaces {
+                    let text = if *surface == current {
+                        format!("> {surface}")
+                    } else {
+                        surface.to_string()
+                    };
+                    cmd.button(font_size, text)
+                        .insert(SurfaceButton(surface.to_string()));
+                }
+            });
+    }
+}
+
+fn handle_surface_button(
+    mut cmd: Commands,
+    mut button_query: InteractionQuery<&SurfaceButton>,
+    current_selected: Res<CurrentSelected>,
+    q_entity: Query<(&EntityType, &EntityId)>,
+    mut level: ResMut<LevelData>,
+    objects: Res<LevelObjects>,
+) {
+    for (interaction, SurfaceButton(surface)) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let Some(entity) = current_selected.0 else {
+            continue;
+        };
+        let Ok((e_type, e_id)) = q_entity.get(entity) else {
+            continue;
+        };
+        let Some(kind) = objects.get(e_type) else {
+            continue;
+        };
+        if kind.set_surface(&mut level, e_id.0, surface) {
+            cmd.trigger_targets(ChangeSurface(surface.clone()), entity);
+        }
+    }
+}
@@ -1,19 +1,29 @@
 pub mod camera;
+mod inspector;
 pub mod tool;
 mod ui;
 
-use bevy::prelude::*;
+use bevy::{
+    color::palettes::css::{ORANGE, PURPLE, RED},
+    prelude::*,
+};
 
-use super::GameState;
+use crate::data::level::LevelData;
+
+use super::{object_size::ObjectSize, trigger_volume::LevelTrigger, GameState};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_gizmo_group::<HighlightGizmos>();
-    app.add_plugins((ui::plugin, camera::plugin, tool::plugin));
+    app.add_plugins((ui::plugin, camera::plugin, tool::plugin, inspector::plugin));
     app.add_systems(
         Update,
         check_start_editor_mode.run_if(in_state(GameState::Playing)),
     );
     app.add_systems(OnEnter(GameState::Editing), update_gizmo_config);
+    app.add_systems(
+        Update,
+        (draw_triggers, draw_camera_zones).run_if(in_state(GameState::Editing)),
+    );
     // app.add_systems(OnExit(GameState::Editing), exit_editing);
 }
 
@@ -35,6 +45,31 @@ fn check_start_editor_mode(
 #[derive(Default, Reflect, GizmoConfigGroup)]
 pub struct HighlightGizmos;
 
+/// Level triggers are invisible in game, outline them while editing.
+fn draw_triggers(
+    q_triggers: Query<(&Transform, &ObjectSize), With<LevelTrigger>>,
+    mut gizmos: Gizmos<HighlightGizmos>,
+) {
+    for (tr, size) in &q_triggers {
+        gizmos.rect_2d(tr.translation.truncate(), 0.0, size.0, PURPLE);
+    }
+}
+
+/// Camera zones and the level bounds only exist in the level data, outline them while editing.
+fn draw_camera_zones(level: Res<LevelData>, mut gizmos: Gizmos<HighlightGizmos>) {
+    for zone in level.camera_zones.values() {
+        let rect = zone.rect.to_rect();
+        gizmos.rect_2d(rect.center(), 0.0, rect.size(), ORANGE);
+        if let Some(fixed) = zone.fixed {
+            gizmos.circle_2d(fixed.into(), 0.5, ORANGE);
+        }
+    }
+    if let Some(bounds) = level.bounds {
+        let rect = bounds.to_rect();
+        gizmos.rect_2d(rect.center(), 0.0, rect.size(), RED);
+    }
+}
+
 fn update_gizmo_config(mut config_store: ResMut<GizmoConfigStore>) {
     let (config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
     let line_width = config.line_width;
@@ -0,0 +1,204 @@
+//! Decorations: pick an image from the add menu to place it. The pointer only selects
+//! decorations while the decoration layer is active, toggled from the tool bar. With a
+//! decoration selected, X and Y flip it, F moves it to the other layer and Page Up and Page Down
+//! change its order in the layer.
+
+use bevy::{color::palettes::css::TEAL, prelude::*, window::PrimaryWindow};
+use uuid::Uuid;
+
+use crate::{
+    data::{
+        config::GameConfig,
+        level::{DecorationData, DecorationLayer, LevelData},
+    },
+    game::{
+        decoration::{load_image, Decoration},
+        editor::HighlightGizmos,
+        entity_id::EntityId,
+        level_object::{LevelObject, ObjectEdited},
+        object_size::ObjectSize,
+        GameState,
+    },
+    ui::prelude::*,
+    AppSet, MainCamera,
+};
+
+use super::{
+    pointer::{selected::CurrentSelected, snap_to_grid, PointerState},
+    Tool,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<DecorationLayerActive>()
+        .add_systems(OnEnter(Tool::Decoration), show_picker)
+        .add_systems(OnExit(Tool::Decoration), drop_picker)
+        .add_systems(
+            Update,
+            (handle_picker_button, cancel_check).run_if(in_state(Tool::Decoration)),
+        )
+        .add_systems(
+            Update,
+            edit_check
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(PointerState::Selected)),
+        )
+        .add_systems(
+            Update,
+            draw_decorations.in_set(AppSet::Update).run_if(
+                in_state(GameState::Editing).and_then(|layer: Res<DecorationLayerActive>| layer.0),
+            ),
+        );
+}
+
+/// Size of a new decoration whose image isn't loaded yet.
+const DEFAULT_SIZE: Vec2 = Vec2::splat(4.0);
+
+/// Whether the pointer selects the decorations, and only them.
+#[derive(Debug, Resource, Default)]
+pub struct DecorationLayerActive(pub bool);
+
+/// The images of the picker, loaded while it is open to size the new decoration.
+#[derive(Debug, Resource)]
+struct PickerImages(Vec<(String, Handle<Image>)>);
+
+#[derive(Component, Debug)]
+struct PickerButton(usize);
+
+/// The images under `assets/images`, relative to it.
+fn image_names() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir("assets/images")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".png"))
+        .collect();
+    names.sort();
+    names
+}
+
+fn show_picker(
+    mut cmd: Commands,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    asset_server: Res<AssetServer>,
+) {
+    let images: Vec<_> = image_names()
+        .into_iter()
+        .map(|name| {
+            let handle = load_image(&asset_server, &name);
+            (name, handle)
+        })
+        .collect();
+    for window in &q_window {
+        let font_size = window.height() / 60.;
+        cmd.ui_center_root()
+            .insert(StateScoped(Tool::Decoration))
+            .with_children(|cmd| {
+                for (i, (name, _)) in images.iter().enumerate() {
+                    cmd.button(font_size, name.clone()).insert(PickerButton(i));
+                }
+            });
+    }
+    cmd.insert_resource(PickerImages(images));
+}
+
+fn drop_picker(mut cmd: Commands) {
+    cmd.remove_resource::<PickerImages>();
+}
+
+fn handle_picker_button(
+    mut cmd: Commands,
+    mut button_query: InteractionQuery<&PickerButton>,
+    picker: Res<PickerImages>,
+    images: Res<Assets<Image>>,
+    q_camera: Query<&Transform, With<MainCamera>>,
+    mut level: ResMut<LevelData>,
+    config: Res<GameConfig>,
+    mut next_tool: ResMut<NextState<Tool>>,
+) {
+    for (interaction, PickerButton(i)) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let Ok(camera_tr) = q_camera.get_single() else {
+            continue;
+        };
+        let (name, handle) = &picker.0[*i];
+        let size = images.get(handle).map_or(DEFAULT_SIZE, |image| {
+            image.size_f32() * config.editor.decoration_pixel_size
+        });
+        let data = DecorationData {
+            image: name.clone(),
+            pos: snap_to_grid(camera_tr.translation.xy(), config.editor.grid_size).into(),
+            size: size.into(),
+            angle: 0.0,
+            tint: default(),
+            flip_x: false,
+            flip_y: false,
+            layer: default(),
+            order: 0,
+        };
+        let uuid = Uuid::new_v4();
+        level.decorations.insert(uuid, data.clone());
+        DecorationData::spawn(&mut cmd, uuid, data);
+        next_tool.set(Tool::Pointer);
+    }
+}
+
+fn cancel_check(buttons: Res<ButtonInput<MouseButton>>, mut next_tool: ResMut<NextState<Tool>>) {
+    if buttons.just_pressed(MouseButton::Right) {
+        next_tool.set(Tool::Pointer);
+    }
+}
+
+fn edit_check(
+    mut cmd: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    current_selected: Res<CurrentSelected>,
+    q_decorations: Query<&EntityId, With<Decoration>>,
+    mut level: ResMut<LevelData>,
+) {
+    let edit: fn(&mut DecorationData) = if input.just_pressed(KeyCode::KeyX) {
+        |data| data.flip_x = !data.flip_x
+    } else if input.just_pressed(KeyCode::KeyY) {
+        |data| data.flip_y = !data.flip_y
+    } else if input.just_pressed(KeyCode::KeyF) {
+        |data| {
+            data.layer = match data.layer {
+                DecorationLayer::Background => DecorationLayer::Foreground,
+                DecorationLayer::Foreground => DecorationLayer::Background,
+            }
+        }
+    } else if input.just_pressed(KeyCode::PageUp) {
+        |data| data.order += 1
+    } else if input.just_pressed(KeyCode::PageDown) {
+        |data| data.order -= 1
+    } else {
+        return;
+    };
+    let Some(entity) = current_selected.0 else {
+        return;
+    };
+    let Ok(eid) = q_decorations.get(entity) else {
+        return;
+    };
+    if let Some(data) = level.decorations.get_mut(&eid.0) {
+        edit(data);
+        cmd.trigger_targets(ObjectEdited(data.clone()), entity);
+    }
+}
+
+/// Outlines the decorations while their layer is active, some may be hidden behind the rest.
+fn draw_decorations(
+    q_decorations: Query<(&Transform, &ObjectSize), With<Decoration>>,
+    mut gizmos: Gizmos<HighlightGizmos>,
+) {
+    for (tr, size) in &q_decorations {
+        gizmos.rect_2d(
+            tr.translation.truncate(),
+            tr.rotation.to_axis_angle().1,
+            size.0,
+            TEAL,
+        );
+    }
+}
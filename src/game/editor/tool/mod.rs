@@ -1,18 +1,27 @@
 pub mod add;
+pub mod decoration;
 pub mod pointer;
+pub mod polygon;
+pub mod prefab;
 
 use bevy::prelude::*;
 
 use crate::game::{editor::ui::UpdateToolText, GameState};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((pointer::plugin, add::plugin))
-        .add_sub_state::<Tool>()
-        .enable_state_scoped_entities::<Tool>()
-        .add_systems(
-            Update,
-            (tool_change, check_escape).run_if(in_state(GameState::Editing)),
-        );
+    app.add_plugins((
+        pointer::plugin,
+        add::plugin,
+        polygon::plugin,
+        prefab::plugin,
+        decoration::plugin,
+    ))
+    .add_sub_state::<Tool>()
+    .enable_state_scoped_entities::<Tool>()
+    .add_systems(
+        Update,
+        (tool_change, check_escape).run_if(in_state(GameState::Editing)),
+    );
 }
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates)]
@@ -21,6 +30,9 @@ pub enum Tool {
     #[default]
     Pointer,
     Add,
+    Polygon,
+    Prefab,
+    Decoration,
 }
 
 impl Tool {
@@ -28,6 +40,9 @@ impl Tool {
         match self {
             Tool::Pointer => "Pointer",
             Tool::Add => "Add",
+            Tool::Polygon => "Polygon (click: add point, right click: undo, enter: close)",
+            Tool::Prefab => "Prefab (type a name, enter: save, right click: cancel)",
+            Tool::Decoration => "Decoration (pick an image, right click: cancel)",
         }
     }
 }
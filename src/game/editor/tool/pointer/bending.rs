@@ -0,0 +1,258 @@
+//! Handles on a selected track: one on the inner edge for the radius, one at each end for the
+//! angles.
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::{
+    color::palettes::css::{BLUE, RED},
+    prelude::*,
+};
+
+use crate::{
+    data::{
+        config::GameConfig,
+        level::{LevelData, TrackData},
+    },
+    game::{
+        entity_id::EntityId,
+        spawn::track::{ReshapeTrack, Track},
+    },
+    mouse::MouseScreenCoords,
+    AppSet, MainCamera,
+};
+
+use super::{selected::CurrentSelected, Pointer, PointerState};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(CurrentTrackHandle(None));
+    app.insert_resource(CurrentBending(None));
+    app.add_systems(
+        Update,
+        draw_gizmos
+            .in_set(AppSet::Update)
+            .run_if(in_state(PointerState::Selected)),
+    );
+    app.add_systems(
+        Update,
+        (check_highlighted, check_click)
+            .chain()
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PointerState::Selected)),
+    );
+    app.add_systems(OnExit(PointerState::Bending), clear_current_bending);
+    app.add_systems(
+        Update,
+        (calc_bend, check_bending_click)
+            .chain()
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PointerState::Bending)),
+    );
+}
+
+#[derive(Debug, Resource)]
+pub struct CurrentTrackHandle(pub Option<TrackHandle>);
+
+#[derive(Debug, Clone, Copy)]
+pub struct TrackHandle {
+    entity: Entity,
+    kind: TrackHandleKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrackHandleKind {
+    Radius,
+    StartAngle,
+    EndAngle,
+}
+
+#[derive(Debug, Resource)]
+struct CurrentBending(Option<Bending>);
+
+#[derive(Debug, Clone, Copy)]
+struct Bending {
+    handle: TrackHandle,
+    start_data: TrackData,
+    /// Updated every frame so the angles can be dragged past a full turn.
+    data: TrackData,
+}
+
+const ANGLE_STEP: f32 = 5.0 * PI / 180.0;
+
+fn clear_current_bending(mut current_bending: ResMut<CurrentBending>) {
+    current_bending.0 = None;
+}
+
+fn calc_bend(
+    mut cmd: Commands,
+    pointer: Res<Pointer>,
+    mut current_bending: ResMut<CurrentBending>,
+    config: Res<GameConfig>,
+) {
+    if let Some(ref mut bending) = current_bending.0 {
+        if let Some(mouse) = pointer.0 {
+            let data = calc_bending(
+                bending.data,
+                mouse,
+                bending.handle.kind,
+                config.editor.grid_size,
+            );
+            if data != bending.data {
+                bending.data = data;
+                cmd.trigger_targets(ReshapeTrack(data), bending.handle.entity);
+            }
+        }
+    }
+}
+
+fn calc_bending(
+    mut data: TrackData,
+    mouse: Vec2,
+    kind: TrackHandleKind,
+    grid_size: f32,
+) -> TrackData {
+    let offset = mouse - Vec2::from(data.center);
+    let angle = (offset.y.atan2(offset.x) / ANGLE_STEP).round() * ANGLE_STEP;
+    // the closest way around from the current angle, so dragging keeps going past a turn
+    let unwrap = |current: f32| current + (angle - current + PI).rem_euclid(TAU) - PI;
+    match kind {
+        TrackHandleKind::Radius => {
+            data.radius = ((offset.length() / grid_size).round() * grid_size).max(grid_size);
+        }
+        TrackHandleKind::StartAngle => {
+            let start = unwrap(data.start_angle);
+            if start != data.end_angle && (data.end_angle - start).abs() <= TAU {
+                data.start_angle = start;
+            }
+        }
+        TrackHandleKind::EndAngle => {
+            let end = unwrap(data.end_angle);
+            if end != data.start_angle && (end - data.start_angle).abs() <= TAU {
+                data.end_angle = end;
+            }
+        }
+    }
+    data
+}
+
+fn check_bending_click(
+    mut cmd: Commands,
+    mut current_bending: ResMut<CurrentBending>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut next_state: ResMut<NextState<PointerState>>,
+    mut level_data: ResMut<LevelData>,
+    q_entity: Query<(&EntityId, &GlobalTransform)>,
+) {
+    if let Some(bending) = current_bending.0 {
+        if buttons.just_pressed(MouseButton::Right) {
+            cmd.trigger_targets(ReshapeTrack(bending.start_data), bending.handle.entity);
+            current_bending.0 = None;
+            next_state.set(PointerState::Selected);
+        }
+        if buttons.just_pressed(MouseButton::Left) {
+            current_bending.0 = None;
+            next_state.set(PointerState::Selected);
+            if let Ok((e_id, gt)) = q_entity.get(bending.handle.entity) {
+                let track = level_data
+                    .tracks
+                    .get_mut(&e_id.0)
+                    .expect("this level track data should exist");
+                *track = TrackData {
+                    center: gt.translation().truncate().into(),
+                    ..bending.data
+                };
+            }
+        }
+    }
+}
+
+fn check_click(
+    highlighted_handle: Res<CurrentTrackHandle>,
+    mut current_bending: ResMut<CurrentBending>,
+    q_tracks: Query<(&Transform, &Track)>,
+    mut next_state: ResMut<NextState<PointerState>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+) {
+    if buttons.just_pressed(MouseButton::Left) {
+        if let Some(handle) = highlighted_handle.0 {
+            if let Ok((tr, Track(data))) = q_tracks.get(handle.entity) {
+                let data = TrackData {
+                    center: tr.translation.truncate().into(),
+                    ..*data
+                };
+                current_bending.0 = Some(Bending {
+                    handle,
+                    start_data: data,
+                    data,
+                });
+                next_state.set(PointerState::Bending);
+            }
+        }
+    }
+}
+
+fn check_highlighted(
+    mut highlighted_handle: ResMut<CurrentTrackHandle>,
+    mouse_wc: Res<MouseScreenCoords>,
+    current_selected: Res<CurrentSelected>,
+    q_tracks: Query<(&Transform, &Track)>,
+    camera_query: Query<&OrthographicProjection, With<MainCamera>>,
+) {
+    if let Some(mouse_wc) = mouse_wc.0 {
+        if let Some(selected) = current_selected.0 {
+            if let Ok((tr, Track(data))) = q_tracks.get(selected) {
+                let projection = camera_query.single();
+                for (kind, pos) in handle_positions(tr, data) {
+                    if Rect::from_center_size(pos, Vec2::splat(HANDLE_SIZE * projection.scale))
+                        .contains(mouse_wc)
+                    {
+                        highlighted_handle.0 = Some(TrackHandle {
+                            entity: selected,
+                            kind,
+                        });
+                        return;
+                    }
+                }
+            }
+        }
+    }
+    highlighted_handle.0 = None;
+}
+
+fn draw_gizmos(
+    mut gizmos: Gizmos,
+    current_selected: Res<CurrentSelected>,
+    q_tracks: Query<(&Transform, &Track)>,
+    camera_query: Query<&OrthographicProjection, With<MainCamera>>,
+    highlighted_handle: Res<CurrentTrackHandle>,
+) {
+    if let Some(selected) = current_selected.0 {
+        if let Ok((tr, Track(data))) = q_tracks.get(selected) {
+            let projection = camera_query.single();
+            for (kind, pos) in handle_positions(tr, data) {
+                let mut color = BLUE;
+                if let Some(ref highlighted) = highlighted_handle.0 {
+                    if highlighted.kind == kind {
+                        color = RED;
+                    }
+                }
+                gizmos.circle_2d(pos, HANDLE_SIZE * projection.scale / 2.0, color);
+            }
+        }
+    }
+}
+
+const HANDLE_SIZE: f32 = 0.5;
+
+fn handle_positions(tr: &Transform, data: &TrackData) -> [(TrackHandleKind, Vec2); 3] {
+    let center = tr.translation.truncate();
+    let at = |angle: f32, radius: f32| center + Vec2::new(angle.cos(), angle.sin()) * radius;
+    let middle = data.radius + data.thickness / 2.0;
+    [
+        (
+            TrackHandleKind::Radius,
+            at(data.start_angle + data.span() / 2.0, data.radius),
+        ),
+        (TrackHandleKind::StartAngle, at(data.start_angle, middle)),
+        (TrackHandleKind::EndAngle, at(data.end_angle, middle)),
+    ]
+}
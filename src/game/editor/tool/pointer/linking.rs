@@ -0,0 +1,106 @@
+//! Links between switches and doors: with a switch selected, press L over a door to link it to
+//! the switch, or to unlink it if it already was.
+
+use bevy::{
+    color::palettes::css::{AQUA, YELLOW},
+    prelude::*,
+};
+
+use crate::{
+    data::level::{LevelData, TriggerAction},
+    game::{
+        editor::HighlightGizmos,
+        entity_id::EntityId,
+        object_size::ObjectSize,
+        spawn::door::Door,
+        switch::{RelinkSwitch, Switch},
+        trigger_volume::{LevelTrigger, TriggerVolume},
+        GameState,
+    },
+    AppSet,
+};
+
+use super::{selected::CurrentSelected, sized_contains, Pointer, PointerState};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        draw_links
+            .in_set(AppSet::Update)
+            .run_if(in_state(GameState::Editing)),
+    );
+    app.add_systems(
+        Update,
+        link_check
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PointerState::Selected)),
+    );
+}
+
+fn link_check(
+    mut cmd: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    current_selected: Res<CurrentSelected>,
+    pointer: Res<Pointer>,
+    q_switches: Query<&EntityId, With<Switch>>,
+    q_doors: Query<(&EntityId, &ObjectSize, &GlobalTransform), With<Door>>,
+    mut level_data: ResMut<LevelData>,
+) {
+    if !input.just_pressed(KeyCode::KeyL) {
+        return;
+    }
+    let (Some(selected), Some(point)) = (current_selected.0, pointer.0) else {
+        return;
+    };
+    let Ok(switch_id) = q_switches.get(selected) else {
+        return;
+    };
+    // the doors of prefab instances are linked from within their prefab
+    let Some((door_id, _, _)) = q_doors.iter().find(|(eid, size, gt)| {
+        sized_contains(gt, size.0, point) && !level_data.prefab_objects.contains_key(&eid.0)
+    }) else {
+        return;
+    };
+    let switch = level_data
+        .switches
+        .get_mut(&switch_id.0)
+        .expect("this level switch data should exist");
+    if switch.doors.contains(&door_id.0) {
+        switch.doors.retain(|door| *door != door_id.0);
+    } else {
+        switch.doors.push(door_id.0);
+    }
+    cmd.trigger_targets(RelinkSwitch(switch.doors.clone()), selected);
+}
+
+/// Where every door opens to, and lines from the switches and triggers to the doors they open.
+fn draw_links(
+    q_switches: Query<(&Switch, &GlobalTransform)>,
+    q_triggers: Query<(&TriggerVolume, &GlobalTransform), With<LevelTrigger>>,
+    q_doors: Query<(&EntityId, &Door, &ObjectSize)>,
+    mut gizmos: Gizmos<HighlightGizmos>,
+) {
+    let door_center = |uuid| {
+        q_doors
+            .iter()
+            .find(|(eid, _, _)| eid.0 == uuid)
+            .map(|(_, door, _)| door.closed)
+    };
+    for (_, door, size) in &q_doors {
+        gizmos.rect_2d(door.closed + door.open_offset, 0.0, size.0, YELLOW);
+    }
+    for (Switch(data), gt) in &q_switches {
+        for center in data.doors.iter().filter_map(|uuid| door_center(*uuid)) {
+            gizmos.line_2d(gt.translation().truncate(), center, AQUA);
+        }
+    }
+    for (volume, gt) in &q_triggers {
+        for action in volume.on_enter.iter().chain(&volume.on_exit) {
+            if let TriggerAction::Door(uuid) = action {
+                if let Some(center) = door_center(*uuid) {
+                    gizmos.line_2d(gt.translation().truncate(), center, AQUA);
+                }
+            }
+        }
+    }
+}
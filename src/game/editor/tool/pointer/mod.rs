@@ -1,21 +1,39 @@
+pub mod bending;
+pub mod linking;
 pub mod moving;
 pub mod pointing;
 pub mod resizing;
+pub mod routing;
 pub mod selected;
 
 use crate::{
-    data::config::GameConfig,
-    game::{editor::HighlightGizmos, entity_type::EntityType, object_size::ObjectSize, GameState},
+    data::{
+        config::GameConfig,
+        level::{LevelData, TrackData},
+    },
+    game::{
+        decoration::Decoration,
+        editor::{tool::decoration::DecorationLayerActive, HighlightGizmos},
+        entity_id::EntityId,
+        entity_type::EntityType,
+        level_object::{LevelObjects, ObjectShape},
+        object_size::ObjectSize,
+        spawn::{track::Track, wall::PolygonWall},
+        GameState,
+    },
     mouse::{update_mouse_coords, MouseScreenCoords},
-    AppSet,
+    polygon, track, AppSet,
 };
+use bending::CurrentTrackHandle;
 use bevy::{
     color::palettes::css::{GREEN, RED},
+    ecs::system::SystemParam,
     prelude::*,
 };
 use moving::CurrentMove;
 use pointing::CurrentHighlight;
 use resizing::CurrentHighlightedHandle;
+use routing::CurrentWaypointHandle;
 use selected::CurrentSelected;
 
 use super::Tool;
@@ -29,6 +47,9 @@ pub(super) fn plugin(app: &mut App) {
             selected::plugin,
             moving::plugin,
             resizing::plugin,
+            bending::plugin,
+            routing::plugin,
+            linking::plugin,
         ))
         .add_systems(
             Update,
@@ -53,6 +74,8 @@ pub enum PointerState {
     Selected,
     Moving,
     Resizing,
+    Bending,
+    Routing,
 }
 
 #[derive(Debug)]
@@ -62,7 +85,7 @@ enum GizmoType {
 }
 
 #[derive(Debug, Resource)]
-pub struct Pointer(Option<Vec2>);
+pub struct Pointer(pub Option<Vec2>);
 
 fn update_pointer(
     mouse: Res<MouseScreenCoords>,
@@ -82,11 +105,15 @@ fn clear_resources(
     mut current_highlight: ResMut<CurrentHighlight>,
     mut current_move: ResMut<CurrentMove>,
     mut current_highlight_handle: ResMut<CurrentHighlightedHandle>,
+    mut current_track_handle: ResMut<CurrentTrackHandle>,
+    mut current_waypoint_handle: ResMut<CurrentWaypointHandle>,
 ) {
     current_highlight.0 = None;
     current_selected.0 = None;
     current_move.0 = None;
     current_highlight_handle.0 = None;
+    current_track_handle.0 = None;
+    current_waypoint_handle.0 = None;
 }
 
 fn show_highlighted_gizmos(
@@ -95,7 +122,10 @@ fn show_highlighted_gizmos(
     current_selected: Res<CurrentSelected>,
     q_sized: Query<(&Transform, &ObjectSize)>,
     q_entity_type: Query<&EntityType>,
+    objects: Res<LevelObjects>,
     q_sprite_entity: Query<(&Transform, &Sprite)>,
+    q_polygon: Query<(&Transform, &PolygonWall)>,
+    q_track: Query<(&Transform, &Track)>,
     mut gizmos: Gizmos<HighlightGizmos>,
 ) {
     let mut gizmo = |gizmo_type: GizmoType, entity: Entity| {
@@ -104,7 +134,10 @@ fn show_highlighted_gizmos(
             gizmo_type,
             &q_sized,
             &q_entity_type,
+            &objects,
             &q_sprite_entity,
+            &q_polygon,
+            &q_track,
             &mut gizmos,
         );
     };
@@ -135,21 +168,31 @@ fn draw_gizmo(
     gizmo_type: GizmoType,
     q_sized: &Query<(&Transform, &ObjectSize)>,
     q_entity_type: &Query<&EntityType>,
+    objects: &LevelObjects,
     q_sprite_entity: &Query<(&Transform, &Sprite)>,
+    q_polygon: &Query<(&Transform, &PolygonWall)>,
+    q_track: &Query<(&Transform, &Track)>,
     gizmos: &mut Gizmos<HighlightGizmos>,
 ) {
-    if let Ok(entity_type) = q_entity_type.get(entity) {
-        match entity_type {
-            EntityType::Wall => {
-                draw_sized_gizmo(q_sized, entity, gizmos, &gizmo_type);
-            }
-            EntityType::Checkpoint
-            | EntityType::Goal
-            | EntityType::SpaceTutorial
-            | EntityType::ArrowTutorial
-            | EntityType::Arrow => {
-                draw_sprite_gizmo(q_sprite_entity, entity, gizmos, gizmo_type);
-            }
+    let Some(kind) = q_entity_type
+        .get(entity)
+        .ok()
+        .and_then(|e_type| objects.get(e_type))
+    else {
+        return;
+    };
+    match kind.shape {
+        ObjectShape::Sized => {
+            draw_sized_gizmo(q_sized, entity, gizmos, &gizmo_type);
+        }
+        ObjectShape::Polygon => {
+            draw_polygon_gizmo(q_polygon, entity, gizmos, &gizmo_type);
+        }
+        ObjectShape::Track => {
+            draw_track_gizmo(q_track, entity, gizmos, &gizmo_type);
+        }
+        ObjectShape::Sprite => {
+            draw_sprite_gizmo(q_sprite_entity, entity, gizmos, gizmo_type);
         }
     }
 }
@@ -173,6 +216,95 @@ fn draw_sized_gizmo(
     }
 }
 
+fn draw_polygon_gizmo(
+    q_polygon: &Query<(&Transform, &PolygonWall)>,
+    entity: Entity,
+    gizmos: &mut Gizmos<HighlightGizmos>,
+    gizmo_type: &GizmoType,
+) {
+    if let Ok((tr, PolygonWall(points))) = q_polygon.get(entity) {
+        let world_points = points
+            .iter()
+            .map(|point| tr.transform_point(point.extend(0.0)).truncate());
+        gizmos.linestrip_2d(
+            world_points.clone().chain(world_points.take(1)),
+            match *gizmo_type {
+                GizmoType::Selected => RED,
+                GizmoType::Highlighted => GREEN,
+            },
+        );
+    }
+}
+
+fn draw_track_gizmo(
+    q_track: &Query<(&Transform, &Track)>,
+    entity: Entity,
+    gizmos: &mut Gizmos<HighlightGizmos>,
+    gizmo_type: &GizmoType,
+) {
+    if let Ok((tr, Track(data))) = q_track.get(entity) {
+        let world_points = track::outline(data, 0.0, 1.0)
+            .into_iter()
+            .map(|point| tr.translation.truncate() + point);
+        gizmos.linestrip_2d(
+            world_points.clone().chain(world_points.take(1)),
+            match *gizmo_type {
+                GizmoType::Selected => RED,
+                GizmoType::Highlighted => GREEN,
+            },
+        );
+    }
+}
+
+/// Whether `point` is on a sized object, taking its rotation into account.
+pub fn sized_contains(gt: &GlobalTransform, size: Vec2, point: Vec2) -> bool {
+    let local = gt.affine().inverse().transform_point3(point.extend(0.0));
+    Rect::from_center_size(Vec2::ZERO, size).contains(local.truncate())
+}
+
+/// Whether `point` is on a track, tracks are never rotated.
+pub fn track_contains(gt: &GlobalTransform, data: &TrackData, point: Vec2) -> bool {
+    track::contains(data, point - gt.translation().truncate())
+}
+
+/// The entities the pointer can pick. The objects spawned from prefab instances are edited
+/// through their prefab, and decorations are only picked while their layer is active.
+#[derive(SystemParam)]
+pub struct Selectable<'w, 's> {
+    q_ids: Query<
+        'w,
+        's,
+        (
+            Option<&'static EntityId>,
+            Option<&'static Parent>,
+            Has<Decoration>,
+        ),
+    >,
+    level: Res<'w, LevelData>,
+    decoration_layer: Res<'w, DecorationLayerActive>,
+}
+
+impl Selectable<'_, '_> {
+    pub fn contains(&self, entity: Entity) -> bool {
+        // the parts of an object belong to the closest parent with an id
+        let mut entity = Some(entity);
+        while let Some((id, parent, decoration)) = entity.and_then(|e| self.q_ids.get(e).ok()) {
+            if let Some(id) = id {
+                return !self.level.prefab_objects.contains_key(&id.0)
+                    && decoration == self.decoration_layer.0;
+            }
+            entity = parent.map(Parent::get);
+        }
+        !self.decoration_layer.0
+    }
+}
+
+/// Whether `point` is on a polygon wall.
+pub fn polygon_contains(gt: &GlobalTransform, points: &[Vec2], point: Vec2) -> bool {
+    let local = gt.affine().inverse().transform_point3(point.extend(0.0));
+    polygon::contains(points, local.truncate())
+}
+
 fn draw_sprite_gizmo(
     q_sprite_entity: &Query<(&Transform, &Sprite)>,
     entity: Entity,
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 
 use crate::{
     data::level::LevelData,
-    game::{entity_id::EntityId, entity_type::EntityType, object_size::ObjectSize},
+    game::{entity_id::EntityId, entity_type::EntityType, level_object::LevelObjects},
     AppSet,
 };
 
@@ -33,6 +33,9 @@ pub struct CurrentMove(pub Option<MoveOp>);
 #[derive(Debug, Clone, Copy)]
 pub struct MoveOp {
     pub entity: Entity,
+    /// Where the entity was, the level data is moved by how far it went from there.
+    pub start: Vec2,
+    /// `start` snapped to the grid, the entity follows the mouse from there.
     pub origin: Vec2,
     pub mouse_origin: Vec2,
 }
@@ -59,60 +62,18 @@ fn move_op(
 
 fn on_commit_move(
     _trigger: Trigger<CommitMove>,
+    mut cmd: Commands,
     mut current_move: ResMut<CurrentMove>,
     mut level_data: ResMut<LevelData>,
     mut next_state: ResMut<NextState<PointerState>>,
-    q_sized: Query<&ObjectSize>,
+    objects: Res<LevelObjects>,
     q_entity: Query<(&Transform, &EntityType, &EntityId)>,
 ) {
     if let Some(ref mut move_op) = current_move.0 {
         if let Ok((tr, e_type, e_id)) = q_entity.get(move_op.entity) {
-            match e_type {
-                EntityType::Wall => {
-                    let size = q_sized
-                        .get(move_op.entity)
-                        .expect("The wall should have an ObjectSize");
-                    let wall = level_data
-                        .walls
-                        .get_mut(&e_id.0)
-                        .expect("this level wall data should exist");
-                    wall.rect = Rect::from_center_size(tr.translation.truncate(), size.0).into();
-                }
-                EntityType::Checkpoint => {
-                    let checkpoint = level_data
-                        .checkpoints
-                        .get_mut(&e_id.0)
-                        .expect("this level checkpoint data should exist");
-                    checkpoint.pos = tr.translation.truncate().into();
-                }
-                EntityType::Goal => {
-                    let goal = level_data
-                        .goals
-                        .get_mut(&e_id.0)
-                        .expect("this goal data should exist");
-                    goal.pos = tr.translation.truncate().into();
-                }
-                EntityType::SpaceTutorial => {
-                    let space_tutorial = level_data
-                        .space_tutorials
-                        .get_mut(&e_id.0)
-                        .expect("this space tutorial data should exist");
-                    space_tutorial.pos = tr.translation.truncate().into();
-                }
-                EntityType::ArrowTutorial => {
-                    let arrow_tutorial = level_data
-                        .arrow_tutorials
-                        .get_mut(&e_id.0)
-                        .expect("this arrow tutorial data should exist");
-                    arrow_tutorial.pos = tr.translation.truncate().into();
-                }
-                EntityType::Arrow => {
-                    let arrow = level_data
-                        .arrows
-                        .get_mut(&e_id.0)
-                        .expect("this arrow data should exist");
-                    arrow.pos = tr.translation.truncate().into();
-                }
+            if let Some(kind) = objects.get(e_type) {
+                let offset = tr.translation.truncate() - move_op.start;
+                kind.translate(&mut cmd, &mut level_data, move_op.entity, e_id.0, offset);
             }
         }
     }
@@ -124,20 +85,11 @@ fn on_cancel_move(
     _trigger: Trigger<CancelMove>,
     mut current_move: ResMut<CurrentMove>,
     mut next_state: ResMut<NextState<PointerState>>,
-    mut q_entity: Query<(&mut Transform, &EntityType)>,
+    mut q_entity: Query<&mut Transform, With<EntityType>>,
 ) {
     if let Some(ref mut move_op) = current_move.0 {
-        if let Ok((mut tr, e_type)) = q_entity.get_mut(move_op.entity) {
-            match e_type {
-                EntityType::Wall
-                | EntityType::ArrowTutorial
-                | EntityType::Checkpoint
-                | EntityType::Goal
-                | EntityType::SpaceTutorial
-                | EntityType::Arrow => {
-                    tr.translation = move_op.origin.extend(tr.translation.z);
-                }
-            }
+        if let Ok(mut tr) = q_entity.get_mut(move_op.entity) {
+            tr.translation = move_op.origin.extend(tr.translation.z);
         }
     }
     current_move.0 = None;
@@ -1,8 +1,18 @@
 use bevy::prelude::*;
 
-use crate::{game::object_size::ObjectSize, mouse::MouseScreenCoords, AppSet};
+use crate::{
+    game::{
+        object_size::ObjectSize,
+        spawn::{track::Track, wall::PolygonWall},
+    },
+    mouse::MouseScreenCoords,
+    AppSet,
+};
 
-use super::{selected::CurrentSelected, PointerState};
+use super::{
+    polygon_contains, selected::CurrentSelected, sized_contains, track_contains, PointerState,
+    Selectable,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.insert_resource(CurrentHighlight(None));
@@ -21,12 +31,27 @@ pub struct CurrentHighlight(pub Option<Entity>);
 fn highlight_check(
     mouse_wc: Res<MouseScreenCoords>,
     q_sized: Query<(Entity, &ObjectSize, &GlobalTransform)>,
+    q_polygon: Query<(Entity, &PolygonWall, &GlobalTransform)>,
+    q_track: Query<(Entity, &Track, &GlobalTransform)>,
     q_sprites: Query<(Entity, &Sprite, &GlobalTransform)>,
+    selectable: Selectable,
     mut current_highlight: ResMut<CurrentHighlight>,
 ) {
     let Some(point) = mouse_wc.0 else { return };
     for (e, ObjectSize(size), gt) in &q_sized {
-        if Rect::from_center_size(gt.translation().truncate(), *size).contains(point) {
+        if sized_contains(gt, *size, point) && selectable.contains(e) {
+            current_highlight.0 = Some(e);
+            return;
+        }
+    }
+    for (e, PolygonWall(points), gt) in &q_polygon {
+        if polygon_contains(gt, points, point) && selectable.contains(e) {
+            current_highlight.0 = Some(e);
+            return;
+        }
+    }
+    for (e, Track(data), gt) in &q_track {
+        if track_contains(gt, data, point) && selectable.contains(e) {
             current_highlight.0 = Some(e);
             return;
         }
@@ -44,6 +69,7 @@ fn highlight_check(
         if let Some(size) = custom_size {
             if Rect::from_center_size(gt.translation().truncate() - anchor.as_vec() * *size, *size)
                 .contains(point)
+                && selectable.contains(e)
             {
                 // warn!("yoh");
                 current_highlight.0 = Some(e);
@@ -10,8 +10,8 @@ use crate::{
         editor::tool::pointer::snap_to_grid,
         entity_id::EntityId,
         entity_type::EntityType,
+        level_object::LevelObjects,
         object_size::{ObjectSize, RepositionRect},
-        spawn::wall::Wall,
     },
     mouse::MouseScreenCoords,
     AppSet, MainCamera,
@@ -45,6 +45,11 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
+/// Whether the objects of this type have resize handles.
+fn is_resizable(objects: &LevelObjects, e_type: &EntityType) -> bool {
+    objects.get(e_type).is_some_and(|kind| kind.resizable)
+}
+
 #[derive(Debug, Resource)]
 pub struct CurrentHighlightedHandle(pub Option<ResizeHandle>);
 
@@ -60,7 +65,9 @@ struct CurrentResizing(Option<Resizing>);
 #[derive(Debug, Clone, Copy)]
 struct Resizing {
     handle: ResizeHandle,
+    /// The rect before rotation, the wall is rotated by `angle` around its center.
     start_rect: Rect,
+    angle: f32,
     // start_translate: Vec2,
     // start_size: Vec2,
     mouse_start: Vec2,
@@ -82,11 +89,15 @@ fn calc_resize(
     mut cmd: Commands,
     pointer: Res<Pointer>,
     current_resizing: ResMut<CurrentResizing>,
-    q_walls: Query<Entity, With<Wall>>,
+    objects: Res<LevelObjects>,
+    q_entity_type: Query<&EntityType>,
 ) {
     if let Some(resizing) = current_resizing.0 {
         if let Some(mouse) = pointer.0 {
-            if q_walls.get(resizing.handle.entity).is_ok() {
+            if q_entity_type
+                .get(resizing.handle.entity)
+                .is_ok_and(|e_type| is_resizable(&objects, e_type))
+            {
                 let new_rect = calc_resizing(resizing, mouse);
                 cmd.trigger_targets(RepositionRect { rect: new_rect }, resizing.handle.entity);
             }
@@ -95,10 +106,12 @@ fn calc_resize(
 }
 
 fn calc_resizing(resizing: Resizing, mouse: Vec2) -> Rect {
-    let (start_rect, mouse_start) = (resizing.start_rect, resizing.mouse_start);
-    let mouse_offset = mouse - mouse_start;
+    // resize in the wall local space, then move the center back to world space
+    let rotation = Rot2::radians(resizing.angle);
+    let start_rect = Rect::from_center_size(Vec2::ZERO, resizing.start_rect.size());
+    let mouse_offset = rotation.inverse() * (mouse - resizing.mouse_start);
 
-    match resizing.handle.kind {
+    let local_rect = match resizing.handle.kind {
         HandleKind::TopLeft => Rect::from_corners(
             vec2(start_rect.min.x, start_rect.max.y) + mouse_offset,
             vec2(start_rect.max.x, start_rect.min.y),
@@ -115,7 +128,11 @@ fn calc_resizing(resizing: Resizing, mouse: Vec2) -> Rect {
             vec2(start_rect.max.x, start_rect.min.y) + mouse_offset,
             vec2(start_rect.min.x, start_rect.max.y),
         ),
-    }
+    };
+    Rect::from_center_size(
+        resizing.start_rect.center() + rotation * local_rect.center(),
+        local_rect.size(),
+    )
 }
 
 fn check_resizing_click(
@@ -124,6 +141,7 @@ fn check_resizing_click(
     buttons: Res<ButtonInput<MouseButton>>,
     mut next_state: ResMut<NextState<PointerState>>,
     mut level_data: ResMut<LevelData>,
+    objects: Res<LevelObjects>,
     q_entity: Query<(&EntityType, &EntityId)>,
     pointer: Res<Pointer>,
 ) {
@@ -142,21 +160,15 @@ fn check_resizing_click(
             current_resizing.0 = None;
             next_state.set(PointerState::Selected);
             if let Ok((e_type, e_id)) = q_entity.get(resizing.handle.entity) {
-                if let Some(mouse) = pointer.0 {
-                    match e_type {
-                        EntityType::Wall => {
-                            let wall = level_data
-                                .walls
-                                .get_mut(&e_id.0)
-                                .expect("this level wall data should exist");
-                            wall.rect = calc_resizing(resizing, mouse).into();
-                        }
-                        EntityType::Checkpoint
-                        | EntityType::Goal
-                        | EntityType::ArrowTutorial
-                        | EntityType::Arrow
-                        | EntityType::SpaceTutorial => {}
-                    }
+                if let (Some(kind), Some(mouse)) = (objects.get(e_type), pointer.0) {
+                    kind.resize(
+                        &mut cmd,
+                        &mut level_data,
+                        resizing.handle.entity,
+                        e_id.0,
+                        resizing.start_rect,
+                        calc_resizing(resizing, mouse),
+                    );
                 }
             }
         }
@@ -166,7 +178,8 @@ fn check_resizing_click(
 fn check_click(
     highlighted_handle: Res<CurrentHighlightedHandle>,
     mut current_resizing: ResMut<CurrentResizing>,
-    q_walls: Query<(&Transform, &ObjectSize), With<Wall>>,
+    q_walls: Query<(&Transform, &ObjectSize, &EntityType)>,
+    objects: Res<LevelObjects>,
     pointer: Res<Pointer>,
     mut next_state: ResMut<NextState<PointerState>>,
     buttons: Res<ButtonInput<MouseButton>>,
@@ -174,18 +187,29 @@ fn check_click(
 ) {
     if buttons.just_pressed(MouseButton::Left) {
         if let Some(handle) = highlighted_handle.0 {
-            if let Ok((tr, size)) = q_walls.get(handle.entity) {
+            if let Some((tr, size, _)) = q_walls
+                .get(handle.entity)
+                .ok()
+                .filter(|(_, _, e_type)| is_resizable(&objects, e_type))
+            {
                 if let Some(pointer) = pointer.0 {
                     warn!("resizing");
                     let rect = Rect::from_center_size(tr.translation.xy(), size.0);
+                    let angle = tr.rotation.to_axis_angle().1;
                     let grid_size = config.editor.grid_size;
-                    let rect = Rect::from_corners(
-                        snap_to_grid(rect.min, grid_size),
-                        snap_to_grid(rect.max, grid_size),
-                    );
+                    // a rotated wall's corners aren't on the grid anyway
+                    let rect = if angle == 0.0 {
+                        Rect::from_corners(
+                            snap_to_grid(rect.min, grid_size),
+                            snap_to_grid(rect.max, grid_size),
+                        )
+                    } else {
+                        rect
+                    };
                     current_resizing.0 = Some(Resizing {
                         handle,
                         start_rect: rect,
+                        angle,
                         mouse_start: pointer,
                     });
                     next_state.set(PointerState::Resizing);
@@ -199,12 +223,17 @@ fn check_highlighted(
     mut highlighted_handle: ResMut<CurrentHighlightedHandle>,
     mouse_wc: Res<MouseScreenCoords>,
     current_selected: Res<CurrentSelected>,
-    q_walls: Query<(&Transform, &ObjectSize), With<Wall>>,
+    q_walls: Query<(&Transform, &ObjectSize, &EntityType)>,
+    objects: Res<LevelObjects>,
     camera_query: Query<&OrthographicProjection, With<MainCamera>>,
 ) {
     if let Some(mouse_wc) = mouse_wc.0 {
         if let Some(selected) = current_selected.0 {
-            if let Ok((tr, size)) = q_walls.get(selected) {
+            if let Some((tr, size, _)) = q_walls
+                .get(selected)
+                .ok()
+                .filter(|(_, _, e_type)| is_resizable(&objects, e_type))
+            {
                 let projection = camera_query.single();
                 let handle_positions = HandlePos::all_handle_pos(tr, size.0, projection);
                 for handle_pos in &handle_positions {
@@ -227,12 +256,17 @@ fn check_highlighted(
 fn draw_gizmos(
     mut gizmos: Gizmos,
     current_selected: Res<CurrentSelected>,
-    q_walls: Query<(&Transform, &ObjectSize), With<Wall>>,
+    q_walls: Query<(&Transform, &ObjectSize, &EntityType)>,
+    objects: Res<LevelObjects>,
     camera_query: Query<&OrthographicProjection, With<MainCamera>>,
     highlighted_handle: ResMut<CurrentHighlightedHandle>,
 ) {
     if let Some(selected) = current_selected.0 {
-        if let Ok((tr, size)) = q_walls.get(selected) {
+        if let Some((tr, size, _)) = q_walls
+            .get(selected)
+            .ok()
+            .filter(|(_, _, e_type)| is_resizable(&objects, e_type))
+        {
             let projection = camera_query.single();
             let handle_positions = HandlePos::all_handle_pos(tr, size.0, projection);
             for handle_pos in &handle_positions {
@@ -268,14 +302,17 @@ impl HandlePos {
         object_size: Vec2,
         camera_projection: &OrthographicProjection,
     ) -> Self {
-        let rect = Rect::from_center_size(object_tr.translation.xy(), object_size)
+        let rect = Rect::from_center_size(Vec2::ZERO, object_size)
             .inflate(HANDLE_MARGIN * camera_projection.scale);
-        let world_translation = match kind {
+        let local_translation = match kind {
             HandleKind::TopLeft => vec2(rect.min.x, rect.max.y),
             HandleKind::TopRight => vec2(rect.max.x, rect.max.y),
             HandleKind::BottomLeft => vec2(rect.min.x, rect.min.y),
             HandleKind::BottomRight => vec2(rect.max.x, rect.min.y),
         };
+        let world_translation = object_tr
+            .transform_point(local_translation.extend(0.0))
+            .truncate();
         Self {
             world_translation,
             kind,
@@ -0,0 +1,226 @@
+//! Waypoint handles on a selected platform: drag one to move it, right click one to delete it,
+//! press W to add one under the pointer after the last one.
+
+use bevy::{
+    color::palettes::css::{BLUE, ORANGE, RED},
+    prelude::*,
+};
+
+use crate::{
+    data::level::{LevelData, PlatformMode},
+    game::{editor::HighlightGizmos, entity_id::EntityId, spawn::platform::Platform, GameState},
+    mouse::MouseScreenCoords,
+    AppSet, MainCamera,
+};
+
+use super::{selected::CurrentSelected, Pointer, PointerState};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(CurrentWaypointHandle(None));
+    app.insert_resource(CurrentRouting(None));
+    app.add_systems(
+        Update,
+        (
+            draw_paths.run_if(in_state(GameState::Editing)),
+            draw_gizmos
+                .run_if(in_state(PointerState::Selected).or_else(in_state(PointerState::Routing))),
+        )
+            .in_set(AppSet::Update),
+    );
+    app.add_systems(
+        Update,
+        (check_highlighted, check_click, add_check)
+            .chain()
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PointerState::Selected)),
+    );
+    app.add_systems(OnExit(PointerState::Routing), clear_current_routing);
+    app.add_systems(
+        Update,
+        (calc_route, check_routing_click)
+            .chain()
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PointerState::Routing)),
+    );
+}
+
+#[derive(Debug, Resource)]
+pub struct CurrentWaypointHandle(pub Option<WaypointHandle>);
+
+#[derive(Debug, Clone, Copy)]
+pub struct WaypointHandle {
+    entity: Entity,
+    index: usize,
+}
+
+#[derive(Debug, Resource)]
+struct CurrentRouting(Option<Routing>);
+
+#[derive(Debug, Clone, Copy)]
+struct Routing {
+    handle: WaypointHandle,
+    start: Vec2,
+}
+
+fn clear_current_routing(mut current_routing: ResMut<CurrentRouting>) {
+    current_routing.0 = None;
+}
+
+/// Writes the waypoints of the platform entity back to the level.
+fn save_waypoints(level_data: &mut LevelData, e_id: &EntityId, platform: &Platform) {
+    level_data
+        .platforms
+        .get_mut(&e_id.0)
+        .expect("this level platform data should exist")
+        .waypoints
+        .clone_from(&platform.0.waypoints);
+}
+
+fn calc_route(
+    pointer: Res<Pointer>,
+    current_routing: Res<CurrentRouting>,
+    mut q_platforms: Query<&mut Platform>,
+) {
+    if let (Some(routing), Some(mouse)) = (current_routing.0, pointer.0) {
+        if let Ok(mut platform) = q_platforms.get_mut(routing.handle.entity) {
+            platform.0.waypoints[routing.handle.index] = mouse.into();
+        }
+    }
+}
+
+fn check_routing_click(
+    mut current_routing: ResMut<CurrentRouting>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut next_state: ResMut<NextState<PointerState>>,
+    mut level_data: ResMut<LevelData>,
+    mut q_platforms: Query<(&mut Platform, &EntityId)>,
+) {
+    let Some(routing) = current_routing.0 else {
+        return;
+    };
+    let Ok((mut platform, e_id)) = q_platforms.get_mut(routing.handle.entity) else {
+        return;
+    };
+    if buttons.just_pressed(MouseButton::Right) {
+        platform.0.waypoints[routing.handle.index] = routing.start.into();
+        current_routing.0 = None;
+        next_state.set(PointerState::Selected);
+    }
+    if buttons.just_pressed(MouseButton::Left) {
+        save_waypoints(&mut level_data, e_id, &platform);
+        current_routing.0 = None;
+        next_state.set(PointerState::Selected);
+    }
+}
+
+fn check_click(
+    highlighted_handle: Res<CurrentWaypointHandle>,
+    mut current_routing: ResMut<CurrentRouting>,
+    mut q_platforms: Query<(&mut Platform, &EntityId)>,
+    mut next_state: ResMut<NextState<PointerState>>,
+    mut level_data: ResMut<LevelData>,
+    buttons: Res<ButtonInput<MouseButton>>,
+) {
+    let Some(handle) = highlighted_handle.0 else {
+        return;
+    };
+    let Ok((mut platform, e_id)) = q_platforms.get_mut(handle.entity) else {
+        return;
+    };
+    if buttons.just_pressed(MouseButton::Left) {
+        current_routing.0 = Some(Routing {
+            handle,
+            start: platform.0.waypoints[handle.index].into(),
+        });
+        next_state.set(PointerState::Routing);
+    } else if buttons.just_pressed(MouseButton::Right) && platform.0.waypoints.len() > 1 {
+        platform.0.waypoints.remove(handle.index);
+        save_waypoints(&mut level_data, e_id, &platform);
+    }
+}
+
+fn add_check(
+    input: Res<ButtonInput<KeyCode>>,
+    current_selected: Res<CurrentSelected>,
+    pointer: Res<Pointer>,
+    mut q_platforms: Query<(&mut Platform, &EntityId)>,
+    mut level_data: ResMut<LevelData>,
+) {
+    if !input.just_pressed(KeyCode::KeyW) {
+        return;
+    }
+    let (Some(selected), Some(point)) = (current_selected.0, pointer.0) else {
+        return;
+    };
+    if let Ok((mut platform, e_id)) = q_platforms.get_mut(selected) {
+        platform.0.waypoints.push(point.into());
+        save_waypoints(&mut level_data, e_id, &platform);
+    }
+}
+
+fn check_highlighted(
+    mut highlighted_handle: ResMut<CurrentWaypointHandle>,
+    mouse_wc: Res<MouseScreenCoords>,
+    current_selected: Res<CurrentSelected>,
+    q_platforms: Query<&Platform>,
+    camera_query: Query<&OrthographicProjection, With<MainCamera>>,
+) {
+    if let (Some(mouse_wc), Some(selected)) = (mouse_wc.0, current_selected.0) {
+        if let Ok(Platform(data)) = q_platforms.get(selected) {
+            let projection = camera_query.single();
+            let index = data.points().into_iter().position(|point| {
+                Rect::from_center_size(point, Vec2::splat(HANDLE_SIZE * projection.scale))
+                    .contains(mouse_wc)
+            });
+            if let Some(index) = index {
+                highlighted_handle.0 = Some(WaypointHandle {
+                    entity: selected,
+                    index,
+                });
+                return;
+            }
+        }
+    }
+    highlighted_handle.0 = None;
+}
+
+/// The path of every platform, so they can be told apart from walls.
+fn draw_paths(q_platforms: Query<&Platform>, mut gizmos: Gizmos<HighlightGizmos>) {
+    for Platform(data) in &q_platforms {
+        let points = data.points();
+        let closing = match data.mode {
+            PlatformMode::Loop => points.first().copied(),
+            PlatformMode::PingPong => None,
+        };
+        gizmos.linestrip_2d(points.into_iter().chain(closing), ORANGE);
+    }
+}
+
+fn draw_gizmos(
+    mut gizmos: Gizmos,
+    current_selected: Res<CurrentSelected>,
+    q_platforms: Query<&Platform>,
+    camera_query: Query<&OrthographicProjection, With<MainCamera>>,
+    highlighted_handle: Res<CurrentWaypointHandle>,
+    current_routing: Res<CurrentRouting>,
+) {
+    if let Some(selected) = current_selected.0 {
+        if let Ok(Platform(data)) = q_platforms.get(selected) {
+            let projection = camera_query.single();
+            let active = current_routing
+                .0
+                .map(|routing| routing.handle)
+                .or(highlighted_handle.0);
+            for (index, point) in data.points().into_iter().enumerate() {
+                let color = if active.is_some_and(|handle| handle.index == index) {
+                    RED
+                } else {
+                    BLUE
+                };
+                gizmos.circle_2d(point, HANDLE_SIZE * projection.scale / 2.0, color);
+            }
+        }
+    }
+}
+
+const HANDLE_SIZE: f32 = 0.5;
@@ -1,24 +1,23 @@
-use std::f32::consts::TAU;
-
 use bevy::{input::mouse::MouseWheel, math::vec2, prelude::*};
-use uuid::Uuid;
 
 use crate::{
-    data::{
-        config::GameConfig,
-        level::{LevelData, WallData},
-    },
+    data::{config::GameConfig, level::LevelData},
     game::{
-        arrow::Arrow, editor::tool::pointer::moving::MoveOp, entity_id::EntityId,
-        entity_type::EntityType, object_size::ObjectSize, spawn::wall::SpawnWall,
+        editor::tool::pointer::moving::MoveOp,
+        entity_id::EntityId,
+        entity_type::EntityType,
+        level_object::{LevelObjects, ObjectRemoved},
+        object_size::ObjectSize,
+        spawn::{track::Track, wall::PolygonWall},
     },
     mouse::MouseScreenCoords,
     AppSet,
 };
 
 use super::{
-    moving::CurrentMove, pointing::CurrentHighlight, resizing::CurrentHighlightedHandle,
-    snap_to_grid, Pointer, PointerState,
+    bending::CurrentTrackHandle, moving::CurrentMove, pointing::CurrentHighlight, polygon_contains,
+    resizing::CurrentHighlightedHandle, routing::CurrentWaypointHandle, sized_contains,
+    snap_to_grid, track_contains, Pointer, PointerState, Selectable,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -41,17 +40,22 @@ pub(super) fn plugin(app: &mut App) {
 pub struct CurrentSelected(pub Option<Entity>);
 
 fn rotate_check(
+    mut cmd: Commands,
     current_selected: Res<CurrentSelected>,
-    mut q_entity: Query<(&EntityId, &mut Transform), With<Arrow>>,
+    mut q_entity: Query<(&EntityId, &EntityType, &mut Transform)>,
     input: Res<ButtonInput<KeyCode>>,
     mut evr_scroll: EventReader<MouseWheel>,
     mut level: ResMut<LevelData>,
+    objects: Res<LevelObjects>,
 ) {
     use bevy::input::mouse::MouseScrollUnit;
     for ev in evr_scroll.read() {
         if input.pressed(KeyCode::ControlLeft) || input.pressed(KeyCode::ControlRight) {
             if let Some(selected) = current_selected.0 {
-                if let Ok((id, mut transform)) = q_entity.get_mut(selected) {
+                if let Ok((id, e_type, mut transform)) = q_entity.get_mut(selected) {
+                    let Some(kind) = objects.get(e_type).filter(|kind| kind.rotatable) else {
+                        continue;
+                    };
                     match ev.unit {
                         MouseScrollUnit::Line => {
                             let delta = if ev.y > 0.0 {
@@ -59,11 +63,15 @@ fn rotate_check(
                             } else {
                                 Rot2::degrees(-5.0)
                             };
-                            let angle = (transform.rotation.to_axis_angle().1 + delta.as_radians())
-                                .rem_euclid(TAU);
-                            transform.rotation = Quat::from_rotation_z(angle);
-                            if let Some(ref mut arrow_data) = level.arrows.get_mut(&id.0) {
-                                arrow_data.angle = angle;
+                            let angle = kind.rotate(
+                                &mut cmd,
+                                &mut level,
+                                selected,
+                                id.0,
+                                delta.as_radians(),
+                            );
+                            if let Some(angle) = angle {
+                                transform.rotation = Quat::from_rotation_z(angle);
                             }
                         }
                         MouseScrollUnit::Pixel => {
@@ -82,28 +90,13 @@ fn copy_check(
     input: Res<ButtonInput<KeyCode>>,
     q_entity: Query<(&EntityType, &EntityId)>,
     mut level_data: ResMut<LevelData>,
+    objects: Res<LevelObjects>,
 ) {
     if let Some(entity) = current_selected.0 {
         if input.just_pressed(KeyCode::KeyC) {
             if let Ok((e_type, e_id)) = q_entity.get(entity) {
-                let offset = vec2(1.0, 1.0);
-                match e_type {
-                    EntityType::Wall => {
-                        let mut wall_rect: Rect = level_data.walls[&e_id.0].rect.into();
-                        wall_rect.max += offset;
-                        wall_rect.min += offset;
-                        let uuid = Uuid::new_v4();
-                        let data = WallData {
-                            rect: wall_rect.into(),
-                        };
-                        level_data.walls.insert(uuid, data);
-                        cmd.trigger(SpawnWall(uuid, data));
-                    }
-                    EntityType::Checkpoint => todo!(),
-                    EntityType::Goal => todo!(),
-                    EntityType::SpaceTutorial => todo!(),
-                    EntityType::ArrowTutorial => todo!(),
-                    EntityType::Arrow => todo!(),
+                if let Some(kind) = objects.get(e_type) {
+                    kind.duplicate(&mut cmd, &mut level_data, e_id.0, vec2(1.0, 1.0));
                 }
             }
         }
@@ -116,48 +109,18 @@ fn delete_check(
     input: Res<ButtonInput<KeyCode>>,
     q_entity: Query<(&EntityType, &EntityId)>,
     mut level_data: ResMut<LevelData>,
+    objects: Res<LevelObjects>,
     mut next_state: ResMut<NextState<PointerState>>,
 ) {
     if let Some(entity) = current_selected.0 {
         if input.just_pressed(KeyCode::Delete) {
             if let Ok((e_type, e_id)) = q_entity.get(entity) {
-                match e_type {
-                    EntityType::Wall => {
-                        level_data.walls.remove(&e_id.0);
-                        cmd.entity(entity).despawn_recursive();
-                        current_selected.0 = None;
-                        next_state.set(PointerState::Pointing);
-                    }
-                    EntityType::Checkpoint => {
-                        level_data.checkpoints.remove(&e_id.0);
-                        cmd.entity(entity).despawn_recursive();
-                        current_selected.0 = None;
-                        next_state.set(PointerState::Pointing);
-                    }
-                    EntityType::Goal => {
-                        level_data.goals.remove(&e_id.0);
-                        cmd.entity(entity).despawn_recursive();
-                        current_selected.0 = None;
-                        next_state.set(PointerState::Pointing);
-                    }
-                    EntityType::SpaceTutorial => {
-                        level_data.space_tutorials.remove(&e_id.0);
-                        cmd.entity(entity).despawn_recursive();
-                        current_selected.0 = None;
-                        next_state.set(PointerState::Pointing);
-                    }
-                    EntityType::ArrowTutorial => {
-                        level_data.arrow_tutorials.remove(&e_id.0);
-                        cmd.entity(entity).despawn_recursive();
-                        current_selected.0 = None;
-                        next_state.set(PointerState::Pointing);
-                    }
-                    EntityType::Arrow => {
-                        level_data.arrows.remove(&e_id.0);
-                        cmd.entity(entity).despawn_recursive();
-                        current_selected.0 = None;
-                        next_state.set(PointerState::Pointing);
-                    }
+                if let Some(kind) = objects.get(e_type) {
+                    kind.remove(&mut level_data, e_id.0);
+                    cmd.trigger(ObjectRemoved(e_id.0));
+                    cmd.entity(entity).despawn_recursive();
+                    current_selected.0 = None;
+                    next_state.set(PointerState::Pointing);
                 }
             }
         }
@@ -167,12 +130,27 @@ fn delete_check(
 fn highlight_check(
     mouse_wc: Res<MouseScreenCoords>,
     q_sized: Query<(Entity, &ObjectSize, &GlobalTransform)>,
+    q_polygon: Query<(Entity, &PolygonWall, &GlobalTransform)>,
+    q_track: Query<(Entity, &Track, &GlobalTransform)>,
     q_sprite: Query<(Entity, &Sprite, &GlobalTransform)>,
+    selectable: Selectable,
     mut current_highlight: ResMut<CurrentHighlight>,
 ) {
     let Some(point) = mouse_wc.0 else { return };
     for (e, ObjectSize(size), gt) in &q_sized {
-        if Rect::from_center_size(gt.translation().truncate(), *size).contains(point) {
+        if sized_contains(gt, *size, point) && selectable.contains(e) {
+            current_highlight.0 = Some(e);
+            return;
+        }
+    }
+    for (e, PolygonWall(points), gt) in &q_polygon {
+        if polygon_contains(gt, points, point) && selectable.contains(e) {
+            current_highlight.0 = Some(e);
+            return;
+        }
+    }
+    for (e, Track(data), gt) in &q_track {
+        if track_contains(gt, data, point) && selectable.contains(e) {
             current_highlight.0 = Some(e);
             return;
         }
@@ -190,6 +168,7 @@ fn highlight_check(
         if let Some(size) = custom_size {
             if Rect::from_center_size(gt.translation().truncate() - anchor.as_vec() * *size, *size)
                 .contains(point)
+                && selectable.contains(e)
             {
                 current_highlight.0 = Some(e);
                 return;
@@ -203,6 +182,8 @@ fn click_check(
     buttons: Res<ButtonInput<MouseButton>>,
     current_highlight: Res<CurrentHighlight>,
     highlighted_handle: Res<CurrentHighlightedHandle>,
+    track_handle: Res<CurrentTrackHandle>,
+    waypoint_handle: Res<CurrentWaypointHandle>,
     mut current_selected: ResMut<CurrentSelected>,
     mut current_move: ResMut<CurrentMove>,
     mut next_state: ResMut<NextState<PointerState>>,
@@ -210,14 +191,22 @@ fn click_check(
     // mouse_wc: Res<MouseScreenCoords>,
     pointer: Res<Pointer>,
     config: Res<GameConfig>,
+    q_interaction: Query<&Interaction>,
 ) {
+    // clicks on the inspector buttons are not meant for the level behind them
+    if q_interaction
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
     if buttons.just_pressed(MouseButton::Left) {
         let (highlight, selected, highlighted_handle) = (
             current_highlight.0,
             current_selected.0,
             highlighted_handle.0,
         );
-        if highlighted_handle.is_some() {
+        if highlighted_handle.is_some() || track_handle.0.is_some() || waypoint_handle.0.is_some() {
             return;
         }
         if let Some(e) = highlight {
@@ -226,6 +215,7 @@ fn click_check(
                     warn!("move");
                     current_move.0 = Some(MoveOp {
                         entity: e,
+                        start: gt.translation().truncate(),
                         origin: snap_to_grid(gt.translation().truncate(), config.editor.grid_size),
                         mouse_origin: pointer.0.expect("mouse should be in window if we get here"),
                     });
@@ -0,0 +1,108 @@
+//! Places the vertices of a new polygon wall.
+//!
+//! Left click adds a vertex, right click removes the last one, Enter or clicking the first
+//! vertex again closes the polygon.
+
+use bevy::{
+    color::palettes::css::{BLUE, RED},
+    prelude::*,
+};
+use uuid::Uuid;
+
+use super::{
+    pointer::{snap_to_grid, Pointer},
+    Tool,
+};
+use crate::{
+    data::{
+        config::{GameConfig, DEFAULT_SURFACE},
+        level::{LevelData, PolygonWallData},
+    },
+    game::spawn::wall::SpawnPolygonWall,
+    polygon::triangulate,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(PolygonDraft(vec![]))
+        .add_systems(OnEnter(Tool::Polygon), clear_draft)
+        .add_systems(
+            Update,
+            click_check
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(Tool::Polygon)),
+        )
+        .add_systems(
+            Update,
+            draw_draft
+                .in_set(AppSet::Update)
+                .run_if(in_state(Tool::Polygon)),
+        );
+}
+
+#[derive(Debug, Resource)]
+struct PolygonDraft(Vec<Vec2>);
+
+fn clear_draft(mut draft: ResMut<PolygonDraft>) {
+    draft.0.clear();
+}
+
+fn click_check(
+    mut cmd: Commands,
+    buttons: Res<ButtonInput<MouseButton>>,
+    input: Res<ButtonInput<KeyCode>>,
+    pointer: Res<Pointer>,
+    mut draft: ResMut<PolygonDraft>,
+    mut level_data: ResMut<LevelData>,
+    mut next_tool: ResMut<NextState<Tool>>,
+    config: Res<GameConfig>,
+) {
+    if buttons.just_pressed(MouseButton::Right) && draft.0.pop().is_none() {
+        next_tool.set(Tool::Pointer);
+        return;
+    }
+    let Some(point) = pointer.0 else { return };
+    let close = input.just_pressed(KeyCode::Enter)
+        || (buttons.just_pressed(MouseButton::Left) && draft.0.first() == Some(&point));
+    if close {
+        if draft.0.len() < 3 {
+            return;
+        }
+        let min = draft
+            .0
+            .iter()
+            .copied()
+            .reduce(Vec2::min)
+            .unwrap_or_default();
+        let max = draft
+            .0
+            .iter()
+            .copied()
+            .reduce(Vec2::max)
+            .unwrap_or_default();
+        let pos = snap_to_grid((min + max) / 2.0, config.editor.grid_size);
+        let data = PolygonWallData {
+            pos: pos.into(),
+            points: draft.0.iter().map(|point| (*point - pos).into()).collect(),
+            angle: 0.0,
+            surface: DEFAULT_SURFACE.to_string(),
+        };
+        if triangulate(&data.local_points()).is_none() {
+            warn!("the polygon crosses itself, fix it before closing it");
+            return;
+        }
+        let uuid = Uuid::new_v4();
+        level_data.polygon_walls.insert(uuid, data.clone());
+        cmd.trigger(SpawnPolygonWall(uuid, data));
+        next_tool.set(Tool::Pointer);
+    } else if buttons.just_pressed(MouseButton::Left) && draft.0.last() != Some(&point) {
+        draft.0.push(point);
+    }
+}
+
+fn draw_draft(mut gizmos: Gizmos, draft: Res<PolygonDraft>, pointer: Res<Pointer>) {
+    if let Some(first) = draft.0.first() {
+        gizmos.circle_2d(*first, 0.3, RED);
+    }
+    gizmos.linestrip_2d(draft.0.iter().copied().chain(pointer.0), BLUE);
+}
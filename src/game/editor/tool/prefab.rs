@@ -0,0 +1,270 @@
+//! Prefabs: press G over objects to group them, then save the group as a prefab from the tool
+//! bar. The group is replaced with an instance of the prefab, and the other instances of a
+//! prefab saved again are updated. With an instance selected, press U to unpack it into plain
+//! objects.
+
+use bevy::{
+    color::palettes::css::{FUCHSIA, ORANGE},
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+    utils::HashSet,
+    window::PrimaryWindow,
+};
+use uuid::Uuid;
+
+use crate::{
+    data::{config::GameConfig, level::LevelData},
+    game::{
+        editor::HighlightGizmos,
+        entity_id::EntityId,
+        entity_type::EntityType,
+        level_object::{LevelObjects, ObjectRemoved},
+        object_size::ObjectSize,
+        prefab::{add_instance, PrefabInstance, Prefabs, RefreshInstance},
+        GameState,
+    },
+    ui::prelude::*,
+    AppSet,
+};
+
+use super::{
+    pointer::{pointing::CurrentHighlight, selected::CurrentSelected, snap_to_grid, PointerState},
+    Tool,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PrefabGroup>()
+        .init_resource::<PrefabName>()
+        .observe(on_save_prefab)
+        .add_systems(OnEnter(Tool::Prefab), show_prefab_menu)
+        .add_systems(
+            Update,
+            (type_name, handle_prefab_button, cancel_check).run_if(in_state(Tool::Prefab)),
+        )
+        .add_systems(
+            Update,
+            group_check
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(PointerState::Pointing).or_else(in_state(PointerState::Selected))),
+        )
+        .add_systems(
+            Update,
+            unpack_check
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(PointerState::Selected)),
+        )
+        .add_systems(
+            Update,
+            draw_prefabs
+                .in_set(AppSet::Update)
+                .run_if(in_state(GameState::Editing)),
+        );
+}
+
+/// The objects to save as a prefab.
+#[derive(Debug, Resource, Default)]
+struct PrefabGroup(Vec<Entity>);
+
+/// The name typed for the prefab.
+#[derive(Debug, Resource, Default)]
+struct PrefabName(String);
+
+#[derive(Component)]
+struct PrefabNameText;
+
+/// Saves the group over the prefab of this name.
+#[derive(Component, Debug)]
+struct PrefabButton(String);
+
+#[derive(Debug, Event)]
+struct SavePrefab(String);
+
+fn group_check(
+    input: Res<ButtonInput<KeyCode>>,
+    current_highlight: Res<CurrentHighlight>,
+    q_objects: Query<&EntityId, (With<EntityType>, Without<PrefabInstance>)>,
+    level: Res<LevelData>,
+    mut group: ResMut<PrefabGroup>,
+) {
+    if !input.just_pressed(KeyCode::KeyG) {
+        return;
+    }
+    // the objects of an instance already belong to a prefab
+    let Some(entity) = current_highlight.0.filter(|e| {
+        q_objects
+            .get(*e)
+            .is_ok_and(|eid| !level.prefab_objects.contains_key(&eid.0))
+    }) else {
+        return;
+    };
+    if group.0.contains(&entity) {
+        group.0.retain(|e| *e != entity);
+    } else {
+        group.0.push(entity);
+    }
+}
+
+fn unpack_check(
+    mut cmd: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    mut current_selected: ResMut<CurrentSelected>,
+    q_instances: Query<&EntityId, With<PrefabInstance>>,
+    mut level: ResMut<LevelData>,
+    mut next_state: ResMut<NextState<PointerState>>,
+) {
+    if !input.just_pressed(KeyCode::KeyU) {
+        return;
+    }
+    let Some(entity) = current_selected.0 else {
+        return;
+    };
+    let Ok(eid) = q_instances.get(entity) else {
+        return;
+    };
+    // the objects stay, as objects of the level
+    level.prefabs.remove(&eid.0);
+    level
+        .prefab_objects
+        .retain(|_, instance| *instance != eid.0);
+    cmd.entity(entity).despawn_recursive();
+    current_selected.0 = None;
+    next_state.set(PointerState::Pointing);
+}
+
+fn show_prefab_menu(
+    mut cmd: Commands,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    prefabs: Res<Prefabs>,
+    group: Res<PrefabGroup>,
+    mut name: ResMut<PrefabName>,
+) {
+    name.0.clear();
+    for window in &q_window {
+        let font_size = window.height() / 30.;
+        cmd.ui_center_root()
+            .insert(StateScoped(Tool::Prefab))
+            .with_children(|cmd| {
+                cmd.label(format!("{} grouped objects", group.0.len()));
+                cmd.text("name: ").insert(PrefabNameText);
+                for prefab in prefabs.names() {
+                    cmd.button(font_size, prefab)
+                        .insert(PrefabButton(prefab.to_string()));
+                }
+            });
+    }
+}
+
+fn type_name(
+    mut cmd: Commands,
+    mut evr_keys: EventReader<KeyboardInput>,
+    mut name: ResMut<PrefabName>,
+    mut q_text: Query<&mut Text, With<PrefabNameText>>,
+) {
+    for ev in evr_keys.read() {
+        if !ev.state.is_pressed() {
+            continue;
+        }
+        match &ev.logical_key {
+            Key::Character(chars) => name.0.extend(
+                chars
+                    .chars()
+                    .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-'),
+            ),
+            Key::Backspace => {
+                name.0.pop();
+            }
+            Key::Enter if !name.0.is_empty() => cmd.trigger(SavePrefab(name.0.clone())),
+            _ => {}
+        }
+    }
+    for mut text in &mut q_text {
+        text.sections[0].value = format!("name: {}", name.0);
+    }
+}
+
+fn handle_prefab_button(mut cmd: Commands, mut button_query: InteractionQuery<&PrefabButton>) {
+    for (interaction, PrefabButton(prefab)) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            cmd.trigger(SavePrefab(prefab.clone()));
+        }
+    }
+}
+
+fn cancel_check(buttons: Res<ButtonInput<MouseButton>>, mut next_tool: ResMut<NextState<Tool>>) {
+    if buttons.just_pressed(MouseButton::Right) {
+        next_tool.set(Tool::Pointer);
+    }
+}
+
+fn on_save_prefab(
+    trigger: Trigger<SavePrefab>,
+    mut cmd: Commands,
+    mut group: ResMut<PrefabGroup>,
+    mut level: ResMut<LevelData>,
+    mut prefabs: ResMut<Prefabs>,
+    objects: Res<LevelObjects>,
+    q_objects: Query<(&EntityType, &EntityId, &GlobalTransform)>,
+    config: Res<GameConfig>,
+    mut next_tool: ResMut<NextState<Tool>>,
+) {
+    let name = &trigger.event().0;
+    let members: Vec<_> = group
+        .0
+        .iter()
+        .filter_map(|entity| {
+            let (e_type, eid, gt) = q_objects.get(*entity).ok()?;
+            Some((
+                *entity,
+                objects.get(e_type)?,
+                eid.0,
+                gt.translation().truncate(),
+            ))
+        })
+        .collect();
+    if members.is_empty() {
+        warn!("Group objects with G before saving a prefab");
+        return;
+    }
+    let center = members.iter().map(|(_, _, _, pos)| *pos).sum::<Vec2>() / members.len() as f32;
+    let origin = snap_to_grid(center, config.editor.grid_size);
+    let mut prefab = LevelData::default();
+    for (_, kind, uuid, _) in &members {
+        kind.extract(&level, *uuid, &mut prefab, -origin);
+    }
+    // links to objects left out of the prefab can't follow it
+    let uuids: HashSet<Uuid> = members.iter().map(|(_, _, uuid, _)| *uuid).collect();
+    for kind in objects.kinds() {
+        kind.remap(&mut prefab, &|link| uuids.contains(&link).then_some(link));
+    }
+    prefab.save_prefab(name);
+    prefabs.prefabs.insert(name.clone(), prefab);
+
+    for (entity, kind, uuid, _) in members {
+        kind.remove(&mut level, uuid);
+        cmd.trigger(ObjectRemoved(uuid));
+        cmd.entity(entity).despawn_recursive();
+    }
+    let instance = add_instance(&mut cmd, &mut level, name, origin);
+    for (uuid, data) in &level.prefabs {
+        if data.prefab == *name && *uuid != instance {
+            cmd.trigger(RefreshInstance(*uuid));
+        }
+    }
+    group.0.clear();
+    next_tool.set(Tool::Pointer);
+}
+
+/// Outlines the instances, which have nothing to show in game, and marks the grouped objects.
+fn draw_prefabs(
+    group: Res<PrefabGroup>,
+    q_instances: Query<(&Transform, &ObjectSize), With<PrefabInstance>>,
+    q_transforms: Query<&Transform>,
+    mut gizmos: Gizmos<HighlightGizmos>,
+) {
+    for (tr, size) in &q_instances {
+        gizmos.rect_2d(tr.translation.truncate(), 0.0, size.0, FUCHSIA);
+    }
+    for tr in group.0.iter().filter_map(|e| q_transforms.get(*e).ok()) {
+        gizmos.circle_2d(tr.translation.truncate(), 0.5, ORANGE);
+    }
+}
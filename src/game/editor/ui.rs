@@ -1,9 +1,9 @@
-use crate::{data::level::LevelData, ui::prelude::*};
+use crate::{data::level::LevelData, game::level_object::LevelObjects, ui::prelude::*};
 use bevy::{prelude::*, window::PrimaryWindow};
 
 use crate::game::GameState;
 
-use super::tool::Tool;
+use super::tool::{decoration::DecorationLayerActive, Tool};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
@@ -20,6 +20,8 @@ enum EditorAction {
     Save,
     Back,
     Add,
+    Prefab,
+    Decorations,
 }
 
 #[derive(Component)]
@@ -38,6 +40,9 @@ fn enter_editing(mut cmd: Commands, q_window: Query<&Window, With<PrimaryWindow>
                     cmd.button(font_size, "Save").insert(EditorAction::Save);
                     cmd.button(font_size, "Back").insert(EditorAction::Back);
                     cmd.button(font_size, "Add").insert(EditorAction::Add);
+                    cmd.button(font_size, "Prefab").insert(EditorAction::Prefab);
+                    cmd.button(font_size, "Decorations")
+                        .insert(EditorAction::Decorations);
                     cmd.text("tool: ").insert(ToolText);
                 });
             });
@@ -49,13 +54,17 @@ fn handle_editor_action(
     mut next_game_state: ResMut<NextState<GameState>>,
     mut next_tool: ResMut<NextState<Tool>>,
     level: Res<LevelData>,
+    objects: Res<LevelObjects>,
+    mut decoration_layer: ResMut<DecorationLayerActive>,
 ) {
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
-                EditorAction::Save => level.save(),
+                EditorAction::Save => objects.without_prefab_objects(&level).save(),
                 EditorAction::Back => next_game_state.set(GameState::Playing),
                 EditorAction::Add => next_tool.set(Tool::Add),
+                EditorAction::Prefab => next_tool.set(Tool::Prefab),
+                EditorAction::Decorations => decoration_layer.0 = !decoration_layer.0,
             }
         }
     }
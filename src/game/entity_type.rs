@@ -1,11 +1,14 @@
+use std::any::TypeId;
+
 use bevy::prelude::*;
 
-#[derive(Debug, Component, Clone, Copy)]
-pub enum EntityType {
-    Wall,
-    Checkpoint,
-    Goal,
-    SpaceTutorial,
-    ArrowTutorial,
-    Arrow,
+/// The kind of level object an entity was spawned from, see [`super::level_object`].
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntityType(TypeId);
+
+impl EntityType {
+    /// The kind of the objects with `T` as level data.
+    pub fn of<T: 'static>() -> Self {
+        Self(TypeId::of::<T>())
+    }
 }
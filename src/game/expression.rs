@@ -0,0 +1,194 @@
+//! Facial expressions of the robot. The head shows how the run is going and the eyes look where
+//! the robot is heading.
+
+use bevy::{math::vec2, prelude::*};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    data::config::GameConfig,
+    game::{
+        assets::{HandleMap, ImageKey},
+        checkpoint::{CurrentActiveCheckpoint, ReachCheckpoint},
+        spawn::player::{Eyes, Head, LiveTorso, LostLimbs, PlayerControls, Wheel},
+        GameState,
+    },
+    screen::Screen,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Expression>()
+        .init_resource::<HeldExpression>()
+        .observe(on_reach_checkpoint)
+        .add_systems(OnExit(Screen::Playing), release_expression)
+        .add_systems(
+            Update,
+            tick_held_expression
+                .in_set(AppSet::TickTimers)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                hurt_expression.run_if(in_state(GameState::Playing)),
+                update_expression
+                    .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Victory))),
+                (show_expression, look_ahead),
+            )
+                .chain()
+                .in_set(AppSet::Update)
+                .run_if(in_state(Screen::Playing)),
+        );
+}
+
+/// The face the robot is making, on its [`Head`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub enum Expression {
+    #[default]
+    Neutral,
+    /// Pushing in a direction.
+    Focused,
+    /// Tipping over or falling fast.
+    Panic,
+    /// Just lost an arm.
+    Hurt,
+    /// Reached a checkpoint or the goal.
+    Happy,
+}
+
+/// An expression kept for a while after what caused it, over the ones following the robot's
+/// motion.
+#[derive(Resource, Debug, Default)]
+struct HeldExpression(Option<(Expression, Timer)>);
+
+impl HeldExpression {
+    fn hold(&mut self, expression: Expression, seconds: f32) {
+        self.0 = Some((expression, Timer::from_seconds(seconds, TimerMode::Once)));
+    }
+}
+
+fn on_reach_checkpoint(
+    trigger: Trigger<ReachCheckpoint>,
+    active_checkpoint: Res<CurrentActiveCheckpoint>,
+    mut held: ResMut<HeldExpression>,
+    config: Res<GameConfig>,
+) {
+    // reaching the active checkpoint again is nothing to smile about
+    let eid = trigger.event().0;
+    if active_checkpoint.0.as_ref().map(|active| active.eid) != Some(eid) {
+        held.hold(Expression::Happy, config.head.happy_time);
+    }
+}
+
+fn release_expression(mut held: ResMut<HeldExpression>) {
+    held.0 = None;
+}
+
+fn tick_held_expression(mut held: ResMut<HeldExpression>, time: Res<Time>) {
+    if let Some((_, timer)) = &mut held.0 {
+        if timer.tick(time.delta()).finished() {
+            held.0 = None;
+        }
+    }
+}
+
+fn hurt_expression(
+    lost_limbs: Res<LostLimbs>,
+    mut previous: Local<LostLimbs>,
+    mut held: ResMut<HeldExpression>,
+    config: Res<GameConfig>,
+) {
+    if (lost_limbs.left && !previous.left) || (lost_limbs.right && !previous.right) {
+        held.hold(Expression::Hurt, config.head.hurt_time);
+    }
+    *previous = *lost_limbs;
+}
+
+fn update_expression(
+    mut q_head: Query<&mut Expression, With<Head>>,
+    q_torso: Query<(&Transform, &Velocity), With<LiveTorso>>,
+    held: Res<HeldExpression>,
+    controls: Res<PlayerControls>,
+    game_state: Res<State<GameState>>,
+    config: Res<GameConfig>,
+) {
+    let panic = q_torso.get_single().is_ok_and(|(tr, velocity)| {
+        let tilt = (tr.rotation * Vec3::Y).angle_between(Vec3::Y);
+        tilt > config.head.panic_tilt || -velocity.linvel.y > config.head.panic_fall_speed
+    });
+    let expression = if *game_state.get() == GameState::Victory {
+        Expression::Happy
+    } else if let Some((expression, _)) = held.0 {
+        expression
+    } else if panic {
+        Expression::Panic
+    } else if controls.lean != 0.0 {
+        Expression::Focused
+    } else {
+        Expression::Neutral
+    };
+    for mut current in &mut q_head {
+        current.set_if_neq(expression);
+    }
+}
+
+/// Swaps the face of the head and the shape of the eyes when the expression changes.
+fn show_expression(
+    mut q_head: Query<
+        (&Expression, &mut Handle<Image>, &mut Sprite, &Children),
+        Changed<Expression>,
+    >,
+    mut q_eyes: Query<&mut Transform, With<Eyes>>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    config: Res<GameConfig>,
+) {
+    for (expression, mut texture, mut sprite, children) in &mut q_head {
+        let face = match expression {
+            Expression::Panic => ImageKey::HeadPanic,
+            _ => ImageKey::Head,
+        };
+        *texture = image_handles[&face].clone_weak();
+        sprite.color = match expression {
+            Expression::Hurt => Color::from(Srgba::from(config.head.hurt_tint)),
+            _ => Color::WHITE,
+        };
+        let eyes_scale = match expression {
+            Expression::Neutral => Vec2::ONE,
+            Expression::Focused => vec2(1.0, config.eyes.focused),
+            Expression::Panic => Vec2::splat(config.eyes.panic),
+            Expression::Hurt => vec2(1.0, config.eyes.hurt),
+            Expression::Happy => vec2(1.0, config.eyes.happy),
+        };
+        for child in children {
+            if let Ok(mut tr) = q_eyes.get_mut(*child) {
+                tr.scale = eyes_scale.extend(1.0);
+            }
+        }
+    }
+}
+
+/// Moves the eyes toward the direction the wheel is going.
+fn look_ahead(
+    mut q_eyes: Query<(&mut Transform, &Parent), With<Eyes>>,
+    q_head: Query<&GlobalTransform, With<Head>>,
+    q_wheel: Query<&Velocity, With<Wheel>>,
+    config: Res<GameConfig>,
+) {
+    // the wheel is gone once the robot fell apart, the eyes stay where they were
+    let Ok(velocity) = q_wheel.get_single() else {
+        return;
+    };
+    let look = (velocity.linvel / config.eyes.look_speed).clamp_length_max(1.0)
+        * config.eyes.look_distance;
+    for (mut tr, parent) in &mut q_eyes {
+        let Ok(head_gt) = q_head.get(parent.get()) else {
+            continue;
+        };
+        // the head turns with the torso, the eyes look in world space
+        let (_, rotation, _) = head_gt.to_scale_rotation_translation();
+        let local = (rotation.inverse() * look.extend(0.0)).xy();
+        tr.translation.x = config.eyes.x + local.x;
+        tr.translation.y = config.eyes.y + local.y;
+    }
+}
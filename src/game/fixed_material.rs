@@ -33,6 +33,7 @@ impl FixedMaterial {
                 texture_scale: scale,
                 parallax_rate: parallax,
                 pos: Vec2::ZERO,
+                offset: Vec2::ZERO,
             },
         }
     }
@@ -44,6 +45,8 @@ pub struct MaterialUniforms {
     pub texture_scale: Vec2,
     pub parallax_rate: Vec2,
     pub pos: Vec2,
+    /// Shifts the texture, in world units.
+    pub offset: Vec2,
 }
 impl Material2d for FixedMaterial {
     fn fragment_shader() -> ShaderRef {
@@ -1,30 +1,50 @@
-use bevy::{prelude::*, sprite::Anchor};
-use bevy_rapier2d::prelude::*;
+use bevy::{prelude::*, sprite::Anchor, utils::HashMap};
 use uuid::Uuid;
 
-use crate::{data::config::GameConfig, screen::Screen, AppSet};
+use crate::{
+    data::{
+        config::GameConfig,
+        level::{GoalData, LevelData, TriggerAction},
+    },
+    screen::Screen,
+};
 
 use super::{
     assets::{HandleMap, ImageKey},
     entity_id::EntityId,
     entity_type::EntityType,
-    physics::{coll_groups, ObjectGroup},
-    spawn::player::Torso,
-    GameState,
+    level_object::{LevelObject, ObjectKind, ObjectShape, RegisterLevelObject},
+    trigger_volume::{trigger_volume_bundle, TriggerVolume},
 };
 
 pub(super) fn plugin(app: &mut App) {
-    app.observe(on_spawn_goal).add_systems(
-        Update,
-        check_player_collision
-            .in_set(AppSet::TickTimers)
-            .run_if(in_state(GameState::Playing)),
+    app.observe(on_spawn_goal).register_level_object(
+        ObjectKind::<GoalData>::new(ObjectShape::Sprite)
+            .menu_entry("Goal", |point| GoalData { pos: point.into() }),
     );
 }
 
 #[derive(Debug, Event)]
 pub struct SpawnGoal(pub Uuid, pub Vec2);
 
+impl LevelObject for GoalData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.goals
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.goals
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnGoal(uuid, data.pos.into()));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+}
+
 #[derive(Debug, Component)]
 pub struct GoalCollider;
 
@@ -40,7 +60,7 @@ fn on_spawn_goal(
     cmd.spawn((
         Goal,
         EntityId(trigger.event().0),
-        EntityType::Goal,
+        EntityType::of::<GoalData>(),
         SpriteBundle {
             sprite: Sprite {
                 custom_size: Some(config.goal.size.into()),
@@ -59,34 +79,10 @@ fn on_spawn_goal(
             TransformBundle::from_transform(Transform::from_translation(
                 Vec2::from(config.goal.collider.pos).extend(0.0),
             )),
-            coll_groups(ObjectGroup::GOAL, ObjectGroup::PLAYER),
-            Collider::cuboid(
-                config.goal.collider.size.x / 2.0,
-                config.goal.collider.size.y / 2.0,
+            trigger_volume_bundle(
+                TriggerVolume::on_enter(TriggerAction::Victory),
+                config.goal.collider.size.into(),
             ),
         ));
     });
 }
-
-pub fn check_player_collision(
-    // mut cmd: Commands,
-    rapier_context: Res<RapierContext>,
-    q_torso: Query<(&GlobalTransform, &Collider), With<Torso>>,
-    q_goal_colliders: Query<Entity, With<GoalCollider>>,
-    mut next_state: ResMut<NextState<GameState>>,
-) {
-    for (body_tr, body_collider) in &q_torso {
-        let shape = body_collider;
-        let body_tr = body_tr.compute_transform();
-        let shape_pos = body_tr.translation.xy();
-        let shape_rot = body_tr.rotation.to_axis_angle().1;
-        let filter =
-            QueryFilter::default().groups(coll_groups(ObjectGroup::PLAYER, ObjectGroup::GOAL));
-        rapier_context.intersections_with_shape(shape_pos, shape_rot, shape, filter, |entity| {
-            if q_goal_colliders.get(entity).is_ok() {
-                next_state.set(GameState::Victory);
-            }
-            false
-        });
-    }
-}
@@ -0,0 +1,168 @@
+//! Spikes, saws and lava. Touching one severs an arm, and touching one with the torso or the
+//! wheel kills the robot, whatever the force.
+
+use bevy::{prelude::*, sprite::Anchor, utils::HashMap};
+use bevy_rapier2d::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    data::{
+        config::GameConfig,
+        level::{HazardData, HazardKind, LevelData},
+    },
+    screen::Screen,
+    AppSet,
+};
+
+use super::{
+    assets::{HandleMap, ImageKey, SfxKey},
+    audio::sfx::PlaySfx,
+    entity_id::EntityId,
+    entity_type::EntityType,
+    level_object::{LevelObject, ObjectKind, ObjectShape, RegisterLevelObject},
+    physics::{coll_groups, ObjectGroup},
+    spawn::player::{kill_torso, sever_arm, Arm, LiveArm, LiveTorso, LostLimbs, Wheel},
+    GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_spawn_hazard)
+        .add_systems(
+            Update,
+            check_player_collision
+                .in_set(AppSet::TickTimers)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .register_level_object(
+            ObjectKind::<HazardData>::new(ObjectShape::Sprite)
+                .menu_entry("Spikes", |point| new_hazard(HazardKind::Spikes, point))
+                .menu_entry("Saw", |point| new_hazard(HazardKind::Saw, point))
+                .menu_entry("Lava", |point| new_hazard(HazardKind::Lava, point)),
+        );
+}
+
+#[derive(Debug, Event)]
+pub struct SpawnHazard(pub Uuid, pub HazardData);
+
+#[derive(Debug, Component)]
+pub struct Hazard;
+
+#[derive(Debug, Component)]
+pub struct HazardCollider;
+
+impl LevelObject for HazardData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.hazards
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.hazards
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnHazard(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+}
+
+fn new_hazard(kind: HazardKind, point: Vec2) -> HazardData {
+    HazardData {
+        kind,
+        pos: point.into(),
+    }
+}
+
+fn image_key(kind: HazardKind) -> ImageKey {
+    match kind {
+        HazardKind::Spikes => ImageKey::Spikes,
+        HazardKind::Saw => ImageKey::Saw,
+        HazardKind::Lava => ImageKey::Lava,
+    }
+}
+
+fn on_spawn_hazard(
+    trigger: Trigger<SpawnHazard>,
+    mut cmd: Commands,
+    config: Res<GameConfig>,
+    image_handles: Res<HandleMap<ImageKey>>,
+) {
+    let SpawnHazard(uuid, data) = trigger.event();
+    let hazard_config = config.hazard(data.kind);
+    cmd.spawn((
+        Hazard,
+        EntityId(*uuid),
+        EntityType::of::<HazardData>(),
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(hazard_config.size.into()),
+                anchor: Anchor::BottomCenter,
+                ..default()
+            },
+            texture: image_handles[&image_key(data.kind)].clone_weak(),
+            transform: Transform::from_translation(Vec2::from(data.pos).extend(-1.5)),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ))
+    .with_children(|cmd| {
+        cmd.spawn((
+            HazardCollider,
+            TransformBundle::from_transform(Transform::from_translation(
+                Vec2::from(hazard_config.collider.pos).extend(0.0),
+            )),
+            coll_groups(ObjectGroup::HAZARD, ObjectGroup::PLAYER),
+            Sensor,
+            Collider::cuboid(
+                hazard_config.collider.size.x / 2.0,
+                hazard_config.collider.size.y / 2.0,
+            ),
+        ));
+    });
+}
+
+/// Whether a part of the robot overlaps any hazard.
+fn touches_hazard(
+    rapier_context: &RapierContext,
+    gt: &GlobalTransform,
+    collider: &Collider,
+) -> bool {
+    let tr = gt.compute_transform();
+    let filter =
+        QueryFilter::default().groups(coll_groups(ObjectGroup::PLAYER, ObjectGroup::HAZARD));
+    rapier_context
+        .intersection_with_shape(
+            tr.translation.xy(),
+            tr.rotation.to_euler(EulerRot::ZYX).0,
+            collider,
+            filter,
+        )
+        .is_some()
+}
+
+fn check_player_collision(
+    mut cmd: Commands,
+    rapier_context: Res<RapierContext>,
+    q_arms: Query<(Entity, &Arm, &GlobalTransform, &Collider), With<LiveArm>>,
+    q_torso: Query<(Entity, &GlobalTransform, &Collider), With<LiveTorso>>,
+    q_wheel: Query<(&GlobalTransform, &Collider), With<Wheel>>,
+    mut lost_limbs: ResMut<LostLimbs>,
+) {
+    for (entity, arm, gt, collider) in &q_arms {
+        if touches_hazard(&rapier_context, gt, collider) {
+            cmd.trigger(PlaySfx::At(SfxKey::Clonk, gt.translation().xy(), 1.0));
+            sever_arm(&mut cmd, entity, *arm, &mut lost_limbs);
+        }
+    }
+    let wheel_hit = q_wheel
+        .iter()
+        .any(|(gt, collider)| touches_hazard(&rapier_context, gt, collider));
+    for (torso, gt, collider) in &q_torso {
+        if wheel_hit || touches_hazard(&rapier_context, gt, collider) {
+            cmd.trigger(PlaySfx::At(SfxKey::Clonk, gt.translation().xy(), 1.0));
+            kill_torso(&mut cmd, torso);
+        }
+    }
+}
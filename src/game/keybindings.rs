@@ -0,0 +1,51 @@
+//! The keys driving the robot, read by the player input and shown on signs.
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Keybindings>();
+}
+
+#[derive(Debug, Resource, Clone)]
+pub struct Keybindings {
+    pub lean_left: Vec<KeyCode>,
+    pub lean_right: Vec<KeyCode>,
+    pub jump: Vec<KeyCode>,
+    pub respawn: Vec<KeyCode>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            lean_left: vec![KeyCode::KeyA, KeyCode::ArrowLeft],
+            lean_right: vec![KeyCode::KeyD, KeyCode::ArrowRight],
+            jump: vec![KeyCode::Space],
+            respawn: vec![KeyCode::KeyR],
+        }
+    }
+}
+
+/// The name of `key` as printed on the keyboard.
+pub fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::ArrowLeft => "Left".to_string(),
+        KeyCode::ArrowRight => "Right".to_string(),
+        KeyCode::ArrowUp => "Up".to_string(),
+        KeyCode::ArrowDown => "Down".to_string(),
+        _ => {
+            let name = format!("{key:?}");
+            name.strip_prefix("Key")
+                .or_else(|| name.strip_prefix("Digit"))
+                .unwrap_or(&name)
+                .to_string()
+        }
+    }
+}
+
+/// The names of `keys`, separated by slashes.
+pub fn keys_name(keys: &[KeyCode]) -> String {
+    keys.iter()
+        .map(|key| key_name(*key))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
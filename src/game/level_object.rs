@@ -0,0 +1,548 @@
+//! The kinds of objects placed in levels. Each kind is listed with its level data and how it
+//! spawns in [`LevelObjects::default`], and registers what the editor can do with it from its
+//! own plugin. The level spawner, the prefabs and the editor then handle every kind the same way.
+
+// the editor is the only one moving, resizing and deleting objects
+#![cfg_attr(not(feature = "dev"), allow(dead_code))]
+
+use bevy::{prelude::*, utils::HashMap};
+use uuid::Uuid;
+
+use crate::data::level::{
+    prefab_object_id, ArrowData, BoltData, CheckpointData, DecorationData, DoorData, GoalData,
+    HazardData, LevelData, PlatformData, PolygonWallData, PrefabInstanceData, RepairKitData,
+    SignData, SwitchData, TrackData, TriggerData, WallData,
+};
+
+use super::entity_type::EntityType;
+
+/// The level data of a kind of object.
+pub trait LevelObject: Clone + Send + Sync + 'static {
+    /// Where the level keeps the objects of this kind.
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self>;
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self>;
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self);
+    /// Moves the object by `offset`.
+    fn translate(&mut self, offset: Vec2);
+    /// Fits the object in `to`, it was in `from`. Only called on resizable kinds.
+    fn resize(&mut self, _from: Rect, _to: Rect) {}
+    /// Turns the object by `delta` radians. Only called on rotatable kinds.
+    fn rotate(&mut self, _delta: f32) {}
+    /// The rotation of the object transform, `None` when the rotation is part of its shape.
+    fn angle(&self) -> Option<f32> {
+        None
+    }
+    fn surface(&self) -> Option<&str> {
+        None
+    }
+    fn surface_mut(&mut self) -> Option<&mut String> {
+        None
+    }
+    /// Removes the references to the deleted object `uuid` from the rest of the level.
+    fn unlink(_level: &mut LevelData, _uuid: Uuid) {}
+    /// Replaces the uuids of the objects this one links to, dropping the links mapped to `None`.
+    fn remap(&mut self, _f: &dyn Fn(Uuid) -> Option<Uuid>) {}
+}
+
+/// How the editor outlines and picks the objects of a kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectShape {
+    /// A rect of the entity `ObjectSize`, around its transform.
+    Sized,
+    /// The sprite of the entity.
+    Sprite,
+    Polygon,
+    Track,
+}
+
+type CreateFn<T> = Box<dyn Fn(Vec2) -> T + Send + Sync>;
+
+/// A kind of object to register with [`RegisterLevelObject::register_level_object`].
+pub struct ObjectKind<T> {
+    shape: ObjectShape,
+    resizable: bool,
+    rotatable: bool,
+    menu: Vec<(&'static str, CreateFn<T>)>,
+}
+
+impl<T: LevelObject> ObjectKind<T> {
+    /// A kind the editor can move but not resize nor rotate.
+    pub fn new(shape: ObjectShape) -> Self {
+        Self {
+            shape,
+            resizable: false,
+            rotatable: false,
+            menu: vec![],
+        }
+    }
+
+    pub fn resizable(mut self) -> Self {
+        self.resizable = true;
+        self
+    }
+
+    pub fn rotatable(mut self) -> Self {
+        self.rotatable = true;
+        self
+    }
+
+    /// Adds a button to the editor add menu, creating an object at the given point.
+    pub fn menu_entry(
+        mut self,
+        label: &'static str,
+        create: impl Fn(Vec2) -> T + Send + Sync + 'static,
+    ) -> Self {
+        self.menu.push((label, Box::new(create)));
+        self
+    }
+}
+
+/// Triggered on an object after the editor changed its data, for the kinds keeping a copy of it
+/// in their components.
+#[derive(Debug, Event)]
+pub struct ObjectEdited<T: LevelObject>(pub T);
+
+/// Triggered after the editor deleted an object.
+#[derive(Debug, Event)]
+pub struct ObjectRemoved(pub Uuid);
+
+/// A kind of object, with its data type erased.
+pub struct RegisteredKind {
+    pub shape: ObjectShape,
+    pub resizable: bool,
+    pub rotatable: bool,
+    /// Whether the plugin of the kind registered it, the fields above are set then.
+    registered: bool,
+    spawn_all: fn(&mut Commands, &LevelData),
+    remove: fn(&mut LevelData, Uuid),
+    duplicate: fn(&mut Commands, &mut LevelData, Uuid, Vec2),
+    translate: fn(&mut Commands, &mut LevelData, Entity, Uuid, Vec2),
+    resize: fn(&mut Commands, &mut LevelData, Entity, Uuid, Rect, Rect),
+    rotate: fn(&mut Commands, &mut LevelData, Entity, Uuid, f32) -> Option<f32>,
+    surface: fn(&LevelData, Uuid) -> Option<&str>,
+    set_surface: fn(&mut LevelData, Uuid, &str) -> bool,
+    retain: fn(&mut LevelData, &dyn Fn(&Uuid) -> bool),
+    extract: fn(&LevelData, Uuid, &mut LevelData, Vec2),
+    remap: fn(&mut LevelData, &dyn Fn(Uuid) -> Option<Uuid>),
+    expand: fn(&mut LevelData, &LevelData, Uuid, Vec2) -> Vec<Uuid>,
+    spawn: fn(&mut Commands, &LevelData, Uuid),
+}
+
+impl RegisteredKind {
+    pub fn spawn_all(&self, cmd: &mut Commands, level: &LevelData) {
+        (self.spawn_all)(cmd, level);
+    }
+
+    pub fn remove(&self, level: &mut LevelData, uuid: Uuid) {
+        (self.remove)(level, uuid);
+    }
+
+    /// Adds a copy of the object, moved by `offset`.
+    pub fn duplicate(&self, cmd: &mut Commands, level: &mut LevelData, uuid: Uuid, offset: Vec2) {
+        (self.duplicate)(cmd, level, uuid, offset);
+    }
+
+    pub fn translate(
+        &self,
+        cmd: &mut Commands,
+        level: &mut LevelData,
+        entity: Entity,
+        uuid: Uuid,
+        offset: Vec2,
+    ) {
+        (self.translate)(cmd, level, entity, uuid, offset);
+    }
+
+    pub fn resize(
+        &self,
+        cmd: &mut Commands,
+        level: &mut LevelData,
+        entity: Entity,
+        uuid: Uuid,
+        from: Rect,
+        to: Rect,
+    ) {
+        (self.resize)(cmd, level, entity, uuid, from, to);
+    }
+
+    /// Returns the new rotation of the object transform, if it has one.
+    pub fn rotate(
+        &self,
+        cmd: &mut Commands,
+        level: &mut LevelData,
+        entity: Entity,
+        uuid: Uuid,
+        delta: f32,
+    ) -> Option<f32> {
+        (self.rotate)(cmd, level, entity, uuid, delta)
+    }
+
+    pub fn surface<'a>(&self, level: &'a LevelData, uuid: Uuid) -> Option<&'a str> {
+        (self.surface)(level, uuid)
+    }
+
+    /// Whether the object has a surface to set.
+    pub fn set_surface(&self, level: &mut LevelData, uuid: Uuid, surface: &str) -> bool {
+        (self.set_surface)(level, uuid, surface)
+    }
+
+    /// Keeps only the objects of this kind whose uuid passes `keep`, without touching the rest
+    /// of the level.
+    pub fn retain(&self, level: &mut LevelData, keep: &dyn Fn(&Uuid) -> bool) {
+        (self.retain)(level, keep);
+    }
+
+    /// Copies the object `uuid` of `level` into `prefab`, moved by `offset`.
+    pub fn extract(&self, level: &LevelData, uuid: Uuid, prefab: &mut LevelData, offset: Vec2) {
+        (self.extract)(level, uuid, prefab, offset);
+    }
+
+    /// Replaces the links of every object of this kind, see [`LevelObject::remap`].
+    pub fn remap(&self, level: &mut LevelData, f: &dyn Fn(Uuid) -> Option<Uuid>) {
+        (self.remap)(level, f);
+    }
+
+    /// Adds the objects of this kind from `prefab` to the level, for the prefab instance
+    /// `instance` placed at `pos`. Returns their uuids.
+    pub fn expand(
+        &self,
+        level: &mut LevelData,
+        prefab: &LevelData,
+        instance: Uuid,
+        pos: Vec2,
+    ) -> Vec<Uuid> {
+        (self.expand)(level, prefab, instance, pos)
+    }
+
+    /// Spawns the object `uuid`, if it is of this kind.
+    pub fn spawn(&self, cmd: &mut Commands, level: &LevelData, uuid: Uuid) {
+        (self.spawn)(cmd, level, uuid);
+    }
+}
+
+/// A button of the editor add menu.
+pub struct MenuEntry {
+    pub label: &'static str,
+    create: Box<dyn Fn(&mut Commands, &mut LevelData, Vec2) + Send + Sync>,
+}
+
+impl MenuEntry {
+    /// Adds an object at `point` to the level and spawns it.
+    pub fn create(&self, cmd: &mut Commands, level: &mut LevelData, point: Vec2) {
+        (self.create)(cmd, level, point);
+    }
+}
+
+/// The kinds of objects.
+#[derive(Resource)]
+pub struct LevelObjects {
+    kinds: HashMap<EntityType, RegisteredKind>,
+    /// In the order of [`LevelObjects::default`].
+    order: Vec<EntityType>,
+    menu: Vec<MenuEntry>,
+}
+
+impl Default for LevelObjects {
+    /// Every kind of object a level holds. Their plugins register how the editor handles them,
+    /// the tools reading level files without the game use the list as it is.
+    fn default() -> Self {
+        let mut objects = Self {
+            kinds: HashMap::new(),
+            order: vec![],
+            menu: vec![],
+        };
+        objects.add::<WallData>();
+        objects.add::<PolygonWallData>();
+        objects.add::<TrackData>();
+        objects.add::<PlatformData>();
+        objects.add::<DoorData>();
+        objects.add::<CheckpointData>();
+        objects.add::<HazardData>();
+        objects.add::<RepairKitData>();
+        objects.add::<BoltData>();
+        objects.add::<TriggerData>();
+        objects.add::<SwitchData>();
+        objects.add::<PrefabInstanceData>();
+        objects.add::<DecorationData>();
+        objects.add::<GoalData>();
+        objects.add::<SignData>();
+        objects.add::<ArrowData>();
+        objects
+    }
+}
+
+impl LevelObjects {
+    fn add<T: LevelObject>(&mut self) {
+        let e_type = EntityType::of::<T>();
+        self.order.push(e_type);
+        self.kinds.insert(
+            e_type,
+            RegisteredKind {
+                shape: ObjectShape::Sized,
+                resizable: false,
+                rotatable: false,
+                registered: false,
+                spawn_all: spawn_all::<T>,
+                remove: remove::<T>,
+                duplicate: duplicate::<T>,
+                translate: translate::<T>,
+                resize: resize::<T>,
+                rotate: rotate::<T>,
+                surface: surface::<T>,
+                set_surface: set_surface::<T>,
+                retain: retain::<T>,
+                extract: extract::<T>,
+                remap: remap::<T>,
+                expand: expand::<T>,
+                spawn: spawn::<T>,
+            },
+        );
+    }
+
+    pub fn get(&self, e_type: &EntityType) -> Option<&RegisteredKind> {
+        self.kinds.get(e_type)
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = &RegisteredKind> {
+        self.order.iter().map(|e_type| &self.kinds[e_type])
+    }
+
+    pub fn menu(&self) -> &[MenuEntry] {
+        &self.menu
+    }
+
+    /// The level as it is saved, without the objects spawned from prefab instances.
+    pub fn without_prefab_objects(&self, level: &LevelData) -> LevelData {
+        let mut level = level.clone();
+        let members = std::mem::take(&mut level.prefab_objects);
+        for kind in self.kinds() {
+            kind.retain(&mut level, &|uuid| !members.contains_key(uuid));
+        }
+        level
+    }
+
+    /// Adds the objects of every prefab instance to the level, with the prefabs of `prefabs` by
+    /// name. Call it after [`LevelData::migrate`].
+    pub fn expand_prefabs(&self, level: &mut LevelData, prefabs: &HashMap<String, LevelData>) {
+        let instances: Vec<Uuid> = level.prefabs.keys().copied().collect();
+        for instance in instances {
+            self.expand_instance(level, prefabs, instance);
+        }
+    }
+
+    /// Adds the objects of the prefab instance `instance` to the level, returns their uuids.
+    pub fn expand_instance(
+        &self,
+        level: &mut LevelData,
+        prefabs: &HashMap<String, LevelData>,
+        instance: Uuid,
+    ) -> Vec<Uuid> {
+        let Some(data) = level.prefabs.get(&instance).cloned() else {
+            return vec![];
+        };
+        let Some(prefab) = prefabs.get(&data.prefab) else {
+            return vec![];
+        };
+        self.kinds()
+            .flat_map(|kind| kind.expand(level, prefab, instance, data.pos.into()))
+            .collect()
+    }
+}
+
+pub trait RegisterLevelObject {
+    fn register_level_object<T: LevelObject>(&mut self, kind: ObjectKind<T>) -> &mut Self;
+}
+
+impl RegisterLevelObject for App {
+    fn register_level_object<T: LevelObject>(&mut self, kind: ObjectKind<T>) -> &mut Self {
+        let mut objects = self
+            .world_mut()
+            .get_resource_or_insert_with(LevelObjects::default);
+        for (label, create) in kind.menu {
+            objects.menu.push(MenuEntry {
+                label,
+                create: Box::new(move |cmd, level, point| {
+                    let data = create(point);
+                    let uuid = Uuid::new_v4();
+                    T::map_mut(level).insert(uuid, data.clone());
+                    T::spawn(cmd, uuid, data);
+                }),
+            });
+        }
+        let entry = objects
+            .kinds
+            .get_mut(&EntityType::of::<T>())
+            .expect("level objects should be listed in LevelObjects::default");
+        assert!(!entry.registered, "level objects should be registered once");
+        entry.shape = kind.shape;
+        entry.resizable = kind.resizable;
+        entry.rotatable = kind.rotatable;
+        entry.registered = true;
+        self
+    }
+}
+
+fn spawn_all<T: LevelObject>(cmd: &mut Commands, level: &LevelData) {
+    for (uuid, data) in T::map(level) {
+        T::spawn(cmd, *uuid, data.clone());
+    }
+}
+
+fn remove<T: LevelObject>(level: &mut LevelData, uuid: Uuid) {
+    T::map_mut(level).remove(&uuid);
+    T::unlink(level, uuid);
+}
+
+fn duplicate<T: LevelObject>(cmd: &mut Commands, level: &mut LevelData, uuid: Uuid, offset: Vec2) {
+    if let Some(mut data) = T::map(level).get(&uuid).cloned() {
+        data.translate(offset);
+        let uuid = Uuid::new_v4();
+        T::map_mut(level).insert(uuid, data.clone());
+        T::spawn(cmd, uuid, data);
+    }
+}
+
+/// Changes the data of the object and tells its entity.
+fn edit<'a, T: LevelObject>(
+    cmd: &mut Commands,
+    level: &'a mut LevelData,
+    entity: Entity,
+    uuid: Uuid,
+    f: impl FnOnce(&mut T),
+) -> Option<&'a T> {
+    let data = T::map_mut(level).get_mut(&uuid)?;
+    f(data);
+    cmd.trigger_targets(ObjectEdited(data.clone()), entity);
+    Some(data)
+}
+
+fn translate<T: LevelObject>(
+    cmd: &mut Commands,
+    level: &mut LevelData,
+    entity: Entity,
+    uuid: Uuid,
+    offset: Vec2,
+) {
+    edit::<T>(cmd, level, entity, uuid, |data| data.translate(offset));
+}
+
+fn resize<T: LevelObject>(
+    cmd: &mut Commands,
+    level: &mut LevelData,
+    entity: Entity,
+    uuid: Uuid,
+    from: Rect,
+    to: Rect,
+) {
+    edit::<T>(cmd, level, entity, uuid, |data| data.resize(from, to));
+}
+
+fn rotate<T: LevelObject>(
+    cmd: &mut Commands,
+    level: &mut LevelData,
+    entity: Entity,
+    uuid: Uuid,
+    delta: f32,
+) -> Option<f32> {
+    edit::<T>(cmd, level, entity, uuid, |data| data.rotate(delta))?.angle()
+}
+
+fn surface<T: LevelObject>(level: &LevelData, uuid: Uuid) -> Option<&str> {
+    T::map(level).get(&uuid)?.surface()
+}
+
+fn set_surface<T: LevelObject>(level: &mut LevelData, uuid: Uuid, surface: &str) -> bool {
+    match T::map_mut(level)
+        .get_mut(&uuid)
+        .and_then(|data| data.surface_mut())
+    {
+        Some(data) => {
+            *data = surface.to_string();
+            true
+        }
+        None => false,
+    }
+}
+
+fn retain<T: LevelObject>(level: &mut LevelData, keep: &dyn Fn(&Uuid) -> bool) {
+    T::map_mut(level).retain(|uuid, _| keep(uuid));
+}
+
+fn extract<T: LevelObject>(level: &LevelData, uuid: Uuid, prefab: &mut LevelData, offset: Vec2) {
+    if let Some(mut data) = T::map(level).get(&uuid).cloned() {
+        data.translate(offset);
+        T::map_mut(prefab).insert(uuid, data);
+    }
+}
+
+fn remap<T: LevelObject>(level: &mut LevelData, f: &dyn Fn(Uuid) -> Option<Uuid>) {
+    for data in T::map_mut(level).values_mut() {
+        data.remap(f);
+    }
+}
+
+fn expand<T: LevelObject>(
+    level: &mut LevelData,
+    prefab: &LevelData,
+    instance: Uuid,
+    pos: Vec2,
+) -> Vec<Uuid> {
+    let mut uuids = vec![];
+    for (uuid, data) in T::map(prefab) {
+        let mut data = data.clone();
+        data.translate(pos);
+        // the objects of a prefab only link to each other
+        data.remap(&|link| Some(prefab_object_id(instance, link)));
+        let uuid = prefab_object_id(instance, *uuid);
+        level.prefab_objects.insert(uuid, instance);
+        T::map_mut(level).insert(uuid, data);
+        uuids.push(uuid);
+    }
+    uuids
+}
+
+fn spawn<T: LevelObject>(cmd: &mut Commands, level: &LevelData, uuid: Uuid) {
+    if let Some(data) = T::map(level).get(&uuid) {
+        T::spawn(cmd, uuid, data.clone());
+    }
+}
+
+/// Moves a rect by `offset`, for the kinds placed by their rect.
+pub fn translate_rect(rect: Rect, offset: Vec2) -> Rect {
+    Rect::from_center_size(rect.center() + offset, rect.size())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::vec2;
+
+    use crate::data::level::{my_vec2, CheckpointData};
+
+    use super::*;
+
+    #[test]
+    fn test_expand_prefabs() {
+        let mut prefab = LevelData::with_spawn(Vec2::ZERO);
+        prefab.checkpoints.insert(
+            Uuid::from_u128(1),
+            CheckpointData {
+                pos: my_vec2(1.0, 2.0),
+            },
+        );
+        let mut prefabs = HashMap::new();
+        prefabs.insert("ledge".to_string(), prefab);
+        let mut level = LevelData::with_spawn(Vec2::ZERO);
+        let instance = Uuid::from_u128(0xf0);
+        level.prefabs.insert(
+            instance,
+            PrefabInstanceData {
+                prefab: "ledge".to_string(),
+                pos: my_vec2(10.0, 0.0),
+            },
+        );
+        LevelObjects::default().expand_prefabs(&mut level, &prefabs);
+        let uuid = prefab_object_id(instance, Uuid::from_u128(1));
+        assert_eq!(Vec2::from(level.checkpoints[&uuid].pos), vec2(11.0, 2.0));
+        assert_eq!(level.prefab_objects[&uuid], instance);
+    }
+}
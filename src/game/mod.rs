@@ -5,33 +5,42 @@ use bevy_rapier2d::{plugin::RapierConfiguration, render::DebugRenderContext};
 
 use crate::{data::config::GameConfig, screen::Screen};
 
-#[cfg(feature = "dev")]
-use bevy::input::common_conditions::input_just_pressed;
-
 mod animation;
 pub mod arrow;
-pub mod arrow_tutorial;
 pub mod assets;
 pub mod atlas_animation;
 pub mod audio;
 pub mod background;
+pub mod bolt;
+#[cfg(feature = "dev")]
+mod bot;
 pub mod camera;
 pub mod checkpoint;
 
 pub mod death_state;
+pub mod decoration;
 #[cfg(feature = "dev")]
 pub mod editor;
 pub mod entity_id;
 pub mod entity_type;
+pub mod expression;
 pub mod fixed_material;
 pub mod game_time;
 pub mod goal;
+pub mod hazard;
+pub mod keybindings;
+pub mod level_object;
 mod movement;
 pub mod object_size;
+pub mod particle;
 pub mod pause;
 pub mod physics;
-pub mod space_tutorial;
+pub mod prefab;
+pub mod repair_kit;
+pub mod sign;
 pub mod spawn;
+pub mod switch;
+pub mod trigger_volume;
 pub mod ui;
 pub mod victory;
 
@@ -48,12 +57,23 @@ pub(super) fn plugin(app: &mut App) {
         fixed_material::plugin,
         background::plugin,
         checkpoint::plugin,
+        (
+            hazard::plugin,
+            repair_kit::plugin,
+            bolt::plugin,
+            trigger_volume::plugin,
+            switch::plugin,
+            prefab::plugin,
+            decoration::plugin,
+            expression::plugin,
+            particle::plugin,
+        ),
         (
             goal::plugin,
             victory::plugin,
             death_state::plugin,
-            space_tutorial::plugin,
-            arrow_tutorial::plugin,
+            keybindings::plugin,
+            sign::plugin,
             arrow::plugin,
             atlas_animation::plugin,
             game_time::plugin,
@@ -62,6 +82,8 @@ pub(super) fn plugin(app: &mut App) {
         ),
         #[cfg(feature = "dev")]
         editor::plugin,
+        #[cfg(feature = "dev")]
+        bot::plugin,
     ));
     app.add_systems(OnEnter(Screen::Playing), playing_entered);
     app.add_systems(OnEnter(GameState::Death), start_rapier);
@@ -71,11 +93,6 @@ pub(super) fn plugin(app: &mut App) {
     {
         app.add_systems(OnExit(GameState::Editing), start_rapier);
         app.add_systems(OnEnter(GameState::Editing), stop_rapier);
-        app.add_systems(OnEnter(GameState::BotSetup), stop_rapier);
-        app.add_systems(
-            Update,
-            go_to_bot_setup.run_if(input_just_pressed(KeyCode::F9)),
-        );
     }
 }
 
@@ -93,21 +110,6 @@ pub enum GameState {
     Editing,
 }
 
-#[cfg(feature = "dev")]
-fn go_to_bot_setup(
-    mut cmd: Commands,
-    mut next_state: ResMut<NextState<GameState>>,
-    state: Res<State<GameState>>,
-) {
-    use spawn::player::Respawn;
-    if *state == GameState::BotSetup {
-        next_state.set(GameState::Playing)
-    } else {
-        next_state.set(GameState::BotSetup);
-        cmd.trigger(Respawn);
-    }
-}
-
 fn playing_entered(
     mut rapier_debug_context: Option<ResMut<DebugRenderContext>>,
     config: Res<GameConfig>,
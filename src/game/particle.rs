@@ -0,0 +1,185 @@
+//! Lightweight particles simulated on the CPU: sparks of the hits, dust under the wheel and
+//! debris of the robot falling apart. The emitters are set in the config.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{math::vec2, prelude::*};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    data::config::{EmitterConfig, GameConfig},
+    game::{
+        assets::{HandleMap, ImageKey},
+        spawn::player::{Player, PlayerControls, PlayerOnGround, Wheel},
+        GameState,
+    },
+    screen::Screen,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_emit_particles)
+        .add_systems(
+            Update,
+            kick_up_dust
+                .in_set(AppSet::Update)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            update_particles
+                .in_set(AppSet::Update)
+                .run_if(in_state(Screen::Playing).and_then(not(in_state(GameState::Pause)))),
+        );
+}
+
+/// In front of the level and the robot.
+const PARTICLE_Z: f32 = 5.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Emitter {
+    Sparks,
+    Dust,
+    Debris,
+}
+
+impl Emitter {
+    fn config(self, config: &GameConfig) -> &EmitterConfig {
+        match self {
+            Emitter::Sparks => &config.particles.sparks,
+            Emitter::Dust => &config.particles.dust,
+            Emitter::Debris => &config.particles.debris,
+        }
+    }
+}
+
+/// Trigger this event to emit particles.
+#[derive(Event, Debug)]
+pub enum EmitParticles {
+    /// Emits from a point toward a direction, `strength` scales the count of the emitter.
+    At {
+        emitter: Emitter,
+        position: Vec2,
+        direction: Vec2,
+        strength: f32,
+    },
+    /// Bursts upward from where this entity is.
+    On(Emitter, Entity),
+}
+
+#[derive(Component, Debug)]
+struct Particle {
+    velocity: Vec2,
+    spin: f32,
+    gravity: f32,
+    size: f32,
+    life: Timer,
+}
+
+fn on_emit_particles(
+    trigger: Trigger<EmitParticles>,
+    mut cmd: Commands,
+    q_transform: Query<&GlobalTransform>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    config: Res<GameConfig>,
+) {
+    let (emitter, position, direction, strength) = match *trigger.event() {
+        EmitParticles::At {
+            emitter,
+            position,
+            direction,
+            strength,
+        } => (emitter, position, direction, strength),
+        EmitParticles::On(emitter, entity) => {
+            let Ok(gt) = q_transform.get(entity) else {
+                return;
+            };
+            (emitter, gt.translation().xy(), Vec2::Y, 1.0)
+        }
+    };
+    let emitter = emitter.config(&config);
+    let count = (emitter.count as f32 * strength.min(1.0)).ceil() as u32;
+    let angle = direction.y.atan2(direction.x);
+    let mut rng = thread_rng();
+    for _ in 0..count {
+        let spread = emitter.spread / 2.0;
+        let direction = Vec2::from_angle(angle + rng.gen_range(-spread..=spread));
+        let speed = rng.gen_range(emitter.min_speed..=emitter.max_speed);
+        let mut sprite = SpriteBundle {
+            transform: Transform::from_translation(position.extend(PARTICLE_Z))
+                .with_rotation(Quat::from_rotation_z(rng.gen_range(-FRAC_PI_2..=FRAC_PI_2))),
+            sprite: Sprite {
+                color: Color::from(Srgba::from(emitter.color)),
+                custom_size: Some(Vec2::splat(emitter.size)),
+                ..default()
+            },
+            ..default()
+        };
+        if emitter.bolts {
+            sprite.texture = image_handles[&ImageKey::Bolt].clone_weak();
+        }
+        cmd.spawn((
+            Name::new("Particle"),
+            Particle {
+                velocity: direction * speed,
+                spin: rng.gen_range(-emitter.spin..=emitter.spin),
+                gravity: emitter.gravity,
+                size: emitter.size,
+                life: Timer::from_seconds(emitter.lifetime, TimerMode::Once),
+            },
+            sprite,
+            StateScoped(Screen::Playing),
+        ));
+    }
+}
+
+/// Puffs dust behind the wheel while it pushes on the ground.
+fn kick_up_dust(
+    mut cmd: Commands,
+    mut since_puff: Local<f32>,
+    controls: Res<PlayerControls>,
+    q_on_ground: Query<(), (With<Player>, With<PlayerOnGround>)>,
+    q_wheel: Query<&GlobalTransform, With<Wheel>>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let Ok(gt) = q_wheel.get_single() else {
+        return;
+    };
+    if controls.lean == 0.0 || q_on_ground.is_empty() {
+        *since_puff = config.particles.dust_interval;
+        return;
+    }
+    *since_puff += time.delta_seconds();
+    if *since_puff < config.particles.dust_interval {
+        return;
+    }
+    *since_puff = 0.0;
+    // the wheel throws the dust back as it rolls forward
+    cmd.trigger(EmitParticles::At {
+        emitter: Emitter::Dust,
+        position: gt.translation().xy() - Vec2::Y,
+        direction: vec2(-controls.lean, 1.0).normalize(),
+        strength: 1.0,
+    });
+}
+
+fn update_particles(
+    mut cmd: Commands,
+    mut q_particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut particle, mut tr, mut sprite) in &mut q_particles {
+        if particle.life.tick(time.delta()).finished() {
+            cmd.entity(entity).despawn();
+            continue;
+        }
+        particle.velocity.y -= particle.gravity * dt;
+        tr.translation += (particle.velocity * dt).extend(0.0);
+        tr.rotate_z(particle.spin * dt);
+        let left = particle.life.fraction_remaining();
+        sprite.custom_size = Some(Vec2::splat(particle.size * left));
+        sprite.color.set_alpha(left);
+    }
+}
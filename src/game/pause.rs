@@ -1,10 +1,11 @@
 use bevy::{color::palettes::css::WHITE_SMOKE, prelude::*, window::PrimaryWindow};
 
-use crate::{data::config::GameConfig, screen::Screen, ui::prelude::*, AppSet};
+use crate::{screen::Screen, ui::prelude::*, AppSet};
 
 use super::{
     assets::{FontKey, HandleMap},
-    audio::soundtrack::AdjustSoundtrackVolume,
+    audio::mixer::{Bus, Mixer},
+    camera::ScreenShake,
     start_rapier, stop_rapier, GameState,
 };
 
@@ -19,7 +20,7 @@ pub(super) fn plugin(app: &mut App) {
                     handle_action.run_if(in_state(GameState::Pause)),
                 )
                     .in_set(AppSet::RecordInput),
-                update_sliders
+                (update_sliders, update_shake_toggle)
                     .in_set(AppSet::Update)
                     .run_if(in_state(GameState::Pause)),
             ),
@@ -35,33 +36,27 @@ struct TitleSection;
 struct ButtonSection;
 
 #[derive(Debug, Component)]
-struct MusicSliderSection;
+struct SliderSection;
 
+/// The slider of the gain of a bus of the mixer.
 #[derive(Debug, Component, Clone, Copy)]
-struct MusicSlider;
-
-#[derive(Debug, Component)]
-struct SfxSliderSection;
-
-#[derive(Debug, Component, Clone, Copy)]
-struct SfxSlider;
+struct BusSlider(Bus);
 
 #[derive(Debug, Component)]
 enum Action {
     Resume,
     Title,
-    DecMusic,
-    IncMusic,
-    DecSfx,
-    IncSfx,
+    Dec(Bus),
+    Inc(Bus),
+    ToggleShake,
 }
 
 fn handle_action(
     mut button_query: InteractionQuery<&Action>,
     mut next_game_state: ResMut<NextState<GameState>>,
     mut next_screen: ResMut<NextState<Screen>>,
-    mut config: ResMut<GameConfig>,
-    mut cmd: Commands,
+    mut mixer: ResMut<Mixer>,
+    mut screen_shake: ResMut<ScreenShake>,
 ) {
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
@@ -72,23 +67,16 @@ fn handle_action(
                 Action::Title => {
                     next_screen.set(Screen::Title);
                 }
-                Action::DecMusic => {
-                    config.audio.soundtrack_volume =
-                        (config.audio.soundtrack_volume - 0.05).max(0.0);
-                    cmd.trigger(AdjustSoundtrackVolume(config.audio.soundtrack_volume));
+                Action::Dec(bus) => {
+                    let gain = mixer.gain(*bus);
+                    mixer.set_gain(*bus, gain - 0.05);
                 }
-                Action::IncMusic => {
-                    config.audio.soundtrack_volume =
-                        (config.audio.soundtrack_volume + 0.05).min(1.0);
-                    cmd.trigger(AdjustSoundtrackVolume(config.audio.soundtrack_volume));
+                Action::Inc(bus) => {
+                    let gain = mixer.gain(*bus);
+                    mixer.set_gain(*bus, gain + 0.05);
                 }
-                Action::DecSfx => {
-                    config.audio.sfx_volume = (config.audio.sfx_volume - 0.05).max(0.0);
-                    // cmd.trigger(AdjustSoundtrackVolume(config.audio.soundtrack_volume));
-                }
-                Action::IncSfx => {
-                    config.audio.sfx_volume = (config.audio.sfx_volume + 0.05).min(1.0);
-                    // cmd.trigger(AdjustSoundtrackVolume(config.audio.soundtrack_volume));
+                Action::ToggleShake => {
+                    screen_shake.0 = !screen_shake.0;
                 }
             }
         }
@@ -124,42 +112,49 @@ fn enter_pause(
                         ..default()
                     },
                 ));
-                cmd.spawn((
-                    MusicSliderSection,
-                    NodeBundle {
-                        style: Style {
-                            display: Display::Flex,
-                            flex_direction: FlexDirection::Row,
-                            align_items: AlignItems::Center,
-                            column_gap: Val::Vh(1.0),
-                            width: Val::Vh(70.),
-                            // flex_grow: 1.0,
+                for (label, bus) in [
+                    ("Master", Bus::Master),
+                    ("Music", Bus::Music),
+                    ("Sfx", Bus::Sfx),
+                    ("Interface", Bus::Ui),
+                ] {
+                    cmd.spawn((
+                        SliderSection,
+                        NodeBundle {
+                            style: Style {
+                                display: Display::Flex,
+                                flex_direction: FlexDirection::Row,
+                                align_items: AlignItems::Center,
+                                column_gap: Val::Vh(1.0),
+                                width: Val::Vh(70.),
+                                // flex_grow: 1.0,
+                                ..default()
+                            },
                             ..default()
                         },
-                        ..default()
-                    },
-                ))
-                .with_children(|cmd| {
-                    let mut text = TextBundle::from_section(
-                        "Music",
-                        TextStyle {
-                            color: Color::from(WHITE_SMOKE),
-                            font: font_handles[&FontKey::GeoFont].clone_weak(),
-                            font_size: normal_font_size,
-                        },
-                    );
-                    text.style.width = Val::Vh(40.);
-                    cmd.spawn(text);
-                    slider(
-                        cmd,
-                        MusicSlider,
-                        font_handles[&FontKey::GeoFont].clone_weak(),
-                        normal_font_size,
-                        (Action::DecMusic, Action::IncMusic),
-                    );
-                });
+                    ))
+                    .with_children(|cmd| {
+                        let mut text = TextBundle::from_section(
+                            label,
+                            TextStyle {
+                                color: Color::from(WHITE_SMOKE),
+                                font: font_handles[&FontKey::GeoFont].clone_weak(),
+                                font_size: normal_font_size,
+                            },
+                        );
+                        text.style.width = Val::Vh(40.);
+                        cmd.spawn(text);
+                        slider(
+                            cmd,
+                            BusSlider(bus),
+                            font_handles[&FontKey::GeoFont].clone_weak(),
+                            normal_font_size,
+                            (Action::Dec(bus), Action::Inc(bus)),
+                        );
+                    });
+                }
                 cmd.spawn((
-                    SfxSliderSection,
+                    SliderSection,
                     NodeBundle {
                         style: Style {
                             display: Display::Flex,
@@ -167,30 +162,23 @@ fn enter_pause(
                             align_items: AlignItems::Center,
                             column_gap: Val::Vh(1.0),
                             width: Val::Vh(70.),
-                            // flex_grow: 1.0,
                             ..default()
                         },
                         ..default()
                     },
                 ))
                 .with_children(|cmd| {
-                    let mut text = TextBundle::from_section(
-                        "Sfx",
-                        TextStyle {
-                            color: Color::from(WHITE_SMOKE),
-                            font: font_handles[&FontKey::GeoFont].clone_weak(),
-                            font_size: normal_font_size,
-                        },
-                    );
-                    text.style.width = Val::Vh(40.0);
+                    let style = TextStyle {
+                        color: Color::from(WHITE_SMOKE),
+                        font: font_handles[&FontKey::GeoFont].clone_weak(),
+                        font_size: normal_font_size,
+                    };
+                    let mut text = TextBundle::from_section("Screen shake", style.clone());
+                    text.style.width = Val::Vh(40.);
                     cmd.spawn(text);
-                    slider(
-                        cmd,
-                        SfxSlider,
-                        font_handles[&FontKey::GeoFont].clone_weak(),
-                        normal_font_size,
-                        (Action::DecSfx, Action::IncSfx),
-                    );
+                    cmd.button(normal_font_size, "Toggle")
+                        .insert(Action::ToggleShake);
+                    cmd.spawn((ShakeToggleText, TextBundle::from_section("", style)));
                 });
                 cmd.spawn((
                     ButtonSection,
@@ -218,22 +206,24 @@ fn enter_pause(
 #[derive(Debug, Component)]
 struct SliderText;
 
-fn update_sliders(
-    mut q_music_slider_text: Query<
-        &mut Text,
-        (With<MusicSlider>, With<SliderText>, Without<SfxSlider>),
-    >,
-    mut q_sfx_slider_text: Query<
-        &mut Text,
-        (With<SfxSlider>, With<SliderText>, Without<MusicSlider>),
-    >,
-    config: Res<GameConfig>,
+#[derive(Debug, Component)]
+struct ShakeToggleText;
+
+fn update_shake_toggle(
+    mut q_text: Query<&mut Text, With<ShakeToggleText>>,
+    screen_shake: Res<ScreenShake>,
 ) {
-    for mut text in &mut q_music_slider_text {
-        text.sections[0].value = format!("{:3.0}%", config.audio.soundtrack_volume * 100.0);
+    for mut text in &mut q_text {
+        text.sections[0].value = if screen_shake.0 { "On" } else { "Off" }.to_string();
     }
-    for mut text in &mut q_sfx_slider_text {
-        text.sections[0].value = format!("{:3.0}%", config.audio.sfx_volume * 100.0);
+}
+
+fn update_sliders(
+    mut q_slider_text: Query<(&mut Text, &BusSlider), With<SliderText>>,
+    mixer: Res<Mixer>,
+) {
+    for (mut text, slider) in &mut q_slider_text {
+        text.sections[0].value = format!("{:3.0}%", mixer.gain(slider.0) * 100.0);
     }
 }
 
@@ -1,3 +1,4 @@
+use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 pub struct ObjectGroup;
@@ -5,8 +6,10 @@ impl ObjectGroup {
     pub const PLAYER: u32 = 1 << 1;
     pub const WHEEL: u32 = 1 << 2;
     pub const WALL: u32 = 1 << 3;
-    pub const CHECKPOINT: u32 = 1 << 4;
-    pub const GOAL: u32 = 1 << 5;
+    /// Goals, checkpoints and level triggers, see [`super::trigger_volume::TriggerVolume`].
+    pub const TRIGGER: u32 = 1 << 4;
+    pub const HAZARD: u32 = 1 << 6;
+    pub const PICKUP: u32 = 1 << 7;
 }
 
 pub fn coll_groups(members: u32, filters: u32) -> CollisionGroups {
@@ -15,3 +18,99 @@ pub fn coll_groups(members: u32, filters: u32) -> CollisionGroups {
         Group::from_bits_retain(filters),
     )
 }
+
+/// What the wheel can stand on: walls, tracks, doors and moving platforms. The kinematic bodies
+/// are kept, and the groups leave out hazards, pickups and triggers, which have no rigid body and
+/// would count as fixed.
+pub fn ground_filter() -> QueryFilter<'static> {
+    QueryFilter::exclude_dynamic().groups(coll_groups(
+        ObjectGroup::PLAYER + ObjectGroup::WHEEL,
+        ObjectGroup::WALL,
+    ))
+}
+
+/// Where two colliders touch, the first point of contact the solver found between them.
+pub fn contact_point(
+    rapier_context: &RapierContext,
+    collider1: Entity,
+    collider2: Entity,
+) -> Option<Vect> {
+    let pair = rapier_context.contact_pair(collider1, collider2)?;
+    let point = pair
+        .manifolds()
+        .find_map(|manifold| manifold.solver_contact(0).map(|contact| contact.point()));
+    point
+}
+
+/// How hard a collider pushes on everything it touches, from the impulses of the last step.
+pub fn contact_force(rapier_context: &RapierContext, collider: Entity) -> f32 {
+    let mut impulse = 0.0;
+    for pair in rapier_context.contact_pairs_with(collider) {
+        for manifold in pair.manifolds() {
+            impulse += manifold.points().map(|point| point.impulse()).sum::<f32>();
+        }
+    }
+    impulse / rapier_context.integration_parameters.dt
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_rapier2d::rapier::prelude as rapier;
+
+    use super::*;
+
+    /// Whether the wheel resting on a collider spawned with these groups counts as on the ground.
+    fn touches_ground(body: Option<rapier::RigidBodyBuilder>, groups: CollisionGroups) -> bool {
+        let mut bodies = rapier::RigidBodySet::new();
+        let mut colliders = rapier::ColliderSet::new();
+        let collider = rapier::ColliderBuilder::cuboid(10.0, 1.0).collision_groups(groups.into());
+        match body {
+            Some(body) => {
+                let parent = bodies.insert(body);
+                colliders.insert_with_parent(collider, parent, &mut bodies);
+            }
+            None => {
+                colliders.insert(collider);
+            }
+        }
+        let mut pipeline = rapier::QueryPipeline::new();
+        pipeline.update(&colliders);
+        let filter = ground_filter();
+        let filter = rapier::QueryFilter {
+            flags: filter.flags,
+            groups: filter.groups.map(Into::into),
+            ..Default::default()
+        };
+        pipeline
+            .intersection_with_shape(
+                &bodies,
+                &colliders,
+                &rapier::Isometry::translation(0.0, 2.0),
+                &rapier::Ball::new(1.05),
+                filter,
+            )
+            .is_some()
+    }
+
+    #[test]
+    fn test_ground_filter() {
+        let wall = coll_groups(ObjectGroup::WALL, Group::all().bits());
+        assert!(touches_ground(None, wall));
+        // the robot stands on a moving platform
+        let platform = rapier::RigidBodyBuilder::kinematic_position_based();
+        assert!(touches_ground(Some(platform), wall));
+        // the arms and the torso
+        let limb = coll_groups(ObjectGroup::PLAYER, ObjectGroup::WALL);
+        assert!(!touches_ground(
+            Some(rapier::RigidBodyBuilder::dynamic()),
+            limb
+        ));
+        // hazards have no rigid body, they would pass for fixed ground
+        let hazard = coll_groups(ObjectGroup::HAZARD, ObjectGroup::PLAYER);
+        assert!(!touches_ground(None, hazard));
+        let pickup = coll_groups(ObjectGroup::PICKUP, ObjectGroup::PLAYER);
+        assert!(!touches_ground(None, pickup));
+        let trigger = coll_groups(ObjectGroup::TRIGGER, ObjectGroup::PLAYER);
+        assert!(!touches_ground(None, trigger));
+    }
+}
@@ -0,0 +1,263 @@
+//! Prefabs: groups of objects saved to their own file and placed in the level as instances.
+//! The objects of an instance are added to the level data when it is loaded, with uuids derived
+//! from the instance, and are never saved with the level. Editing a prefab updates all its
+//! instances.
+
+use bevy::{
+    asset::LoadState,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use uuid::Uuid;
+
+use crate::{
+    data::level::{prefab_path, LevelData, PrefabInstanceData},
+    screen::Screen,
+};
+
+use super::{
+    entity_id::EntityId,
+    entity_type::EntityType,
+    level_object::{
+        LevelObject, LevelObjects, ObjectEdited, ObjectKind, ObjectRemoved, ObjectShape,
+        RegisterLevelObject,
+    },
+    object_size::ObjectSize,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Prefabs>()
+        .observe(on_spawn_prefab_instance)
+        .observe(on_refresh_instance)
+        .observe(on_edit_instance)
+        .observe(on_remove_instance)
+        .add_systems(
+            Update,
+            (
+                load_prefabs.run_if(resource_added::<LevelData>),
+                complete_prefabs.run_if(resource_exists::<PrefabHandles>),
+            )
+                .chain()
+                .run_if(in_state(Screen::Loading)),
+        )
+        // instances are added from the prefab list of the editor add menu
+        .register_level_object(ObjectKind::<PrefabInstanceData>::new(ObjectShape::Sized));
+}
+
+/// Size of the marker of an instance, around its origin.
+const INSTANCE_SIZE: Vec2 = Vec2::splat(2.0);
+
+/// The loaded prefabs, by name.
+#[derive(Debug, Resource, Default)]
+pub struct Prefabs {
+    pub prefabs: HashMap<String, LevelData>,
+    /// Whether the prefabs of the level are loaded and their instances expanded.
+    pub loaded: bool,
+}
+
+impl Prefabs {
+    /// The names of the prefabs, sorted.
+    #[cfg_attr(not(feature = "dev"), allow(dead_code))]
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<_> = self.prefabs.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+}
+
+#[derive(Debug, Resource)]
+struct PrefabHandles(Vec<(String, Handle<LevelData>)>);
+
+/// The marker of a prefab instance, its objects are spawned on their own.
+#[derive(Debug, Component)]
+pub struct PrefabInstance;
+
+#[derive(Debug, Event)]
+pub struct SpawnPrefabInstance(pub Uuid, pub PrefabInstanceData);
+
+/// Replaces the objects of the instance with a fresh copy of its prefab, or removes them if the
+/// instance was deleted.
+#[derive(Debug, Event)]
+pub struct RefreshInstance(pub Uuid);
+
+impl LevelObject for PrefabInstanceData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.prefabs
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.prefabs
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnPrefabInstance(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+}
+
+/// Adds an instance of `prefab` at `pos` to the level, its objects follow once it is spawned.
+#[cfg_attr(not(feature = "dev"), allow(dead_code))]
+pub fn add_instance(cmd: &mut Commands, level: &mut LevelData, prefab: &str, pos: Vec2) -> Uuid {
+    let uuid = Uuid::new_v4();
+    let data = PrefabInstanceData {
+        prefab: prefab.to_string(),
+        pos: pos.into(),
+    };
+    level.prefabs.insert(uuid, data.clone());
+    cmd.trigger(SpawnPrefabInstance(uuid, data));
+    uuid
+}
+
+/// Removes the objects of the instance from the level, returns their uuids.
+fn strip_instance(level: &mut LevelData, objects: &LevelObjects, instance: Uuid) -> HashSet<Uuid> {
+    let members: HashSet<Uuid> = level
+        .prefab_objects
+        .iter()
+        .filter(|(_, i)| **i == instance)
+        .map(|(uuid, _)| *uuid)
+        .collect();
+    level
+        .prefab_objects
+        .retain(|uuid, _| !members.contains(uuid));
+    for kind in objects.kinds() {
+        kind.retain(level, &|uuid| !members.contains(uuid));
+    }
+    members
+}
+
+fn load_prefabs(mut cmd: Commands, asset_server: Res<AssetServer>, level: Res<LevelData>) {
+    let mut names: HashSet<String> = level
+        .prefabs
+        .values()
+        .map(|instance| instance.prefab.clone())
+        .collect();
+    // the editor can place the prefabs the level doesn't use yet
+    #[cfg(feature = "dev")]
+    if let Ok(dir) = std::fs::read_dir("assets/prefabs") {
+        for entry in dir.flatten() {
+            if let Some(name) = entry
+                .file_name()
+                .to_str()
+                .and_then(|file| file.strip_suffix(".prefab.ron"))
+            {
+                names.insert(name.to_string());
+            }
+        }
+    }
+    let handles = names
+        .into_iter()
+        .map(|name| {
+            let handle = asset_server.load::<LevelData>(prefab_path(&name));
+            (name, handle)
+        })
+        .collect();
+    cmd.insert_resource(PrefabHandles(handles));
+}
+
+fn complete_prefabs(
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+    handles: Res<PrefabHandles>,
+    mut level_datas: ResMut<Assets<LevelData>>,
+    mut level: ResMut<LevelData>,
+    mut prefabs: ResMut<Prefabs>,
+    objects: Res<LevelObjects>,
+) {
+    let failed = |handle: &Handle<LevelData>| {
+        matches!(asset_server.load_state(handle), LoadState::Failed(_))
+    };
+    if !handles
+        .0
+        .iter()
+        .all(|(_, handle)| asset_server.is_loaded_with_dependencies(handle) || failed(handle))
+    {
+        return;
+    }
+    for (name, handle) in &handles.0 {
+        match level_datas.remove(handle) {
+            Some(mut prefab) => {
+                if !prefab.prefabs.is_empty() {
+                    warn!("The prefab {name} holds prefab instances, prefabs can't be nested");
+                    prefab.prefabs.clear();
+                }
+                prefabs.prefabs.insert(name.clone(), prefab);
+            }
+            None => warn!("Unable to load the prefab {name}, its instances are left empty"),
+        }
+    }
+    objects.expand_prefabs(&mut level, &prefabs.prefabs);
+    prefabs.loaded = true;
+    cmd.remove_resource::<PrefabHandles>();
+}
+
+fn on_spawn_prefab_instance(
+    trigger: Trigger<SpawnPrefabInstance>,
+    mut cmd: Commands,
+    level: Res<LevelData>,
+) {
+    let SpawnPrefabInstance(uuid, data) = trigger.event();
+    cmd.spawn((
+        PrefabInstance,
+        EntityId(*uuid),
+        EntityType::of::<PrefabInstanceData>(),
+        ObjectSize(INSTANCE_SIZE),
+        TransformBundle::from_transform(Transform::from_translation(
+            Vec2::from(data.pos).extend(0.0),
+        )),
+        StateScoped(Screen::Playing),
+    ));
+    // a new instance, from the editor
+    if !level
+        .prefab_objects
+        .values()
+        .any(|instance| instance == uuid)
+    {
+        cmd.trigger(RefreshInstance(*uuid));
+    }
+}
+
+fn on_refresh_instance(
+    trigger: Trigger<RefreshInstance>,
+    mut cmd: Commands,
+    mut level: ResMut<LevelData>,
+    objects: Res<LevelObjects>,
+    prefabs: Res<Prefabs>,
+    q_objects: Query<(Entity, &EntityId)>,
+) {
+    let instance = trigger.event().0;
+    let members = strip_instance(&mut level, &objects, instance);
+    for (entity, eid) in &q_objects {
+        if members.contains(&eid.0) {
+            cmd.entity(entity).despawn_recursive();
+        }
+    }
+    for uuid in objects.expand_instance(&mut level, &prefabs.prefabs, instance) {
+        for kind in objects.kinds() {
+            kind.spawn(&mut cmd, &level, uuid);
+        }
+    }
+}
+
+fn on_edit_instance(
+    trigger: Trigger<ObjectEdited<PrefabInstanceData>>,
+    mut cmd: Commands,
+    q_instances: Query<&EntityId, With<PrefabInstance>>,
+) {
+    if let Ok(eid) = q_instances.get(trigger.entity()) {
+        cmd.trigger(RefreshInstance(eid.0));
+    }
+}
+
+fn on_remove_instance(trigger: Trigger<ObjectRemoved>, mut cmd: Commands, level: Res<LevelData>) {
+    let uuid = trigger.event().0;
+    if level
+        .prefab_objects
+        .values()
+        .any(|instance| *instance == uuid)
+    {
+        cmd.trigger(RefreshInstance(uuid));
+    }
+}
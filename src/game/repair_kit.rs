@@ -0,0 +1,186 @@
+//! Repair kits give back a lost arm when the torso touches them. They are used up for the
+//! attempt, respawning at a checkpoint brings back the ones used after reaching it.
+
+use bevy::{
+    prelude::*,
+    sprite::Anchor,
+    utils::{HashMap, HashSet},
+};
+use bevy_rapier2d::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    data::{
+        config::GameConfig,
+        level::{LevelData, RepairKitData},
+    },
+    screen::Screen,
+    AppSet,
+};
+
+use super::{
+    assets::{HandleMap, ImageKey, SfxKey},
+    audio::sfx::PlaySfx,
+    entity_id::EntityId,
+    entity_type::EntityType,
+    level_object::{LevelObject, ObjectKind, ObjectShape, RegisterLevelObject},
+    physics::{coll_groups, ObjectGroup},
+    spawn::player::{LostLimbs, RepairArm, Torso},
+    GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_spawn_repair_kit)
+        .init_resource::<UsedRepairKits>()
+        .add_systems(
+            Update,
+            check_player_collision
+                .in_set(AppSet::TickTimers)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            show_unused
+                .in_set(AppSet::Update)
+                .run_if(resource_changed::<UsedRepairKits>),
+        )
+        .register_level_object(
+            ObjectKind::<RepairKitData>::new(ObjectShape::Sprite)
+                .menu_entry("Repair kit", |point| RepairKitData { pos: point.into() }),
+        );
+}
+
+/// The repair kits used during this attempt.
+#[derive(Debug, Resource, Default, Clone, PartialEq, Eq)]
+pub struct UsedRepairKits(pub HashSet<Uuid>);
+
+#[derive(Debug, Event)]
+pub struct SpawnRepairKit(pub Uuid, pub RepairKitData);
+
+impl LevelObject for RepairKitData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.repair_kits
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.repair_kits
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnRepairKit(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct RepairKit;
+
+#[derive(Debug, Component)]
+pub struct RepairKitCollider;
+
+fn on_spawn_repair_kit(
+    trigger: Trigger<SpawnRepairKit>,
+    mut cmd: Commands,
+    config: Res<GameConfig>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    used: Res<UsedRepairKits>,
+) {
+    let SpawnRepairKit(uuid, data) = trigger.event();
+    cmd.spawn((
+        RepairKit,
+        EntityId(*uuid),
+        EntityType::of::<RepairKitData>(),
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(config.repair_kit.size.into()),
+                anchor: Anchor::BottomCenter,
+                ..default()
+            },
+            texture: image_handles[&ImageKey::RepairKit].clone_weak(),
+            transform: Transform::from_translation(Vec2::from(data.pos).extend(-1.0)),
+            visibility: kit_visibility(used.0.contains(uuid)),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ))
+    .with_children(|cmd| {
+        cmd.spawn((
+            RepairKitCollider,
+            TransformBundle::from_transform(Transform::from_translation(
+                Vec2::from(config.repair_kit.collider.pos).extend(0.0),
+            )),
+            coll_groups(ObjectGroup::PICKUP, ObjectGroup::PLAYER),
+            Sensor,
+            Collider::cuboid(
+                config.repair_kit.collider.size.x / 2.0,
+                config.repair_kit.collider.size.y / 2.0,
+            ),
+        ));
+    });
+}
+
+fn kit_visibility(used: bool) -> Visibility {
+    if used {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    }
+}
+
+/// Kits are only used when an arm is missing.
+fn check_player_collision(
+    mut cmd: Commands,
+    rapier_context: Res<RapierContext>,
+    q_torso: Query<(&GlobalTransform, &Collider), With<Torso>>,
+    q_kit_colliders: Query<&Parent, With<RepairKitCollider>>,
+    q_kits: Query<&EntityId, With<RepairKit>>,
+    lost_limbs: Res<LostLimbs>,
+    mut used: ResMut<UsedRepairKits>,
+) {
+    if lost_limbs.limb_count() == 2 {
+        return;
+    }
+    for (body_tr, body_collider) in &q_torso {
+        let body_tr = body_tr.compute_transform();
+        let shape_pos = body_tr.translation.xy();
+        let shape_rot = body_tr.rotation.to_axis_angle().1;
+        let filter =
+            QueryFilter::default().groups(coll_groups(ObjectGroup::PLAYER, ObjectGroup::PICKUP));
+        let mut touched = None;
+        rapier_context.intersections_with_shape(
+            shape_pos,
+            shape_rot,
+            body_collider,
+            filter,
+            |entity| {
+                let eid = q_kit_colliders
+                    .get(entity)
+                    .and_then(|parent| q_kits.get(parent.get()));
+                match eid {
+                    Ok(eid) if !used.0.contains(&eid.0) => {
+                        touched = Some(eid.0);
+                        false
+                    }
+                    _ => true,
+                }
+            },
+        );
+        if let Some(uuid) = touched {
+            used.0.insert(uuid);
+            cmd.trigger(RepairArm);
+            cmd.trigger(PlaySfx::Key(SfxKey::ButtonPress));
+        }
+    }
+}
+
+fn show_unused(
+    used: Res<UsedRepairKits>,
+    mut q_kits: Query<(&EntityId, &mut Visibility), With<RepairKit>>,
+) {
+    for (eid, mut visibility) in &mut q_kits {
+        *visibility = kit_visibility(used.0.contains(&eid.0));
+    }
+}
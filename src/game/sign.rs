@@ -0,0 +1,289 @@
+//! Signs showing a text, an image or the keys bound to an input, fading in when the torso comes
+//! near. They replace the tutorial sprites, see [`crate::data::level::LevelData::migrate`].
+
+use bevy::{prelude::*, text::Text2dBounds, utils::HashMap};
+use uuid::Uuid;
+
+use crate::{
+    data::{
+        config::GameConfig,
+        level::{InputPrompt, LevelData, SignContent, SignData, SignImage},
+    },
+    screen::Screen,
+    AppSet,
+};
+
+use super::{
+    assets::{FontKey, HandleMap, ImageKey},
+    entity_id::EntityId,
+    entity_type::EntityType,
+    keybindings::{keys_name, Keybindings},
+    level_object::{LevelObject, ObjectKind, ObjectShape, RegisterLevelObject},
+    object_size::{ObjectSize, RepositionRect},
+    spawn::player::Torso,
+    GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_spawn_sign)
+        .observe(on_reposition_sign)
+        .add_systems(
+            Update,
+            fade_signs
+                .in_set(AppSet::Update)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            show_prompts
+                .in_set(AppSet::Update)
+                .run_if(resource_changed::<Keybindings>),
+        )
+        // the text is written in the level file
+        .register_level_object(
+            ObjectKind::<SignData>::new(ObjectShape::Sized)
+                .resizable()
+                .menu_entry("Sign", |point| SignData {
+                    pos: point.into(),
+                    size: Vec2::new(8.0, 3.0).into(),
+                    content: SignContent::Text("Sign".to_string()),
+                })
+                .menu_entry("Arrow tutorial", |point| {
+                    image_sign(SignImage::ArrowTutorial, point)
+                })
+                .menu_entry("Space tutorial", |point| {
+                    image_sign(SignImage::SpaceTutorial, point)
+                }),
+        );
+    // the editor shows every sign
+    #[cfg(feature = "dev")]
+    app.add_systems(OnEnter(GameState::Editing), show_signs);
+}
+
+/// Font size the text is rendered at, before being scaled down to the configured text height.
+const TEXT_RESOLUTION: f32 = 48.0;
+
+#[derive(Debug, Component)]
+pub struct Sign {
+    /// From hidden at 0 to fully shown at 1.
+    visibility: f32,
+    /// Alpha of the sprite when fully shown.
+    opacity: f32,
+}
+
+/// The text of a sign, with the input it prompts for if any.
+#[derive(Debug, Component)]
+struct SignText(Option<InputPrompt>);
+
+#[derive(Debug, Event)]
+pub struct SpawnSign(pub Uuid, pub SignData);
+
+impl LevelObject for SignData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.signs
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.signs
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnSign(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+
+    fn resize(&mut self, _from: Rect, to: Rect) {
+        self.pos = to.center().into();
+        self.size = to.size().into();
+    }
+}
+
+fn image_sign(image: SignImage, point: Vec2) -> SignData {
+    SignData {
+        pos: point.into(),
+        size: image.native_size().into(),
+        content: SignContent::Image(image),
+    }
+}
+
+fn image_key(image: SignImage) -> ImageKey {
+    match image {
+        SignImage::ArrowTutorial => ImageKey::ArrowTutorial,
+        SignImage::SpaceTutorial => ImageKey::SpaceTutorial,
+    }
+}
+
+fn prompt_text(prompt: InputPrompt, keybindings: &Keybindings) -> String {
+    match prompt {
+        InputPrompt::Lean => format!(
+            "{} and {} to lean",
+            keys_name(&keybindings.lean_left),
+            keys_name(&keybindings.lean_right)
+        ),
+        InputPrompt::Jump => format!("{} to jump", keys_name(&keybindings.jump)),
+        InputPrompt::Respawn => format!("{} to respawn", keys_name(&keybindings.respawn)),
+    }
+}
+
+fn text_scale(config: &GameConfig) -> f32 {
+    config.sign.text_height / TEXT_RESOLUTION
+}
+
+fn on_spawn_sign(
+    trigger: Trigger<SpawnSign>,
+    mut cmd: Commands,
+    config: Res<GameConfig>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    font_handles: Res<HandleMap<FontKey>>,
+    keybindings: Res<Keybindings>,
+) {
+    let SpawnSign(uuid, data) = trigger.event();
+    let size = Vec2::from(data.size);
+    let (texture, color, opacity) = match &data.content {
+        SignContent::Image(image) => (
+            image_handles[&image_key(*image)].clone_weak(),
+            Color::WHITE,
+            1.0,
+        ),
+        SignContent::Text(_) | SignContent::Prompt(_) => (
+            Handle::default(),
+            Color::from(Srgba::from(config.sign.background_color)),
+            config.sign.background_alpha,
+        ),
+    };
+    let text = match &data.content {
+        SignContent::Text(text) => Some((text.clone(), None)),
+        SignContent::Prompt(prompt) => Some((prompt_text(*prompt, &keybindings), Some(*prompt))),
+        SignContent::Image(_) => None,
+    };
+    let scale = text_scale(&config);
+    cmd.spawn((
+        Sign {
+            visibility: 0.0,
+            opacity,
+        },
+        EntityId(*uuid),
+        EntityType::of::<SignData>(),
+        ObjectSize(size),
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(size),
+                color: color.with_alpha(0.0),
+                ..default()
+            },
+            texture,
+            transform: Transform::from_translation(Vec2::from(data.pos).extend(-1.0)),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ))
+    .with_children(|cmd| {
+        if let Some((text, prompt)) = text {
+            cmd.spawn((
+                SignText(prompt),
+                Text2dBundle {
+                    text: Text::from_section(
+                        text,
+                        TextStyle {
+                            font: font_handles[&FontKey::GeoFont].clone_weak(),
+                            font_size: TEXT_RESOLUTION,
+                            color: Color::from(Srgba::from(config.sign.text_color)).with_alpha(0.0),
+                        },
+                    )
+                    .with_justify(JustifyText::Center),
+                    text_2d_bounds: Text2dBounds { size: size / scale },
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1).with_scale(Vec3::splat(scale)),
+                    ..default()
+                },
+            ));
+        }
+    });
+}
+
+fn on_reposition_sign(
+    trigger: Trigger<RepositionRect>,
+    config: Res<GameConfig>,
+    mut q_signs: Query<
+        (
+            &mut Transform,
+            &mut ObjectSize,
+            &mut Sprite,
+            Option<&Children>,
+        ),
+        With<Sign>,
+    >,
+    mut q_bounds: Query<&mut Text2dBounds, With<SignText>>,
+) {
+    if let Ok((mut tr, mut size, mut sprite, children)) = q_signs.get_mut(trigger.entity()) {
+        let rect = trigger.event().rect;
+        size.0 = rect.size();
+        sprite.custom_size = Some(rect.size());
+        tr.translation = rect.center().extend(tr.translation.z);
+        for child in children.into_iter().flatten() {
+            if let Ok(mut bounds) = q_bounds.get_mut(*child) {
+                bounds.size = rect.size() / text_scale(&config);
+            }
+        }
+    }
+}
+
+fn set_visibility(
+    sign: &mut Sign,
+    visibility: f32,
+    sprite: &mut Sprite,
+    children: Option<&Children>,
+    q_texts: &mut Query<&mut Text, With<SignText>>,
+) {
+    sign.visibility = visibility;
+    sprite.color.set_alpha(visibility * sign.opacity);
+    for child in children.into_iter().flatten() {
+        if let Ok(mut text) = q_texts.get_mut(*child) {
+            for section in &mut text.sections {
+                section.style.color.set_alpha(visibility);
+            }
+        }
+    }
+}
+
+fn fade_signs(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    q_torso: Query<&GlobalTransform, With<Torso>>,
+    mut q_signs: Query<(&mut Sign, &GlobalTransform, &mut Sprite, Option<&Children>)>,
+    mut q_texts: Query<&mut Text, With<SignText>>,
+) {
+    let Ok(torso) = q_torso.get_single() else {
+        return;
+    };
+    let torso = torso.translation().truncate();
+    let step = time.delta_seconds() / config.sign.fade_duration.max(f32::EPSILON);
+    for (mut sign, gt, mut sprite, children) in &mut q_signs {
+        let near = torso.distance(gt.translation().truncate()) < config.sign.view_distance;
+        let target = if near { 1.0 } else { 0.0 };
+        if sign.visibility != target {
+            let visibility = sign.visibility + (target - sign.visibility).clamp(-step, step);
+            set_visibility(&mut sign, visibility, &mut sprite, children, &mut q_texts);
+        }
+    }
+}
+
+fn show_prompts(keybindings: Res<Keybindings>, mut q_texts: Query<(&SignText, &mut Text)>) {
+    for (SignText(prompt), mut text) in &mut q_texts {
+        if let Some(prompt) = prompt {
+            text.sections[0].value = prompt_text(*prompt, &keybindings);
+        }
+    }
+}
+
+#[cfg(feature = "dev")]
+fn show_signs(
+    mut q_signs: Query<(&mut Sign, &mut Sprite, Option<&Children>)>,
+    mut q_texts: Query<&mut Text, With<SignText>>,
+) {
+    for (mut sign, mut sprite, children) in &mut q_signs {
+        set_visibility(&mut sign, 1.0, &mut sprite, children, &mut q_texts);
+    }
+}
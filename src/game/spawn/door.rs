@@ -0,0 +1,242 @@
+//! Walls sliding between their closed and open positions, toggled by switches and triggers.
+//! They are kinematic bodies like platforms, so they push the robot out of the way.
+
+#[cfg(feature = "dev")]
+use bevy::render::view::NoFrustumCulling;
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::{HashMap, HashSet},
+};
+use bevy_rapier2d::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    data::{
+        config::{GameConfig, DEFAULT_SURFACE},
+        level::{DoorData, LevelData, TriggerAction},
+    },
+    game::{
+        entity_id::EntityId,
+        entity_type::EntityType,
+        level_object::{
+            translate_rect, LevelObject, ObjectEdited, ObjectKind, ObjectShape, RegisterLevelObject,
+        },
+        object_size::{ObjectSize, RepositionRect},
+        physics::{coll_groups, ObjectGroup},
+        GameState,
+    },
+    screen::Screen,
+    AppSet,
+};
+
+use super::wall::{surface_physics, SurfaceMaterials};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ToggledDoors>()
+        .init_resource::<HeldDoors>()
+        .observe(on_spawn_door)
+        .observe(on_reposition_door)
+        .observe(on_toggle_door)
+        .observe(on_edit_door)
+        .add_systems(
+            Update,
+            move_doors
+                .in_set(AppSet::Update)
+                .run_if(in_state(GameState::Playing)),
+        )
+        // switches are linked to doors from the pointer tool
+        .register_level_object(
+            ObjectKind::<DoorData>::new(ObjectShape::Sized)
+                .resizable()
+                .menu_entry("Door", |point| DoorData {
+                    rect: Rect::from_center_size(point, Vec2::new(1.0, 6.0)).into(),
+                    open_offset: Vec2::new(0.0, 6.0).into(),
+                    open: false,
+                    surface: DEFAULT_SURFACE.to_string(),
+                }),
+        );
+    // the editor moves and resizes doors where they are closed
+    #[cfg(feature = "dev")]
+    app.add_systems(OnEnter(GameState::Editing), close_doors);
+}
+
+/// The doors toggled from the state they start in by levers and triggers during this attempt.
+#[derive(Debug, Resource, Default, Clone, PartialEq, Eq)]
+pub struct ToggledDoors(pub HashSet<Uuid>);
+
+/// The doors held toggled by a pressure plate.
+#[derive(Debug, Resource, Default)]
+pub struct HeldDoors(pub HashSet<Uuid>);
+
+#[derive(Debug, Component)]
+pub struct Door {
+    pub closed: Vec2,
+    pub open_offset: Vec2,
+    pub starts_open: bool,
+}
+
+impl Door {
+    fn target(&self, uuid: Uuid, toggled: &ToggledDoors, held: &HeldDoors) -> Vec2 {
+        let open = self.starts_open ^ toggled.0.contains(&uuid) ^ held.0.contains(&uuid);
+        if open {
+            self.closed + self.open_offset
+        } else {
+            self.closed
+        }
+    }
+}
+
+#[derive(Debug, Event)]
+pub struct SpawnDoor(pub Uuid, pub DoorData);
+
+/// Opens the door if it's closed, closes it otherwise.
+#[derive(Debug, Event)]
+pub struct ToggleDoor(pub Uuid);
+
+impl LevelObject for DoorData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.doors
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.doors
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnDoor(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.rect = translate_rect(self.rect.into(), offset).into();
+    }
+
+    fn resize(&mut self, _from: Rect, to: Rect) {
+        self.rect = to.into();
+    }
+
+    fn surface(&self) -> Option<&str> {
+        Some(&self.surface)
+    }
+
+    fn surface_mut(&mut self) -> Option<&mut String> {
+        Some(&mut self.surface)
+    }
+
+    /// Unlinks the door from the switches and triggers opening it.
+    fn unlink(level: &mut LevelData, uuid: Uuid) {
+        for switch in level.switches.values_mut() {
+            switch.doors.retain(|door| *door != uuid);
+        }
+        let action = TriggerAction::Door(uuid);
+        for trigger in level.triggers.values_mut() {
+            trigger.on_enter.retain(|a| *a != action);
+            trigger.on_exit.retain(|a| *a != action);
+        }
+    }
+}
+
+fn on_spawn_door(
+    trigger: Trigger<SpawnDoor>,
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut surface_materials: SurfaceMaterials,
+    config: Res<GameConfig>,
+    toggled: Res<ToggledDoors>,
+    held: Res<HeldDoors>,
+) {
+    let SpawnDoor(uuid, data) = trigger.event();
+    let rect: Rect = data.rect.into();
+    let door = Door {
+        closed: rect.center(),
+        open_offset: data.open_offset.into(),
+        starts_open: data.open,
+    };
+    let position = door.target(*uuid, &toggled, &held);
+    cmd.spawn((
+        door,
+        EntityType::of::<DoorData>(),
+        ObjectSize(rect.size()),
+        RigidBody::KinematicPositionBased,
+        Collider::cuboid(rect.width() / 2.0, rect.height() / 2.0),
+        surface_physics(config.surface(&data.surface)),
+        StateScoped(Screen::Playing),
+        coll_groups(ObjectGroup::WALL, Group::all().bits()),
+        EntityId(*uuid),
+        MaterialMesh2dBundle {
+            material: surface_materials.get(&data.surface),
+            mesh: meshes
+                .add(Rectangle::new(rect.width(), rect.height()))
+                .into(),
+            transform: Transform::from_translation(position.extend(-1.0)),
+            ..default()
+        },
+        #[cfg(feature = "dev")]
+        NoFrustumCulling,
+    ));
+}
+
+fn on_reposition_door(
+    trigger: Trigger<RepositionRect>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut q_doors: Query<(
+        &mut Door,
+        &mut Transform,
+        &mut ObjectSize,
+        &Mesh2dHandle,
+        &mut Collider,
+    )>,
+) {
+    if let Ok((mut door, mut tr, mut size, mesh_handle, mut collider)) =
+        q_doors.get_mut(trigger.entity())
+    {
+        if let Some(mesh) = meshes.get_mut(mesh_handle.id()) {
+            let rect = trigger.event().rect;
+            size.0 = rect.size();
+            door.closed = rect.center();
+            *mesh = Rectangle::new(rect.width(), rect.height()).into();
+            tr.translation = rect.center().extend(tr.translation.z);
+            *collider = Collider::cuboid(rect.width() / 2.0, rect.height() / 2.0);
+        }
+    }
+}
+
+fn on_edit_door(trigger: Trigger<ObjectEdited<DoorData>>, mut q_doors: Query<&mut Door>) {
+    if let Ok(mut door) = q_doors.get_mut(trigger.entity()) {
+        door.closed = Rect::from(trigger.event().0.rect).center();
+    }
+}
+
+fn on_toggle_door(trigger: Trigger<ToggleDoor>, mut toggled: ResMut<ToggledDoors>) {
+    let ToggleDoor(uuid) = *trigger.event();
+    if !toggled.0.remove(&uuid) {
+        toggled.0.insert(uuid);
+    }
+}
+
+fn move_doors(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    toggled: Res<ToggledDoors>,
+    held: Res<HeldDoors>,
+    mut q_doors: Query<(&EntityId, &Door, &mut Transform)>,
+) {
+    let step = config.door.speed * time.delta_seconds();
+    for (eid, door, mut tr) in &mut q_doors {
+        let position = tr.translation.truncate();
+        let to_target = door.target(eid.0, &toggled, &held) - position;
+        let position = if to_target.length() <= step {
+            position + to_target
+        } else {
+            position + to_target.normalize() * step
+        };
+        tr.translation = position.extend(tr.translation.z);
+    }
+}
+
+#[cfg(feature = "dev")]
+fn close_doors(mut q_doors: Query<(&Door, &mut Transform)>) {
+    for (door, mut tr) in &mut q_doors {
+        tr.translation = door.closed.extend(tr.translation.z);
+    }
+}
@@ -7,19 +7,14 @@ use bevy::{math::vec2, prelude::*};
 use crate::{
     data::level::LevelData,
     game::{
-        arrow::SpawnArrow,
-        arrow_tutorial::SpawnArrowTutorial,
-        background::SpawnBackground,
-        checkpoint::{CurrentActiveCheckpoint, SpawnCheckpoint},
-        game_time::GameTime,
-        goal::SpawnGoal,
-        space_tutorial::SpawnSpaceTutorial,
+        background::SpawnBackground, bolt::CollectedBolts, checkpoint::CurrentActiveCheckpoint,
+        game_time::GameTime, level_object::LevelObjects, repair_kit::UsedRepairKits,
     },
 };
 
 use super::{
+    door::ToggledDoors,
     player::{LostLimbs, SpawnPlayer},
-    wall::SpawnWall,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -44,10 +39,16 @@ fn on_reset_level(
     mut current_checkpoint: ResMut<CurrentActiveCheckpoint>,
     mut game_time: ResMut<GameTime>,
     mut lost_limbs: ResMut<LostLimbs>,
+    mut used_repair_kits: ResMut<UsedRepairKits>,
+    mut collected_bolts: ResMut<CollectedBolts>,
+    mut toggled_doors: ResMut<ToggledDoors>,
 ) {
     current_checkpoint.0 = None;
     game_time.0 = Duration::ZERO;
-    lost_limbs.reset()
+    lost_limbs.reset();
+    used_repair_kits.0.clear();
+    collected_bolts.0.clear();
+    toggled_doors.0.clear();
 }
 
 fn spawn_level(trigger: Trigger<SpawnLevel>, mut cmd: Commands) {
@@ -67,6 +68,7 @@ fn on_do_spawn(
     _trigger: Trigger<DoSpawn>,
     mut cmd: Commands,
     level: Res<LevelData>,
+    objects: Res<LevelObjects>,
     current_checkpoint: Res<CurrentActiveCheckpoint>,
     // mut current_checkpoint: ResMut<CurrentActiveCheckpoint>,
     // mut game_time: ResMut<GameTime>,
@@ -83,29 +85,7 @@ fn on_do_spawn(
         level.player_spawn.into()
     };
     cmd.trigger(SpawnPlayer(location));
-    for (uuid, wall) in &level.walls {
-        cmd.trigger(SpawnWall(*uuid, *wall));
-    }
-    for (uuid, checkpoint) in &level.checkpoints {
-        cmd.trigger(SpawnCheckpoint {
-            uuid: *uuid,
-            data: *checkpoint,
-        });
-    }
-    for (uuid, goal) in &level.goals {
-        cmd.trigger(SpawnGoal(*uuid, goal.pos.into()));
-    }
-    for (uuid, space_tutorial) in &level.space_tutorials {
-        cmd.trigger(SpawnSpaceTutorial(*uuid, space_tutorial.pos.into()));
-    }
-    for (uuid, arrow_tutorial) in &level.arrow_tutorials {
-        cmd.trigger(SpawnArrowTutorial(*uuid, arrow_tutorial.pos.into()));
-    }
-    for (uuid, arrow) in &level.arrows {
-        cmd.trigger(SpawnArrow {
-            uuid: *uuid,
-            angle: arrow.angle,
-            pos: arrow.pos.into(),
-        });
+    for kind in objects.kinds() {
+        kind.spawn_all(&mut cmd, &level);
     }
 }
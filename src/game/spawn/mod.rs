@@ -4,10 +4,20 @@
 
 use bevy::prelude::*;
 
+pub mod door;
 pub mod level;
+pub mod platform;
 pub mod player;
+pub mod track;
 pub mod wall;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((player::plugin, wall::plugin, level::plugin));
+    app.add_plugins((
+        player::plugin,
+        wall::plugin,
+        track::plugin,
+        platform::plugin,
+        door::plugin,
+        level::plugin,
+    ));
 }
@@ -0,0 +1,172 @@
+//! Walls moving along waypoints. They are kinematic bodies so the wheel friction carries the
+//! robot along, and their position only depends on [`GameTime`] so they pause with the game.
+
+#[cfg(feature = "dev")]
+use bevy::render::view::NoFrustumCulling;
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashMap,
+};
+use bevy_rapier2d::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    data::{
+        config::{GameConfig, DEFAULT_SURFACE},
+        level::{LevelData, PlatformData, PlatformEasing, PlatformMode},
+    },
+    game::{
+        entity_id::EntityId,
+        entity_type::EntityType,
+        game_time::GameTime,
+        level_object::{LevelObject, ObjectEdited, ObjectKind, ObjectShape, RegisterLevelObject},
+        object_size::{ObjectSize, RepositionRect},
+        physics::{coll_groups, ObjectGroup},
+        GameState,
+    },
+    platform,
+    screen::Screen,
+    AppSet,
+};
+
+use super::wall::{surface_physics, SurfaceMaterials};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_spawn_platform)
+        .observe(on_reposition_platform)
+        .observe(on_edit_platform)
+        .add_systems(
+            Update,
+            move_platforms
+                .in_set(AppSet::Update)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .register_level_object(
+            ObjectKind::<PlatformData>::new(ObjectShape::Sized)
+                .resizable()
+                .menu_entry("Platform", |point| PlatformData {
+                    size: Vec2::new(5.0, 1.0).into(),
+                    waypoints: vec![point.into(), (point + Vec2::new(10.0, 0.0)).into()],
+                    speed: 3.0,
+                    easing: PlatformEasing::InOut,
+                    mode: PlatformMode::PingPong,
+                    surface: DEFAULT_SURFACE.to_string(),
+                }),
+        );
+}
+
+/// The platform data, kept up to date by the editor.
+#[derive(Debug, Component)]
+pub struct Platform(pub PlatformData);
+
+#[derive(Debug, Event)]
+pub struct SpawnPlatform(pub Uuid, pub PlatformData);
+
+impl LevelObject for PlatformData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.platforms
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.platforms
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnPlatform(uuid, data));
+    }
+
+    /// The waypoints follow the platform, wherever it was on its path.
+    fn translate(&mut self, offset: Vec2) {
+        for waypoint in &mut self.waypoints {
+            *waypoint = (Vec2::from(*waypoint) + offset).into();
+        }
+    }
+
+    fn resize(&mut self, from: Rect, to: Rect) {
+        self.size = to.size().into();
+        self.translate(to.center() - from.center());
+    }
+
+    fn surface(&self) -> Option<&str> {
+        Some(&self.surface)
+    }
+
+    fn surface_mut(&mut self) -> Option<&mut String> {
+        Some(&mut self.surface)
+    }
+}
+
+fn on_spawn_platform(
+    trigger: Trigger<SpawnPlatform>,
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut surface_materials: SurfaceMaterials,
+    config: Res<GameConfig>,
+    game_time: Res<GameTime>,
+) {
+    let SpawnPlatform(uuid, data) = trigger.event();
+    let size = Vec2::from(data.size);
+    let position = platform::position(data, game_time.0.as_secs_f32());
+    cmd.spawn((
+        Platform(data.clone()),
+        EntityType::of::<PlatformData>(),
+        ObjectSize(size),
+        RigidBody::KinematicPositionBased,
+        Collider::cuboid(size.x / 2.0, size.y / 2.0),
+        surface_physics(config.surface(&data.surface)),
+        StateScoped(Screen::Playing),
+        coll_groups(ObjectGroup::WALL, Group::all().bits()),
+        EntityId(*uuid),
+        MaterialMesh2dBundle {
+            material: surface_materials.get(&data.surface),
+            mesh: meshes.add(Rectangle::new(size.x, size.y)).into(),
+            transform: Transform::from_translation(position.extend(-1.0)),
+            ..default()
+        },
+        #[cfg(feature = "dev")]
+        NoFrustumCulling,
+    ));
+}
+
+/// Only resizes the platform, the editor moves its waypoints along with the center.
+fn on_reposition_platform(
+    trigger: Trigger<RepositionRect>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut q_platforms: Query<(
+        &mut Platform,
+        &mut Transform,
+        &mut ObjectSize,
+        &Mesh2dHandle,
+        &mut Collider,
+    )>,
+) {
+    if let Ok((mut platform, mut tr, mut size, mesh_handle, mut collider)) =
+        q_platforms.get_mut(trigger.entity())
+    {
+        if let Some(mesh) = meshes.get_mut(mesh_handle.id()) {
+            let rect = trigger.event().rect;
+            size.0 = rect.size();
+            platform.0.size = rect.size().into();
+            *mesh = Rectangle::new(rect.width(), rect.height()).into();
+            tr.translation = rect.center().extend(tr.translation.z);
+            *collider = Collider::cuboid(rect.width() / 2.0, rect.height() / 2.0);
+        }
+    }
+}
+
+fn on_edit_platform(
+    trigger: Trigger<ObjectEdited<PlatformData>>,
+    mut q_platforms: Query<&mut Platform>,
+) {
+    if let Ok(mut platform) = q_platforms.get_mut(trigger.entity()) {
+        platform.0.clone_from(&trigger.event().0);
+    }
+}
+
+fn move_platforms(game_time: Res<GameTime>, mut q_platforms: Query<(&Platform, &mut Transform)>) {
+    let elapsed = game_time.0.as_secs_f32();
+    for (Platform(data), mut tr) in &mut q_platforms {
+        tr.translation = platform::position(data, elapsed).extend(tr.translation.z);
+    }
+}
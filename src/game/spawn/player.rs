@@ -10,13 +10,19 @@ use bevy::{
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    data::config::GameConfig,
+    data::{config::GameConfig, level::LevelData},
     game::{
         assets::{HandleMap, ImageKey, SfxKey},
         audio::{engine::AccelerateEngine, sfx::PlaySfx},
-        camera::CenterCamera,
+        bolt::CollectedBolts,
+        camera::{CenterCamera, ShakeCamera},
         checkpoint::{Checkpoint, CurrentActiveCheckpoint},
-        physics::{coll_groups, ObjectGroup},
+        expression::Expression,
+        keybindings::Keybindings,
+        particle::{EmitParticles, Emitter},
+        physics::{coll_groups, contact_point, ground_filter, ObjectGroup},
+        repair_kit::UsedRepairKits,
+        spawn::door::ToggledDoors,
         GameState,
     },
     screen::Screen,
@@ -25,10 +31,12 @@ use crate::{
 
 pub(super) fn plugin(app: &mut App) {
     app.insert_resource(LostLimbs::default())
+        .init_resource::<PlayerControls>()
         .observe(on_spawn_player)
         .observe(on_player_death)
         .observe(on_despawn)
         .observe(on_respawn)
+        .observe(on_repair_arm)
         .register_type::<Player>()
         .add_systems(
             Update,
@@ -36,6 +44,12 @@ pub(super) fn plugin(app: &mut App) {
                 .in_set(AppSet::RecordInput)
                 .run_if(in_state(Screen::Playing)),
         )
+        .add_systems(
+            Update,
+            record_player_input
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(GameState::Playing)),
+        )
         .add_systems(
             Update,
             (
@@ -73,6 +87,15 @@ impl LostLimbs {
     }
 }
 
+/// The inputs driving the robot this frame, written from the keyboard or by the bot.
+#[derive(Debug, Resource, Default, PartialEq, Clone, Copy)]
+pub struct PlayerControls {
+    /// -1.0 leans left, 1.0 leans right.
+    pub lean: f32,
+    /// A jump was requested this frame.
+    pub jump: bool,
+}
+
 #[derive(Event, Debug)]
 pub struct SpawnPlayer(pub Vec2);
 
@@ -118,17 +141,20 @@ pub enum Arm {
 #[reflect(Component)]
 pub struct Eyes;
 
+/// An arm still attached to the torso.
 #[derive(Debug, Component)]
 #[component(storage = "SparseSet")]
-struct LiveArm;
+pub struct LiveArm;
 
+/// The torso of a robot that isn't dead yet.
 #[derive(Debug, Component)]
 #[component(storage = "SparseSet")]
-struct LiveTorso;
+pub struct LiveTorso;
 
+/// The robot has its wheel on the ground, this collider.
 #[derive(Debug, Component)]
 #[component(storage = "SparseSet")]
-struct PlayerOnGround;
+pub struct PlayerOnGround(pub Entity);
 
 #[derive(Debug, Event)]
 pub struct PlayerDeath;
@@ -136,6 +162,10 @@ pub struct PlayerDeath;
 #[derive(Debug, Event)]
 pub struct Despawn;
 
+/// Gives back a lost arm, if any.
+#[derive(Debug, Event)]
+pub struct RepairArm;
+
 fn on_player_death(
     _trigger: Trigger<PlayerDeath>,
     mut cmd: Commands,
@@ -145,8 +175,10 @@ fn on_player_death(
     q_wheel: Query<Entity, With<Wheel>>,
     q_tube: Query<Entity, With<Tube>>,
     mut next_state: ResMut<NextState<GameState>>,
+    config: Res<GameConfig>,
 ) {
     warn!("process death");
+    cmd.trigger(ShakeCamera(config.camera.death_trauma));
     for entity in &q_arm {
         cmd.entity(entity)
             .remove::<Arm>()
@@ -174,40 +206,68 @@ fn on_player_death(
     next_state.set(GameState::Death);
 }
 
+/// Detaches a live arm, it stays lost when respawning at a checkpoint reached without it.
+pub fn sever_arm(cmd: &mut Commands, entity: Entity, arm: Arm, lost_limbs: &mut LostLimbs) {
+    cmd.entity(entity)
+        .remove::<LiveArm>()
+        .remove::<ImpulseJoint>();
+    cmd.trigger(EmitParticles::On(Emitter::Debris, entity));
+    match arm {
+        Arm::Left => lost_limbs.left = true,
+        Arm::Right => lost_limbs.right = true,
+    }
+}
+
+pub fn kill_torso(cmd: &mut Commands, torso: Entity) {
+    cmd.entity(torso).remove::<LiveTorso>();
+    cmd.trigger(EmitParticles::On(Emitter::Debris, torso));
+    cmd.trigger(PlayerDeath);
+}
+
 fn monitor_damage_contacts(
     mut cmd: Commands,
     mut contact_force_events: EventReader<ContactForceEvent>,
-    q_arm: Query<(Entity, &Arm), With<LiveArm>>,
-    q_torso: Query<Entity, With<LiveTorso>>,
+    q_arm: Query<(Entity, &Arm, &GlobalTransform), With<LiveArm>>,
+    q_torso: Query<(Entity, &GlobalTransform), With<LiveTorso>>,
     config: Res<GameConfig>,
     mut lost_limbs: ResMut<LostLimbs>,
+    rapier_context: Res<RapierContext>,
 ) {
-    for event in contact_force_events.read() {
-        if let Ok((arm_entity, arm)) = q_arm.get(event.collider1).or(q_arm.get(event.collider2)) {
-            if event.max_force_magnitude > 1.0 {
-                cmd.trigger(PlaySfx::Key(SfxKey::Clonk));
+    let clonk = |cmd: &mut Commands, gt: &GlobalTransform, event: &ContactForceEvent| {
+        let force = event.max_force_magnitude;
+        if force > 1.0 {
+            let intensity = force / config.audio.impact_force;
+            // the part is close enough when the solver has no contact left
+            let position = contact_point(&rapier_context, event.collider1, event.collider2)
+                .unwrap_or(gt.translation().xy());
+            cmd.trigger(PlaySfx::At(SfxKey::Clonk, position, intensity));
+            if force > config.camera.min_shake_force {
+                cmd.trigger(ShakeCamera(force / config.camera.impact_trauma_force));
             }
+            cmd.trigger(EmitParticles::At {
+                emitter: Emitter::Sparks,
+                position,
+                direction: -event.max_force_direction,
+                strength: intensity,
+            });
+        }
+    };
+    for event in contact_force_events.read() {
+        if let Ok((arm_entity, arm, gt)) = q_arm.get(event.collider1).or(q_arm.get(event.collider2))
+        {
+            clonk(&mut cmd, gt, event);
             if event.max_force_magnitude > config.arms.detach_force {
-                cmd.entity(arm_entity)
-                    .remove::<LiveArm>()
-                    .remove::<ImpulseJoint>();
-                match arm {
-                    Arm::Left => lost_limbs.left = true,
-                    Arm::Right => lost_limbs.right = true,
-                }
+                sever_arm(&mut cmd, arm_entity, *arm, &mut lost_limbs);
             }
             continue;
         }
-        if let Ok(torso) = q_torso
+        if let Ok((torso, gt)) = q_torso
             .get(event.collider1)
             .or(q_torso.get(event.collider2))
         {
-            if event.max_force_magnitude > 1.0 {
-                cmd.trigger(PlaySfx::Key(SfxKey::Clonk));
-            }
+            clonk(&mut cmd, gt, event);
             if event.max_force_magnitude > config.torso.death_force {
-                cmd.entity(torso).remove::<LiveTorso>();
-                cmd.trigger(PlayerDeath);
+                kill_torso(&mut cmd, torso);
             }
             continue;
         }
@@ -305,6 +365,7 @@ fn on_spawn_player(
             .with_children(|cmd| {
                 cmd.spawn((
                     Head,
+                    Expression::default(),
                     SpriteBundle {
                         transform: Transform::from_xyz(config.head.x, config.head.y, 0.4),
                         texture: image_handles[&ImageKey::Head].clone_weak(),
@@ -315,25 +376,25 @@ fn on_spawn_player(
                         ..default()
                     },
                     StateScoped(Screen::Playing),
-                ));
-                // .with_children(|cmd| {
-                //     cmd.spawn((
-                //         Eyes,
-                //         SpriteBundle {
-                //             sprite: Sprite {
-                //                 custom_size: Some(vec2(config.eyes.width, config.eyes.height)),
-                //                 ..default()
-                //             },
-                //             transform: Transform::from_translation(vec3(
-                //                 config.eyes.x,
-                //                 config.eyes.y,
-                //                 0.5,
-                //             )),
-                //             texture: image_handles[&ImageKey::Eyes].clone_weak(),
-                //             ..default()
-                //         },
-                //     ));
-                // });
+                ))
+                .with_children(|cmd| {
+                    cmd.spawn((
+                        Eyes,
+                        SpriteBundle {
+                            sprite: Sprite {
+                                custom_size: Some(vec2(config.eyes.width, config.eyes.height)),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(vec3(
+                                config.eyes.x,
+                                config.eyes.y,
+                                0.5,
+                            )),
+                            texture: image_handles[&ImageKey::Eyes].clone_weak(),
+                            ..default()
+                        },
+                    ));
+                });
             })
             .id();
         let mut arms = vec![];
@@ -344,44 +405,114 @@ fn on_spawn_player(
             arms.push((config.arms.right, Arm::Right));
         }
         for (arm_config, arm) in &arms {
-            let socket_arm_joint = RevoluteJointBuilder::new()
-                .local_anchor1(vec2(arm_config.socket.point.x, arm_config.socket.point.y))
-                .local_anchor2(vec2(-config.arms.length / 2.0, 0.0));
             let arm_center = vec3(arm_config.socket.point.x, arm_config.socket.point.y, 1.0);
             // let arm_center = vec3(
             //     config.arms.length / 2.0 + body_translation.x,
             //     body_translation.y,
             //     1.0,
             // );
-            cmd.spawn((
+            cmd.spawn(arm_bundle(
                 *arm,
-                LiveArm,
-                RigidBody::Dynamic,
-                Collider::cuboid(config.arms.length / 2.0, config.arms.width / 2.0),
-                ImpulseJoint::new(body, socket_arm_joint),
-                Damping {
-                    angular_damping: config.arms.angular_damping,
-                    linear_damping: 0.0,
-                },
-                coll_groups(ObjectGroup::PLAYER, ObjectGroup::WALL),
-                SpriteBundle {
-                    transform: Transform::from_translation(arm_center)
-                        .with_rotation(Quat::from_rotation_z(-PI / 2.0)),
-                    texture: image_handles[&ImageKey::Arm].clone_weak(),
-                    sprite: Sprite {
-                        custom_size: Some(vec2(config.arms.length, config.arms.width)),
-                        ..default()
-                    },
-                    ..default()
-                },
-                ColliderMassProperties::Mass(config.arms.mass),
-                ActiveEvents::CONTACT_FORCE_EVENTS,
-                StateScoped(Screen::Playing),
+                body,
+                Transform::from_translation(arm_center)
+                    .with_rotation(Quat::from_rotation_z(-PI / 2.0)),
+                &config,
+                &image_handles,
             ));
         }
     });
 }
 
+/// An arm jointed to `torso`, the joint pulls it in place if `transform` is off.
+fn arm_bundle(
+    arm: Arm,
+    torso: Entity,
+    transform: Transform,
+    config: &GameConfig,
+    image_handles: &HandleMap<ImageKey>,
+) -> impl Bundle {
+    let arm_config = match arm {
+        Arm::Left => config.arms.left,
+        Arm::Right => config.arms.right,
+    };
+    let socket_arm_joint = RevoluteJointBuilder::new()
+        .local_anchor1(vec2(arm_config.socket.point.x, arm_config.socket.point.y))
+        .local_anchor2(vec2(-config.arms.length / 2.0, 0.0));
+    (
+        arm,
+        LiveArm,
+        RigidBody::Dynamic,
+        Collider::cuboid(config.arms.length / 2.0, config.arms.width / 2.0),
+        ImpulseJoint::new(torso, socket_arm_joint),
+        Damping {
+            angular_damping: config.arms.angular_damping,
+            linear_damping: 0.0,
+        },
+        coll_groups(ObjectGroup::PLAYER, ObjectGroup::WALL),
+        SpriteBundle {
+            transform,
+            texture: image_handles[&ImageKey::Arm].clone_weak(),
+            sprite: Sprite {
+                custom_size: Some(vec2(config.arms.length, config.arms.width)),
+                ..default()
+            },
+            ..default()
+        },
+        ColliderMassProperties::Mass(config.arms.mass),
+        ActiveEvents::CONTACT_FORCE_EVENTS,
+        StateScoped(Screen::Playing),
+    )
+}
+
+/// Reattaches a lost arm to the live torso, the left one first.
+fn on_repair_arm(
+    _trigger: Trigger<RepairArm>,
+    mut cmd: Commands,
+    config: Res<GameConfig>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    mut lost_limbs: ResMut<LostLimbs>,
+    q_player: Query<(Entity, &GlobalTransform), With<Player>>,
+    q_torso: Query<(Entity, &GlobalTransform), With<LiveTorso>>,
+) {
+    let arm = if lost_limbs.left {
+        Arm::Left
+    } else if lost_limbs.right {
+        Arm::Right
+    } else {
+        return;
+    };
+    let (Ok((player, player_gt)), Ok((torso, torso_gt))) =
+        (q_player.get_single(), q_torso.get_single())
+    else {
+        return;
+    };
+    let socket: Vec2 = match arm {
+        Arm::Left => config.arms.left.socket.point.into(),
+        Arm::Right => config.arms.right.socket.point.into(),
+    };
+    // hanging down from the socket, the way it's spawned with the robot
+    let torso_tr = torso_gt.compute_transform();
+    let rotation = torso_tr.rotation * Quat::from_rotation_z(-PI / 2.0);
+    let center = torso_tr.transform_point(socket.extend(0.0))
+        + rotation * vec3(config.arms.length / 2.0, 0.0, 0.0);
+    let world = GlobalTransform::from(
+        Transform::from_translation(center.xy().extend(1.0)).with_rotation(rotation),
+    );
+    cmd.entity(player).with_children(|cmd| {
+        cmd.spawn(arm_bundle(
+            arm,
+            torso,
+            world.reparented_to(player_gt),
+            &config,
+            &image_handles,
+        ));
+    });
+    match arm {
+        Arm::Left => lost_limbs.left = false,
+        Arm::Right => lost_limbs.right = false,
+    }
+}
+
 fn on_despawn(
     _trigger: Trigger<Despawn>,
     mut cmd: Commands,
@@ -398,24 +529,59 @@ fn on_respawn(
     current_active_checkpoint: Res<CurrentActiveCheckpoint>,
     q_checkpoint: Query<&Transform, With<Checkpoint>>,
     mut lost_limbs: ResMut<LostLimbs>,
+    mut used_repair_kits: ResMut<UsedRepairKits>,
+    mut collected_bolts: ResMut<CollectedBolts>,
+    mut toggled_doors: ResMut<ToggledDoors>,
+    level: Res<LevelData>,
 ) {
     cmd.trigger(Despawn);
     if let Some(ref active_checkpoint) = current_active_checkpoint.0 {
         if let Ok(tr) = q_checkpoint.get(active_checkpoint.entity) {
             *lost_limbs = active_checkpoint.lost_limbs;
+            *used_repair_kits = active_checkpoint.used_repair_kits.clone();
+            *collected_bolts = active_checkpoint.collected_bolts.clone();
+            *toggled_doors = active_checkpoint.toggled_doors.clone();
             cmd.trigger(SpawnPlayer(tr.translation.xy() + vec2(0.0, 1.0)));
+            return;
         }
     }
+    lost_limbs.reset();
+    used_repair_kits.0.clear();
+    collected_bolts.0.clear();
+    toggled_doors.0.clear();
+    cmd.trigger(SpawnPlayer(level.player_spawn.into()));
 }
 
-fn check_respawn(input: Res<ButtonInput<KeyCode>>, mut cmd: Commands) {
-    if input.just_pressed(KeyCode::KeyR) {
+fn check_respawn(
+    input: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut cmd: Commands,
+) {
+    if input.any_just_pressed(keybindings.respawn.iter().copied()) {
         cmd.trigger(Respawn);
     }
 }
 
-fn calc_forces(
+pub fn record_player_input(
     input: Res<ButtonInput<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    mut controls: ResMut<PlayerControls>,
+) {
+    let mut lean = 0.0;
+    if input.any_pressed(keybindings.lean_left.iter().copied()) {
+        lean -= 1.0;
+    }
+    if input.any_pressed(keybindings.lean_right.iter().copied()) {
+        lean += 1.0;
+    }
+    *controls = PlayerControls {
+        lean,
+        jump: input.any_just_pressed(keybindings.jump.iter().copied()),
+    };
+}
+
+fn calc_forces(
+    controls: Res<PlayerControls>,
     mut cmd: Commands,
     mut q_wheel: Query<(Entity, &mut Velocity), (With<Wheel>, Without<Tube>, Without<Torso>)>,
     mut q_tube: Query<(Entity, &mut Velocity), (With<Tube>, Without<Wheel>, Without<Torso>)>,
@@ -424,15 +590,9 @@ fn calc_forces(
     config: Res<GameConfig>,
 ) {
     // torque
-    let mut torque_direction = 0.0;
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
-        torque_direction -= -1.0;
-    }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
-        torque_direction += -1.0;
-    }
+    let torque_direction = -controls.lean;
     let mut jump = 0.0;
-    if input.just_pressed(KeyCode::Space) && !q_player_on_ground.is_empty() {
+    if controls.jump && !q_player_on_ground.is_empty() {
         jump = 1.0;
         cmd.trigger(PlaySfx::Key(SfxKey::Jump));
     }
@@ -472,9 +632,16 @@ fn calc_forces(
     }
 }
 
-fn center_camera(mut cmd: Commands, q_wheel: Query<&GlobalTransform, With<Wheel>>) {
-    for wheel_gt in &q_wheel {
-        cmd.trigger(CenterCamera(wheel_gt.translation().truncate()));
+/// Centers the camera ahead of the wheel, to see what's coming at speed.
+fn center_camera(
+    mut cmd: Commands,
+    q_wheel: Query<(&GlobalTransform, &Velocity), With<Wheel>>,
+    config: Res<GameConfig>,
+) {
+    for (wheel_gt, velocity) in &q_wheel {
+        let look_ahead = (velocity.linvel * config.camera.look_ahead)
+            .clamp_length_max(config.camera.max_look_ahead);
+        cmd.trigger(CenterCamera(wheel_gt.translation().truncate() + look_ahead));
     }
 }
 
@@ -493,12 +660,11 @@ fn check_touch_ground(
     for wheel_gp in &q_wheel {
         let shape = Collider::ball(1.05);
         let shape_pos = wheel_gp.translation().truncate();
-        let filter = QueryFilter::only_fixed();
-        if let Some(_entity) =
-            rapier_context.intersection_with_shape(shape_pos, 0.0, &shape, filter)
+        let filter = ground_filter();
+        if let Some(ground) = rapier_context.intersection_with_shape(shape_pos, 0.0, &shape, filter)
         {
             for player in &q_player {
-                cmd.entity(player).insert(PlayerOnGround);
+                cmd.entity(player).insert(PlayerOnGround(ground));
             }
         } else {
             for player in &q_player {
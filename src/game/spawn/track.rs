@@ -0,0 +1,233 @@
+//! Curved track pieces. Loops are split in two halves and only one of them is solid at a
+//! time, see [`next_loop_half`].
+
+#[cfg(feature = "dev")]
+use bevy::render::view::NoFrustumCulling;
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashMap,
+};
+use bevy_rapier2d::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    data::{
+        config::{GameConfig, DEFAULT_SURFACE},
+        level::{LevelData, TrackData},
+    },
+    game::{
+        entity_id::EntityId,
+        entity_type::EntityType,
+        level_object::{LevelObject, ObjectEdited, ObjectKind, ObjectShape, RegisterLevelObject},
+        physics::{coll_groups, ObjectGroup},
+        GameState,
+    },
+    screen::Screen,
+    track::{default_loop, loop_half_range, next_loop_half, outline, outline_triangles, LoopHalf},
+    AppSet,
+};
+
+use super::{
+    player::Wheel,
+    wall::{polygon_mesh, surface_physics, SurfaceMaterials},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_spawn_track)
+        .observe(on_reshape_track)
+        .observe(on_edit_track)
+        .add_systems(
+            Update,
+            switch_loop_halves
+                .in_set(AppSet::Update)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .register_level_object(
+            ObjectKind::<TrackData>::new(ObjectShape::Track)
+                .rotatable()
+                .menu_entry("Track", |point| TrackData {
+                    center: point.into(),
+                    radius: 5.0,
+                    start_angle: -FRAC_PI_2,
+                    end_angle: 0.0,
+                    thickness: 1.0,
+                })
+                .menu_entry("Loop", |point| default_loop(point, 8.0)),
+        );
+}
+
+/// The track data with the center the entity was spawned at, the entity is moved instead.
+#[derive(Debug, Component)]
+pub struct Track(pub TrackData);
+
+/// The solid half of a loop.
+#[derive(Debug, Component)]
+struct ActiveLoopHalf(LoopHalf);
+
+#[derive(Debug, Event)]
+pub struct SpawnTrack(pub Uuid, pub TrackData);
+
+/// Rebuilds the targeted track with new radius and angles, used by the editor.
+#[derive(Debug, Event)]
+pub struct ReshapeTrack(pub TrackData);
+
+impl LevelObject for TrackData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.tracks
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.tracks
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnTrack(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.center = (Vec2::from(self.center) + offset).into();
+    }
+
+    /// Tracks are rebuilt with new angles rather than rotated.
+    fn rotate(&mut self, delta: f32) {
+        self.start_angle += delta;
+        self.end_angle += delta;
+    }
+}
+
+fn on_spawn_track(
+    trigger: Trigger<SpawnTrack>,
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut surface_materials: SurfaceMaterials,
+    config: Res<GameConfig>,
+) {
+    let SpawnTrack(uuid, data) = trigger.event();
+    let material = surface_materials.get(DEFAULT_SURFACE);
+    let physics = surface_physics(config.surface(DEFAULT_SURFACE));
+    let mut entity = cmd.spawn((
+        Track(*data),
+        EntityType::of::<TrackData>(),
+        RigidBody::Fixed,
+        StateScoped(Screen::Playing),
+        EntityId(*uuid),
+        MaterialMesh2dBundle {
+            material,
+            mesh: meshes.add(track_mesh(data)).into(),
+            transform: Transform::from_translation(Vec2::from(data.center).extend(-1.0)),
+            ..default()
+        },
+        #[cfg(feature = "dev")]
+        NoFrustumCulling,
+    ));
+    if data.is_loop() {
+        entity.insert(ActiveLoopHalf(LoopHalf::Entry));
+    }
+    entity.with_children(|cmd| spawn_colliders(cmd, data, physics));
+}
+
+fn on_reshape_track(
+    trigger: Trigger<ReshapeTrack>,
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut q_tracks: Query<(&mut Track, &Mesh2dHandle)>,
+    config: Res<GameConfig>,
+) {
+    let data = trigger.event().0;
+    if let Ok((mut track, mesh_handle)) = q_tracks.get_mut(trigger.entity()) {
+        track.0 = data;
+        if let Some(mesh) = meshes.get_mut(mesh_handle.id()) {
+            *mesh = track_mesh(&data);
+        }
+        let mut entity = cmd.entity(trigger.entity());
+        entity.despawn_descendants();
+        if data.is_loop() {
+            entity.insert(ActiveLoopHalf(LoopHalf::Entry));
+        } else {
+            entity.remove::<ActiveLoopHalf>();
+        }
+        let physics = surface_physics(config.surface(DEFAULT_SURFACE));
+        entity.with_children(|cmd| spawn_colliders(cmd, &data, physics));
+    }
+}
+
+fn on_edit_track(
+    trigger: Trigger<ObjectEdited<TrackData>>,
+    mut cmd: Commands,
+    q_tracks: Query<&Track>,
+) {
+    let data = trigger.event().0;
+    if let Ok(Track(track)) = q_tracks.get(trigger.entity()) {
+        if track.start_angle != data.start_angle || track.end_angle != data.end_angle {
+            cmd.trigger_targets(ReshapeTrack(data), trigger.entity());
+        }
+    }
+}
+
+fn track_mesh(data: &TrackData) -> Mesh {
+    let points = outline(data, 0.0, 1.0);
+    polygon_mesh(&points, &outline_triangles(data, points.len()))
+}
+
+/// Tracks have the default surface.
+fn spawn_colliders(cmd: &mut ChildBuilder, data: &TrackData, physics: (Friction, Restitution)) {
+    let collider = |from: f32, to: f32| {
+        let points = outline(data, from, to);
+        let len = points.len() as u32;
+        let indices = (0..len).map(|i| [i, (i + 1) % len]).collect();
+        (
+            Collider::polyline(points, Some(indices)),
+            physics,
+            TransformBundle::default(),
+        )
+    };
+    if data.is_loop() {
+        for half in [LoopHalf::Entry, LoopHalf::Exit] {
+            let (from, to) = loop_half_range(half);
+            cmd.spawn((
+                collider(from, to),
+                half,
+                loop_half_groups(half, LoopHalf::Entry),
+            ));
+        }
+    } else {
+        cmd.spawn((
+            collider(0.0, 1.0),
+            coll_groups(ObjectGroup::WALL, Group::all().bits()),
+        ));
+    }
+}
+
+fn loop_half_groups(half: LoopHalf, active: LoopHalf) -> CollisionGroups {
+    if half == active {
+        coll_groups(ObjectGroup::WALL, Group::all().bits())
+    } else {
+        coll_groups(ObjectGroup::WALL, 0)
+    }
+}
+
+fn switch_loop_halves(
+    q_wheel: Query<&GlobalTransform, With<Wheel>>,
+    mut q_loops: Query<(&Track, &GlobalTransform, &mut ActiveLoopHalf, &Children)>,
+    mut q_halves: Query<(&LoopHalf, &mut CollisionGroups)>,
+) {
+    let Ok(wheel) = q_wheel.get_single() else {
+        return;
+    };
+    for (track, gt, mut active, children) in &mut q_loops {
+        let local = wheel.translation().truncate() - gt.translation().truncate();
+        let next = next_loop_half(&track.0, active.0, local);
+        if next == active.0 {
+            continue;
+        }
+        active.0 = next;
+        for child in children {
+            if let Ok((half, mut groups)) = q_halves.get_mut(*child) {
+                *groups = loop_half_groups(*half, next);
+            }
+        }
+    }
+}
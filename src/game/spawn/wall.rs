@@ -1,19 +1,34 @@
+use std::f32::consts::TAU;
+
 use crate::{
-    data::{config::GameConfig, level::WallData},
+    data::{
+        config::{GameConfig, SurfaceConfig, DEFAULT_SURFACE},
+        level::{LevelData, PolygonWallData, WallData},
+    },
     game::{
-        assets::{HandleMap, ImageKey},
         entity_id::EntityId,
         entity_type::EntityType,
         fixed_material::FixedMaterial,
+        level_object::{translate_rect, LevelObject, ObjectKind, ObjectShape, RegisterLevelObject},
         object_size::{ObjectSize, RepositionRect},
         physics::{coll_groups, ObjectGroup},
+        spawn::{door::Door, platform::Platform},
     },
+    polygon::triangulate,
     screen::Screen,
 };
 use bevy::{
+    ecs::system::SystemParam,
     math::vec2,
     prelude::*,
+    render::{
+        mesh::Indices,
+        render_asset::RenderAssetUsages,
+        render_resource::PrimitiveTopology,
+        texture::{ImageLoaderSettings, ImageSampler},
+    },
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashMap,
 };
 
 #[cfg(feature = "dev")]
@@ -21,17 +36,166 @@ use bevy::render::view::NoFrustumCulling;
 use bevy_rapier2d::prelude::*;
 use uuid::Uuid;
 pub(super) fn plugin(app: &mut App) {
-    app.observe(on_spawn_wall).observe(on_reposition_wall);
+    app.init_resource::<SurfaceMaterialCache>()
+        .observe(on_spawn_wall)
+        .observe(on_reposition_wall)
+        .observe(on_spawn_polygon_wall)
+        .observe(on_change_surface)
+        .register_level_object(
+            ObjectKind::<WallData>::new(ObjectShape::Sized)
+                .resizable()
+                .rotatable()
+                .menu_entry("Wall", |point| WallData {
+                    rect: Rect::from_center_size(point, Vec2::splat(5.0)).into(),
+                    angle: 0.0,
+                    surface: DEFAULT_SURFACE.to_string(),
+                }),
+        )
+        // polygon walls are drawn with their own tool
+        .register_level_object(
+            ObjectKind::<PolygonWallData>::new(ObjectShape::Polygon).rotatable(),
+        );
 }
 
 #[derive(Debug, Component)]
 pub struct Wall;
 
+/// The polygon points relative to the entity, used by the editor for picking and gizmos.
+#[derive(Debug, Component)]
+pub struct PolygonWall(pub Vec<Vec2>);
+
 #[derive(Debug, Event)]
 pub struct SpawnWall(pub Uuid, pub WallData);
 
-#[derive(Debug, Resource)]
-struct WallMaterialHandle(Handle<FixedMaterial>);
+#[derive(Debug, Event)]
+pub struct SpawnPolygonWall(pub Uuid, pub PolygonWallData);
+
+impl LevelObject for WallData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.walls
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.walls
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnWall(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.rect = translate_rect(self.rect.into(), offset).into();
+    }
+
+    fn resize(&mut self, _from: Rect, to: Rect) {
+        self.rect = to.into();
+    }
+
+    fn rotate(&mut self, delta: f32) {
+        self.angle = (self.angle + delta).rem_euclid(TAU);
+    }
+
+    fn angle(&self) -> Option<f32> {
+        Some(self.angle)
+    }
+
+    fn surface(&self) -> Option<&str> {
+        Some(&self.surface)
+    }
+
+    fn surface_mut(&mut self) -> Option<&mut String> {
+        Some(&mut self.surface)
+    }
+}
+
+impl LevelObject for PolygonWallData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.polygon_walls
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.polygon_walls
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnPolygonWall(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+
+    fn rotate(&mut self, delta: f32) {
+        self.angle = (self.angle + delta).rem_euclid(TAU);
+    }
+
+    fn angle(&self) -> Option<f32> {
+        Some(self.angle)
+    }
+
+    fn surface(&self) -> Option<&str> {
+        Some(&self.surface)
+    }
+
+    fn surface_mut(&mut self) -> Option<&mut String> {
+        Some(&mut self.surface)
+    }
+}
+
+/// Switches the targeted wall, polygon wall, platform or door to the named surface, used by the
+/// editor.
+#[derive(Debug, Event)]
+pub struct ChangeSurface(pub String);
+
+/// One material per surface, shared by all the walls using it.
+#[derive(Debug, Resource, Default)]
+pub(super) struct SurfaceMaterialCache(HashMap<String, Handle<FixedMaterial>>);
+
+#[derive(SystemParam)]
+pub(super) struct SurfaceMaterials<'w> {
+    cache: ResMut<'w, SurfaceMaterialCache>,
+    materials: ResMut<'w, Assets<FixedMaterial>>,
+    asset_server: Res<'w, AssetServer>,
+    config: Res<'w, GameConfig>,
+}
+
+impl SurfaceMaterials<'_> {
+    pub(super) fn get(&mut self, surface: &str) -> Handle<FixedMaterial> {
+        if let Some(handle) = self.cache.0.get(surface) {
+            return handle.clone();
+        }
+        let config = self.config.surface(surface);
+        let texture = self.asset_server.load_with_settings(
+            &config.texture,
+            |settings: &mut ImageLoaderSettings| {
+                settings.sampler = ImageSampler::nearest();
+            },
+        );
+        let handle = self.materials.add(FixedMaterial::new(
+            Color::from(Srgba::from(config.tint)),
+            texture,
+            vec2(self.config.wall.scale_x, self.config.wall.scale_y),
+            vec2(1.0, 1.0),
+        ));
+        self.cache.0.insert(surface.to_string(), handle.clone());
+        handle
+    }
+}
+
+/// Friction is multiplied so slippery and grippy surfaces stay that way against the wheel,
+/// restitution takes the bounciest one since the robot parts have none.
+pub(super) fn surface_physics(surface: &SurfaceConfig) -> (Friction, Restitution) {
+    (
+        Friction {
+            coefficient: surface.friction,
+            combine_rule: CoefficientCombineRule::Multiply,
+        },
+        Restitution {
+            coefficient: surface.restitution,
+            combine_rule: CoefficientCombineRule::Max,
+        },
+    )
+}
 
 fn on_reposition_wall(
     trigger: Trigger<RepositionRect>,
@@ -57,25 +221,39 @@ fn on_reposition_wall(
     }
 }
 
+fn on_change_surface(
+    trigger: Trigger<ChangeSurface>,
+    mut cmd: Commands,
+    mut surface_materials: SurfaceMaterials,
+    q_walls: Query<(), Or<(With<Wall>, With<PolygonWall>, With<Platform>, With<Door>)>>,
+) {
+    if q_walls.contains(trigger.entity()) {
+        let surface = &trigger.event().0;
+        let material = surface_materials.get(surface);
+        let physics = surface_physics(surface_materials.config.surface(surface));
+        cmd.entity(trigger.entity()).insert((material, physics));
+    }
+}
+
 fn on_spawn_wall(
     trigger: Trigger<SpawnWall>,
     mut cmd: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
-    material: Option<Res<WallMaterialHandle>>,
-    materials: ResMut<Assets<FixedMaterial>>,
-    image_handles: Res<HandleMap<ImageKey>>,
+    mut surface_materials: SurfaceMaterials,
     config: Res<GameConfig>,
 ) {
-    let material = ensure_material(cmd.reborrow(), material, materials, image_handles, config);
+    let surface = &trigger.event().1.surface;
+    let material = surface_materials.get(surface);
     let rect: Rect = trigger.event().1.rect.into();
     let translation = rect.center();
+    let rotation = Quat::from_rotation_z(trigger.event().1.angle);
     cmd.spawn((
         Wall,
-        EntityType::Wall,
+        EntityType::of::<WallData>(),
         ObjectSize(rect.size()),
         RigidBody::Fixed,
         Collider::cuboid(rect.width() / 2.0, rect.height() / 2.0),
-        Friction::new(1.0),
+        surface_physics(config.surface(surface)),
         StateScoped(Screen::Playing),
         coll_groups(ObjectGroup::WALL, Group::all().bits()),
         EntityId(trigger.event().0),
@@ -84,7 +262,8 @@ fn on_spawn_wall(
             mesh: meshes
                 .add(Rectangle::new(rect.width(), rect.height()))
                 .into(),
-            transform: Transform::from_translation(translation.extend(-1.0)),
+            transform: Transform::from_translation(translation.extend(-1.0))
+                .with_rotation(rotation),
             ..default()
         },
         // there is some kind of a cache because when i change the size of a wall with the editor,
@@ -94,24 +273,74 @@ fn on_spawn_wall(
     ));
 }
 
-fn ensure_material(
+fn on_spawn_polygon_wall(
+    trigger: Trigger<SpawnPolygonWall>,
     mut cmd: Commands,
-    material: Option<Res<WallMaterialHandle>>,
-    mut materials: ResMut<Assets<FixedMaterial>>,
-    image_handles: Res<HandleMap<ImageKey>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut surface_materials: SurfaceMaterials,
     config: Res<GameConfig>,
-) -> Handle<FixedMaterial> {
-    if let Some(mh) = material {
-        mh.0.clone()
-    } else {
-        let material = FixedMaterial::new(
-            Color::linear_rgba(0.55, 0.6, 0.7, 1.0),
-            image_handles[&ImageKey::Wall].clone_weak(),
-            vec2(config.wall.scale_x, config.wall.scale_y),
-            vec2(1.0, 1.0),
-        );
-        let handle = materials.add(material);
-        cmd.insert_resource(WallMaterialHandle(handle.clone()));
-        handle
-    }
+) {
+    let SpawnPolygonWall(uuid, data) = trigger.event();
+    let points = data.local_points();
+    let Some(triangles) = triangulate(&points) else {
+        warn!("polygon wall {} can't be triangulated, skipping it", uuid);
+        return;
+    };
+    let material = surface_materials.get(&data.surface);
+    cmd.spawn((
+        PolygonWall(points.clone()),
+        EntityType::of::<PolygonWallData>(),
+        RigidBody::Fixed,
+        polygon_collider(&points, &triangles),
+        surface_physics(config.surface(&data.surface)),
+        StateScoped(Screen::Playing),
+        coll_groups(ObjectGroup::WALL, Group::all().bits()),
+        EntityId(*uuid),
+        MaterialMesh2dBundle {
+            material,
+            mesh: meshes.add(polygon_mesh(&points, &triangles)).into(),
+            transform: Transform::from_translation(Vec2::from(data.pos).extend(-1.0))
+                .with_rotation(Quat::from_rotation_z(data.angle)),
+            ..default()
+        },
+        #[cfg(feature = "dev")]
+        NoFrustumCulling,
+    ));
+}
+
+/// A compound of the triangles, so the wall is solid inside even when concave.
+pub fn polygon_collider(points: &[Vec2], triangles: &[[usize; 3]]) -> Collider {
+    Collider::compound(
+        triangles
+            .iter()
+            .map(|[a, b, c]| {
+                (
+                    Vec2::ZERO,
+                    0.0,
+                    Collider::triangle(points[*a], points[*b], points[*c]),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// The texture is sampled in world space by the `FixedMaterial` shader, so only positions matter.
+pub(super) fn polygon_mesh(points: &[Vec2], triangles: &[[usize; 3]]) -> Mesh {
+    let positions: Vec<[f32; 3]> = points.iter().map(|p| [p.x, p.y, 0.0]).collect();
+    let normals = vec![[0.0, 0.0, 1.0]; points.len()];
+    let uvs: Vec<[f32; 2]> = points.iter().map(|p| [p.x, p.y]).collect();
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(
+        triangles
+            .iter()
+            .flatten()
+            .map(|index| *index as u32)
+            .collect(),
+    ))
 }
@@ -0,0 +1,270 @@
+//! Levers and pressure plates, opening and closing their doors. Levers are trigger volumes
+//! toggling their doors, pressure plates hold them toggled while the wheel rests on them.
+
+use bevy::{
+    color::palettes::css::GRAY,
+    prelude::*,
+    sprite::Anchor,
+    utils::{HashMap, HashSet},
+};
+use bevy_rapier2d::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    data::{
+        config::GameConfig,
+        level::{LevelData, SwitchData, SwitchKind, TriggerAction},
+    },
+    screen::Screen,
+    AppSet,
+};
+
+use super::{
+    assets::{HandleMap, ImageKey},
+    entity_id::EntityId,
+    entity_type::EntityType,
+    level_object::{
+        LevelObject, ObjectEdited, ObjectKind, ObjectRemoved, ObjectShape, RegisterLevelObject,
+    },
+    physics::{coll_groups, contact_force, ObjectGroup},
+    spawn::{
+        door::{HeldDoors, ToggledDoors},
+        player::Wheel,
+    },
+    trigger_volume::{trigger_volume_bundle, TriggerVolume},
+    GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_spawn_switch)
+        .observe(on_relink_switch)
+        .observe(on_edit_switch)
+        .observe(on_remove_door)
+        .add_systems(
+            Update,
+            check_pressure_plates
+                .in_set(AppSet::TickTimers)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            show_levers
+                .in_set(AppSet::Update)
+                .run_if(resource_changed::<ToggledDoors>),
+        )
+        .register_level_object(
+            ObjectKind::<SwitchData>::new(ObjectShape::Sprite)
+                .menu_entry("Lever", |point| new_switch(SwitchKind::Lever, point))
+                .menu_entry("Pressure plate", |point| {
+                    new_switch(SwitchKind::PressurePlate, point)
+                }),
+        );
+}
+
+/// The switch data, kept up to date by the editor.
+#[derive(Debug, Component)]
+pub struct Switch(pub SwitchData);
+
+#[derive(Debug, Component)]
+struct PlateCollider;
+
+#[derive(Debug, Event)]
+pub struct SpawnSwitch(pub Uuid, pub SwitchData);
+
+/// Links the targeted switch to these doors instead, used by the editor.
+#[derive(Debug, Event)]
+pub struct RelinkSwitch(pub Vec<Uuid>);
+
+impl LevelObject for SwitchData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.switches
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.switches
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnSwitch(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.pos = (Vec2::from(self.pos) + offset).into();
+    }
+
+    fn remap(&mut self, f: &dyn Fn(Uuid) -> Option<Uuid>) {
+        self.doors = self.doors.iter().filter_map(|door| f(*door)).collect();
+    }
+}
+
+fn new_switch(kind: SwitchKind, point: Vec2) -> SwitchData {
+    SwitchData {
+        kind,
+        pos: point.into(),
+        doors: vec![],
+    }
+}
+
+fn image_key(kind: SwitchKind) -> ImageKey {
+    match kind {
+        SwitchKind::Lever => ImageKey::Lever,
+        SwitchKind::PressurePlate => ImageKey::PressurePlate,
+    }
+}
+
+fn lever_volume(doors: &[Uuid]) -> TriggerVolume {
+    TriggerVolume {
+        on_enter: doors.iter().copied().map(TriggerAction::Door).collect(),
+        on_exit: vec![],
+    }
+}
+
+fn on_spawn_switch(
+    trigger: Trigger<SpawnSwitch>,
+    mut cmd: Commands,
+    config: Res<GameConfig>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    toggled: Res<ToggledDoors>,
+) {
+    let SpawnSwitch(uuid, data) = trigger.event();
+    let switch_config = config.switch(data.kind);
+    let collider_tr = TransformBundle::from_transform(Transform::from_translation(
+        Vec2::from(switch_config.collider.pos).extend(0.0),
+    ));
+    let collider_size = Vec2::from(switch_config.collider.size);
+    cmd.spawn((
+        Switch(data.clone()),
+        EntityId(*uuid),
+        EntityType::of::<SwitchData>(),
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(switch_config.size.into()),
+                anchor: Anchor::BottomCenter,
+                flip_x: lever_flipped(data, &toggled),
+                ..default()
+            },
+            texture: image_handles[&image_key(data.kind)].clone_weak(),
+            transform: Transform::from_translation(Vec2::from(data.pos).extend(-1.0)),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ))
+    .with_children(|cmd| match data.kind {
+        SwitchKind::Lever => {
+            cmd.spawn((
+                collider_tr,
+                trigger_volume_bundle(lever_volume(&data.doors), collider_size),
+            ));
+        }
+        SwitchKind::PressurePlate => {
+            cmd.spawn((
+                PlateCollider,
+                collider_tr,
+                coll_groups(ObjectGroup::TRIGGER, ObjectGroup::PLAYER),
+                Collider::cuboid(collider_size.x / 2.0, collider_size.y / 2.0),
+            ));
+        }
+    });
+}
+
+fn on_relink_switch(
+    trigger: Trigger<RelinkSwitch>,
+    mut q_switches: Query<(&mut Switch, &Children)>,
+    mut q_volumes: Query<&mut TriggerVolume>,
+) {
+    if let Ok((mut switch, children)) = q_switches.get_mut(trigger.entity()) {
+        switch.0.doors.clone_from(&trigger.event().0);
+        for child in children {
+            if let Ok(mut volume) = q_volumes.get_mut(*child) {
+                *volume = lever_volume(&switch.0.doors);
+            }
+        }
+    }
+}
+
+fn on_edit_switch(trigger: Trigger<ObjectEdited<SwitchData>>, mut q_switches: Query<&mut Switch>) {
+    if let Ok(mut switch) = q_switches.get_mut(trigger.entity()) {
+        switch.0.clone_from(&trigger.event().0);
+    }
+}
+
+/// Unlinks a deleted door from the switches opening it.
+fn on_remove_door(
+    trigger: Trigger<ObjectRemoved>,
+    mut cmd: Commands,
+    q_switches: Query<(Entity, &Switch)>,
+) {
+    let uuid = trigger.event().0;
+    for (entity, Switch(data)) in &q_switches {
+        if data.doors.contains(&uuid) {
+            let doors = data
+                .doors
+                .iter()
+                .copied()
+                .filter(|door| *door != uuid)
+                .collect();
+            cmd.trigger_targets(RelinkSwitch(doors), entity);
+        }
+    }
+}
+
+/// A lever leans the other way while any of its doors is toggled.
+fn lever_flipped(data: &SwitchData, toggled: &ToggledDoors) -> bool {
+    data.kind == SwitchKind::Lever && data.doors.iter().any(|door| toggled.0.contains(door))
+}
+
+fn show_levers(toggled: Res<ToggledDoors>, mut q_switches: Query<(&Switch, &mut Sprite)>) {
+    for (Switch(data), mut sprite) in &mut q_switches {
+        sprite.flip_x = lever_flipped(data, &toggled);
+    }
+}
+
+fn check_pressure_plates(
+    rapier_context: Res<RapierContext>,
+    q_wheel: Query<(Entity, &GlobalTransform, &Collider), With<Wheel>>,
+    q_plate_colliders: Query<&Parent, With<PlateCollider>>,
+    mut q_switches: Query<(Entity, &Switch, &mut Sprite)>,
+    mut held: ResMut<HeldDoors>,
+    config: Res<GameConfig>,
+) {
+    let filter =
+        QueryFilter::default().groups(coll_groups(ObjectGroup::PLAYER, ObjectGroup::TRIGGER));
+    let mut pressed = HashSet::new();
+    for (wheel, wheel_tr, wheel_collider) in &q_wheel {
+        // brushing a plate on a jump doesn't press it, the robot has to stand on it
+        if contact_force(&rapier_context, wheel) < config.switches.plate_force {
+            continue;
+        }
+        let wheel_tr = wheel_tr.compute_transform();
+        rapier_context.intersections_with_shape(
+            wheel_tr.translation.xy(),
+            wheel_tr.rotation.to_axis_angle().1,
+            wheel_collider,
+            filter,
+            |entity| {
+                if let Ok(parent) = q_plate_colliders.get(entity) {
+                    pressed.insert(parent.get());
+                }
+                true
+            },
+        );
+    }
+    let mut held_doors = HashSet::new();
+    for (entity, Switch(data), mut sprite) in &mut q_switches {
+        if data.kind != SwitchKind::PressurePlate {
+            continue;
+        }
+        let is_pressed = pressed.contains(&entity);
+        if is_pressed {
+            held_doors.extend(data.doors.iter().copied());
+        }
+        sprite.color = if is_pressed {
+            Color::from(GRAY)
+        } else {
+            Color::WHITE
+        };
+    }
+    if held.0 != held_doors {
+        held.0 = held_doors;
+    }
+}
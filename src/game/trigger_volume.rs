@@ -0,0 +1,237 @@
+//! Areas running [`TriggerAction`]s when the torso enters or leaves them. Goals and checkpoints
+//! are trigger volumes, and levels can place invisible ones with their own actions.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier2d::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    data::level::{LevelData, TriggerAction, TriggerData, TriggerSound},
+    screen::Screen,
+    AppSet,
+};
+
+use super::{
+    assets::{SfxKey, SoundtrackKey},
+    audio::{sfx::PlaySfx, soundtrack::PlaySoundtrack},
+    camera::FocusCamera,
+    checkpoint::ReachCheckpoint,
+    entity_id::EntityId,
+    entity_type::EntityType,
+    level_object::{translate_rect, LevelObject, ObjectKind, ObjectShape, RegisterLevelObject},
+    object_size::{ObjectSize, RepositionRect},
+    physics::{coll_groups, ObjectGroup},
+    spawn::{door::ToggleDoor, player::Torso},
+    ui::ShowMessage,
+    GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(on_spawn_trigger)
+        .observe(on_reposition_trigger)
+        .observe(on_run_trigger_action)
+        .add_systems(
+            Update,
+            check_player_collision
+                .in_set(AppSet::TickTimers)
+                .run_if(in_state(GameState::Playing)),
+        )
+        // actions are written in the level file
+        .register_level_object(
+            ObjectKind::<TriggerData>::new(ObjectShape::Sized)
+                .resizable()
+                .menu_entry("Trigger", |point| TriggerData {
+                    rect: Rect::from_center_size(point, Vec2::splat(4.0)).into(),
+                    on_enter: vec![],
+                    on_exit: vec![],
+                }),
+        );
+}
+
+#[derive(Debug, Component, Clone, Default)]
+pub struct TriggerVolume {
+    pub on_enter: Vec<TriggerAction>,
+    pub on_exit: Vec<TriggerAction>,
+}
+
+impl TriggerVolume {
+    pub fn on_enter(action: TriggerAction) -> Self {
+        Self {
+            on_enter: vec![action],
+            on_exit: vec![],
+        }
+    }
+}
+
+/// Marks the volumes the torso is in.
+#[derive(Debug, Component)]
+struct Occupied;
+
+/// A trigger placed in the level, as opposed to the volumes of goals and checkpoints.
+#[derive(Debug, Component)]
+pub struct LevelTrigger;
+
+#[derive(Debug, Event)]
+pub struct SpawnTrigger(pub Uuid, pub TriggerData);
+
+#[derive(Debug, Event)]
+pub struct RunTriggerAction(pub TriggerAction);
+
+impl LevelObject for TriggerData {
+    fn map(level: &LevelData) -> &HashMap<Uuid, Self> {
+        &level.triggers
+    }
+
+    fn map_mut(level: &mut LevelData) -> &mut HashMap<Uuid, Self> {
+        &mut level.triggers
+    }
+
+    fn spawn(cmd: &mut Commands, uuid: Uuid, data: Self) {
+        cmd.trigger(SpawnTrigger(uuid, data));
+    }
+
+    fn translate(&mut self, offset: Vec2) {
+        self.rect = translate_rect(self.rect.into(), offset).into();
+    }
+
+    fn resize(&mut self, _from: Rect, to: Rect) {
+        self.rect = to.into();
+    }
+
+    fn remap(&mut self, f: &dyn Fn(Uuid) -> Option<Uuid>) {
+        for actions in [&mut self.on_enter, &mut self.on_exit] {
+            actions.retain_mut(|action| match action {
+                TriggerAction::Checkpoint(uuid) | TriggerAction::Door(uuid) => match f(*uuid) {
+                    Some(new) => {
+                        *uuid = new;
+                        true
+                    }
+                    None => false,
+                },
+                _ => true,
+            });
+        }
+    }
+}
+
+/// A trigger volume of `size`, centered on the entity.
+pub fn trigger_volume_bundle(volume: TriggerVolume, size: Vec2) -> impl Bundle {
+    (
+        volume,
+        coll_groups(ObjectGroup::TRIGGER, ObjectGroup::PLAYER),
+        Sensor,
+        Collider::cuboid(size.x / 2.0, size.y / 2.0),
+    )
+}
+
+fn on_spawn_trigger(trigger: Trigger<SpawnTrigger>, mut cmd: Commands) {
+    let SpawnTrigger(uuid, data) = trigger.event();
+    let rect: Rect = data.rect.into();
+    cmd.spawn((
+        LevelTrigger,
+        EntityId(*uuid),
+        EntityType::of::<TriggerData>(),
+        ObjectSize(rect.size()),
+        TransformBundle::from_transform(Transform::from_translation(rect.center().extend(0.0))),
+        trigger_volume_bundle(
+            TriggerVolume {
+                on_enter: data.on_enter.clone(),
+                on_exit: data.on_exit.clone(),
+            },
+            rect.size(),
+        ),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn on_reposition_trigger(
+    trigger: Trigger<RepositionRect>,
+    mut q_triggers: Query<(&mut Transform, &mut ObjectSize, &mut Collider), With<LevelTrigger>>,
+) {
+    if let Ok((mut tr, mut size, mut collider)) = q_triggers.get_mut(trigger.entity()) {
+        let rect = trigger.event().rect;
+        size.0 = rect.size();
+        tr.translation = rect.center().extend(tr.translation.z);
+        *collider = Collider::cuboid(rect.width() / 2.0, rect.height() / 2.0);
+    }
+}
+
+/// Volumes keep their state while there is no torso, dying inside one and respawning inside it
+/// doesn't run its actions again.
+fn check_player_collision(
+    mut cmd: Commands,
+    rapier_context: Res<RapierContext>,
+    q_torso: Query<(&GlobalTransform, &Collider), With<Torso>>,
+    q_volumes: Query<(Entity, &TriggerVolume, Has<Occupied>)>,
+) {
+    let Ok((body_tr, body_collider)) = q_torso.get_single() else {
+        return;
+    };
+    let body_tr = body_tr.compute_transform();
+    let shape_pos = body_tr.translation.xy();
+    let shape_rot = body_tr.rotation.to_axis_angle().1;
+    let filter =
+        QueryFilter::default().groups(coll_groups(ObjectGroup::PLAYER, ObjectGroup::TRIGGER));
+    let mut inside = vec![];
+    rapier_context.intersections_with_shape(
+        shape_pos,
+        shape_rot,
+        body_collider,
+        filter,
+        |entity| {
+            inside.push(entity);
+            true
+        },
+    );
+    for (entity, volume, occupied) in &q_volumes {
+        let actions = match (inside.contains(&entity), occupied) {
+            (true, false) => {
+                cmd.entity(entity).insert(Occupied);
+                &volume.on_enter
+            }
+            (false, true) => {
+                cmd.entity(entity).remove::<Occupied>();
+                &volume.on_exit
+            }
+            _ => continue,
+        };
+        for action in actions {
+            cmd.trigger(RunTriggerAction(action.clone()));
+        }
+    }
+}
+
+fn sfx_key(sound: TriggerSound) -> SfxKey {
+    match sound {
+        TriggerSound::Beep => SfxKey::ButtonPress,
+        TriggerSound::Jump => SfxKey::Jump,
+        TriggerSound::Clonk => SfxKey::Clonk,
+    }
+}
+
+fn on_run_trigger_action(
+    trigger: Trigger<RunTriggerAction>,
+    mut cmd: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let seconds = |seconds: f32| Duration::from_secs_f32(seconds.max(0.0));
+    match &trigger.event().0 {
+        TriggerAction::Message { text, seconds: s } => cmd.trigger(ShowMessage {
+            text: text.clone(),
+            duration: seconds(*s),
+        }),
+        TriggerAction::Sound(sound) => cmd.trigger(PlaySfx::Key(sfx_key(*sound))),
+        TriggerAction::Camera { pos, seconds: s } => cmd.trigger(FocusCamera {
+            pos: (*pos).into(),
+            duration: seconds(*s),
+        }),
+        TriggerAction::Checkpoint(uuid) => cmd.trigger(ReachCheckpoint(EntityId(*uuid))),
+        TriggerAction::Door(uuid) => cmd.trigger(ToggleDoor(*uuid)),
+        TriggerAction::Victory => next_state.set(GameState::Victory),
+        TriggerAction::Music(name) => {
+            cmd.trigger(PlaySoundtrack::Key(SoundtrackKey(name.clone())));
+        }
+    }
+}
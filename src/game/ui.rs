@@ -4,17 +4,25 @@ use bevy::{color::palettes::css::WHITE_SMOKE, prelude::*, window::PrimaryWindow}
 
 use super::{
     assets::{FontKey, HandleMap},
+    bolt::CollectedBolts,
     game_time::GameTime,
     GameState,
 };
-use crate::{data::config::GameConfig, ui::prelude::*, AppSet};
+use crate::{
+    data::{config::GameConfig, level::LevelData},
+    screen::Screen,
+    ui::prelude::*,
+    AppSet,
+};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::Playing), spawn_ui)
+    app.observe(on_show_message)
+        .add_systems(OnEnter(GameState::Playing), spawn_ui)
         .add_systems(
             Update,
             (
-                (adjust_font_size, update_game_time).in_set(AppSet::Update),
+                (adjust_font_size, update_game_time, update_bolt_count).in_set(AppSet::Update),
+                expire_messages.in_set(AppSet::TickTimers),
                 handle_action.in_set(AppSet::RecordInput),
             )
                 .run_if(in_state(GameState::Playing)),
@@ -24,6 +32,19 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Debug, Component)]
 pub struct GameTimeText;
 
+#[derive(Debug, Component)]
+pub struct BoltCountText;
+
+/// Shows a line of text at the bottom of the screen for `duration`, replacing the current one.
+#[derive(Debug, Event)]
+pub struct ShowMessage {
+    pub text: String,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Component)]
+struct Message(Timer);
+
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
 enum Action {
@@ -50,6 +71,8 @@ fn spawn_ui(
     font_handles: Res<HandleMap<FontKey>>,
     q_window: Query<&Window, With<PrimaryWindow>>,
     config: Res<GameConfig>,
+    level: Res<LevelData>,
+    collected_bolts: Res<CollectedBolts>,
 ) {
     use bevy::ui::Val::*;
     for window in &q_window {
@@ -76,6 +99,19 @@ fn spawn_ui(
                                 ..default()
                             },
                         ));
+                        if !level.bolts.is_empty() {
+                            cmd.spawn((
+                                BoltCountText,
+                                TextBundle::from_section(
+                                    format_bolt_count(collected_bolts.0.len(), level.bolts.len()),
+                                    TextStyle {
+                                        font: font_handles[&FontKey::GeoFont].clone_weak(),
+                                        font_size: window.height() / config.game_time.ratio,
+                                        color: Color::from(WHITE_SMOKE),
+                                    },
+                                ),
+                            ));
+                        }
                     }
                 });
             });
@@ -103,13 +139,66 @@ fn spawn_ui(
     }
 }
 
+fn on_show_message(
+    trigger: Trigger<ShowMessage>,
+    mut cmd: Commands,
+    q_messages: Query<Entity, With<Message>>,
+    font_handles: Res<HandleMap<FontKey>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+) {
+    use bevy::ui::Val::*;
+    for message in &q_messages {
+        cmd.entity(message).despawn_recursive();
+    }
+    let ShowMessage { text, duration } = trigger.event();
+    for window in &q_window {
+        cmd.spawn((
+            Name::new("Message"),
+            Message(Timer::new(*duration, TimerMode::Once)),
+            NodeBundle {
+                style: Style {
+                    width: Percent(100.0),
+                    bottom: Vh(10.0),
+                    justify_content: JustifyContent::Center,
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|cmd| {
+            cmd.spawn(TextBundle::from_section(
+                text.clone(),
+                TextStyle {
+                    font: font_handles[&FontKey::GeoFont].clone_weak(),
+                    font_size: window.height() / 28.,
+                    color: Color::from(WHITE_SMOKE),
+                },
+            ));
+        });
+    }
+}
+
+fn expire_messages(
+    mut cmd: Commands,
+    time: Res<Time>,
+    mut q_messages: Query<(Entity, &mut Message)>,
+) {
+    for (entity, mut message) in &mut q_messages {
+        if message.0.tick(time.delta()).finished() {
+            cmd.entity(entity).despawn_recursive();
+        }
+    }
+}
+
 fn adjust_font_size(
-    mut q_game_time_text: Query<&mut Text, With<GameTimeText>>,
+    mut q_text: Query<&mut Text, Or<(With<GameTimeText>, With<BoltCountText>)>>,
     q_window: Query<&Window, With<PrimaryWindow>>,
     config: Res<GameConfig>,
 ) {
     for window in &q_window {
-        for mut text in &mut q_game_time_text {
+        for mut text in &mut q_text {
             for section in text.sections.iter_mut() {
                 section.style.font_size = window.height() / config.game_time.ratio;
             }
@@ -126,6 +215,20 @@ fn update_game_time(
     }
 }
 
+fn update_bolt_count(
+    mut q_bolt_count_text: Query<&mut Text, With<BoltCountText>>,
+    collected_bolts: Res<CollectedBolts>,
+    level: Res<LevelData>,
+) {
+    for mut text in &mut q_bolt_count_text {
+        text.sections[0].value = format_bolt_count(collected_bolts.0.len(), level.bolts.len());
+    }
+}
+
+pub fn format_bolt_count(collected: usize, total: usize) -> String {
+    format!("{collected}/{total} bolts")
+}
+
 pub fn format_game_time(duration: Duration) -> String {
     let minutes = duration.as_secs() / 60;
     let seconds = duration.as_secs() % 60;
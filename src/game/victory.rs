@@ -5,22 +5,34 @@ use bevy::{
 };
 
 use super::{
-    assets::{FontKey, HandleMap, ImageKey},
+    assets::{FontKey, HandleMap, ImageKey, SoundtrackKey},
+    audio::soundtrack::PlaySoundtrack,
+    bolt::CollectedBolts,
     game_time::GameTime,
     spawn::{
         level::ResetLevel,
         player::{Arm, LostLimbs},
     },
-    ui::format_game_time,
+    ui::{format_bolt_count, format_game_time},
     GameState,
 };
-use crate::{screen::Screen, ui::prelude::*};
+use crate::{
+    data::{
+        level::{LevelData, LEVEL_PATH},
+        records::Records,
+    },
+    screen::Screen,
+    ui::prelude::*,
+};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::Victory), init_ui)
-        .add_systems(Update, handle_action.run_if(in_state(GameState::Victory)));
+    app.add_systems(
+        OnEnter(GameState::Victory),
+        (init_ui, play_victory_soundtrack),
+    )
+    .add_systems(Update, handle_action.run_if(in_state(GameState::Victory)));
 }
 
 #[derive(Debug, Component)]
@@ -38,6 +50,9 @@ struct ArmComment;
 #[derive(Debug, Component)]
 struct TimeSection;
 
+#[derive(Debug, Component)]
+struct BoltSection;
+
 #[derive(Debug, Component)]
 struct ButtonSection;
 
@@ -69,6 +84,12 @@ fn handle_action(
     }
 }
 
+fn play_victory_soundtrack(mut cmd: Commands, level: Res<LevelData>) {
+    if let Some(soundtrack) = &level.victory_soundtrack {
+        cmd.trigger(PlaySoundtrack::Key(SoundtrackKey(soundtrack.clone())));
+    }
+}
+
 fn init_ui(
     mut cmd: Commands,
     image_handles: Res<HandleMap<ImageKey>>,
@@ -76,7 +97,16 @@ fn init_ui(
     q_window: Query<&Window, With<PrimaryWindow>>,
     lost_limbs: Res<LostLimbs>,
     game_time: Res<GameTime>,
+    collected_bolts: Res<CollectedBolts>,
+    level: Res<LevelData>,
+    mut records: ResMut<Records>,
 ) {
+    let bolt_count = collected_bolts.0.len();
+    let new_best = records.record_bolts(LEVEL_PATH, bolt_count);
+    if new_best {
+        records.save();
+    }
+    let best_bolts = records.best_bolts(LEVEL_PATH);
     let mut rng = thread_rng();
     for window in &q_window {
         cmd.ui_center_root()
@@ -156,6 +186,36 @@ fn init_ui(
                         ..default()
                     },
                 ));
+                if !level.bolts.is_empty() {
+                    cmd.spawn((
+                        BoltSection,
+                        TextBundle {
+                            text: Text::from_sections([
+                                TextSection {
+                                    value: format_bolt_count(bolt_count, level.bolts.len()),
+                                    style: TextStyle {
+                                        font: font_handles[&FontKey::GeoFont].clone_weak(),
+                                        font_size: window.height() / 18.,
+                                        color: Color::from(YELLOW),
+                                    },
+                                },
+                                TextSection {
+                                    value: if new_best {
+                                        "  New best !".to_string()
+                                    } else {
+                                        format!("  Best: {best_bolts}")
+                                    },
+                                    style: TextStyle {
+                                        font: font_handles[&FontKey::GeoFont].clone_weak(),
+                                        font_size: window.height() / 28.,
+                                        color: Color::from(WHITE_SMOKE),
+                                    },
+                                },
+                            ]),
+                            ..default()
+                        },
+                    ));
+                }
                 cmd.spawn((
                     CommentsSection,
                     NodeBundle {
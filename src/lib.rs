@@ -1,12 +1,19 @@
+pub mod autopilot;
 pub mod data;
 #[cfg(feature = "dev")]
 mod dev_tools;
 mod game;
 pub mod lerp;
 pub mod mouse;
+pub mod platform;
+pub mod polygon;
 mod screen;
+pub mod track;
 mod ui;
 
+// the tools working on level files expand prefab instances like the game does
+pub use game::level_object::LevelObjects;
+
 use bevy::{asset::AssetMetaCheck, audio::AudioPlugin, math::ivec2, prelude::*};
 
 use bevy_rapier2d::prelude::*;
@@ -0,0 +1,95 @@
+//! Paths of moving platforms.
+
+use bevy::prelude::*;
+
+use crate::data::level::{PlatformData, PlatformEasing, PlatformMode};
+
+/// The straight legs the platform goes through in one period, skipping the empty ones.
+pub fn legs(data: &PlatformData) -> Vec<(Vec2, Vec2)> {
+    let points = data.points();
+    let forward = points.windows(2).map(|leg| (leg[0], leg[1]));
+    let legs: Vec<_> = match data.mode {
+        PlatformMode::Loop => forward
+            .chain(points.last().copied().zip(points.first().copied()))
+            .collect(),
+        PlatformMode::PingPong => forward
+            .clone()
+            .chain(forward.rev().map(|(from, to)| (to, from)))
+            .collect(),
+    };
+    legs.into_iter().filter(|(from, to)| from != to).collect()
+}
+
+fn ease(easing: PlatformEasing, t: f32) -> f32 {
+    match easing {
+        PlatformEasing::Linear => t,
+        PlatformEasing::InOut => t * t * (3.0 - 2.0 * t),
+    }
+}
+
+/// Where the platform center is `elapsed` seconds after it started from the first waypoint.
+pub fn position(data: &PlatformData, elapsed: f32) -> Vec2 {
+    let start = data.waypoints.first().copied().map(Vec2::from);
+    let legs = legs(data);
+    let period: f32 = legs.iter().map(|(from, to)| from.distance(*to)).sum();
+    if period <= 0.0 || data.speed <= 0.0 {
+        return start.unwrap_or_default();
+    }
+    let mut distance = (elapsed * data.speed).rem_euclid(period);
+    for (from, to) in &legs {
+        let length = from.distance(*to);
+        if distance <= length {
+            return from.lerp(*to, ease(data.easing, distance / length));
+        }
+        distance -= length;
+    }
+    start.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{config::DEFAULT_SURFACE, level::my_vec2};
+    use bevy::math::vec2;
+
+    fn platform(mode: PlatformMode, easing: PlatformEasing) -> PlatformData {
+        PlatformData {
+            size: my_vec2(4.0, 1.0),
+            waypoints: vec![my_vec2(0.0, 0.0), my_vec2(10.0, 0.0), my_vec2(10.0, 10.0)],
+            speed: 2.0,
+            easing,
+            mode,
+            surface: DEFAULT_SURFACE.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_loop_and_ping_pong() {
+        let looping = platform(PlatformMode::Loop, PlatformEasing::Linear);
+        assert_eq!(position(&looping, 2.5), vec2(5.0, 0.0));
+        assert_eq!(position(&looping, 7.5), vec2(10.0, 5.0));
+        // straight back along the diagonal
+        let back = position(&looping, 10.0 + 10.0 / 2.0_f32.sqrt() / 2.0);
+        assert!(back.distance(vec2(5.0, 5.0)) < 1e-3, "{back}");
+
+        let ping_pong = platform(PlatformMode::PingPong, PlatformEasing::Linear);
+        assert_eq!(position(&ping_pong, 12.5), vec2(10.0, 5.0));
+        assert_eq!(position(&ping_pong, 17.5), vec2(5.0, 0.0));
+        assert_eq!(position(&ping_pong, 20.0), vec2(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_easing_and_degenerate_paths() {
+        let eased = platform(PlatformMode::PingPong, PlatformEasing::InOut);
+        assert!(position(&eased, 1.0).x < 2.0);
+        assert_eq!(position(&eased, 2.5), vec2(5.0, 0.0));
+        assert_eq!(position(&eased, 5.0), vec2(10.0, 0.0));
+
+        let mut still = platform(PlatformMode::Loop, PlatformEasing::Linear);
+        still.speed = 0.0;
+        assert_eq!(position(&still, 3.0), vec2(0.0, 0.0));
+        still.speed = 2.0;
+        still.waypoints.truncate(1);
+        assert_eq!(position(&still, 3.0), vec2(0.0, 0.0));
+    }
+}
@@ -0,0 +1,159 @@
+//! Simple polygon helpers for polygon walls.
+
+use bevy::prelude::*;
+
+/// Signed area, positive for counter-clockwise points.
+pub fn signed_area(points: &[Vec2]) -> f32 {
+    let mut area = 0.0;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        area += a.perp_dot(b);
+    }
+    area / 2.0
+}
+
+/// Even-odd test, works for concave polygons.
+pub fn contains(points: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Whether two edges that don't share a point cross each other. Edges only touching are let
+/// through.
+pub fn self_intersecting(points: &[Vec2]) -> bool {
+    let count = points.len();
+    let edge = |i: usize| (points[i], points[(i + 1) % count]);
+    (0..count).any(|i| {
+        let (a, b) = edge(i);
+        // the last edge is next to the first one
+        let last = if i == 0 { count - 1 } else { count };
+        (i + 2..last).any(|j| {
+            let (c, d) = edge(j);
+            let (d1, d2) = ((b - a).perp_dot(c - a), (b - a).perp_dot(d - a));
+            let (d3, d4) = ((d - c).perp_dot(a - c), (d - c).perp_dot(b - c));
+            d1 * d2 < 0.0 && d3 * d4 < 0.0
+        })
+    })
+}
+
+fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(p - a) >= 0.0
+        && (c - b).perp_dot(p - b) >= 0.0
+        && (a - c).perp_dot(p - c) >= 0.0
+}
+
+/// Ear clipping triangulation of a simple polygon, in either winding.
+///
+/// Returns counter-clockwise triangles indexing into `points`, or `None` when the polygon
+/// is degenerate or self-intersecting.
+pub fn triangulate(points: &[Vec2]) -> Option<Vec<[usize; 3]>> {
+    if points.len() < 3 {
+        return None;
+    }
+    let area = signed_area(points);
+    if area.abs() < f32::EPSILON || self_intersecting(points) {
+        return None;
+    }
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    if area < 0.0 {
+        remaining.reverse();
+    }
+    let mut triangles = Vec::with_capacity(points.len() - 2);
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find(|&i| {
+            let (ia, ib, ic) = (
+                remaining[(i + count - 1) % count],
+                remaining[i],
+                remaining[(i + 1) % count],
+            );
+            let (a, b, c) = (points[ia], points[ib], points[ic]);
+            (b - a).perp_dot(c - b) > 0.0
+                && remaining
+                    .iter()
+                    .filter(|&&j| j != ia && j != ib && j != ic)
+                    .all(|&j| !in_triangle(points[j], a, b, c))
+        })?;
+        triangles.push([
+            remaining[(ear + count - 1) % count],
+            remaining[ear],
+            remaining[(ear + 1) % count],
+        ]);
+        remaining.remove(ear);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    Some(triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::vec2;
+
+    fn triangles_area(points: &[Vec2], triangles: &[[usize; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|t| signed_area(&[points[t[0]], points[t[1]], points[t[2]]]))
+            .sum()
+    }
+
+    #[test]
+    fn test_triangulate_square() {
+        let points = [
+            vec2(0.0, 0.0),
+            vec2(1.0, 0.0),
+            vec2(1.0, 1.0),
+            vec2(0.0, 1.0),
+        ];
+        let triangles = triangulate(&points).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(triangles_area(&points, &triangles), 1.0);
+    }
+
+    #[test]
+    fn test_triangulate_concave_clockwise() {
+        // an L shape, given clockwise
+        let points = [
+            vec2(0.0, 0.0),
+            vec2(0.0, 2.0),
+            vec2(1.0, 2.0),
+            vec2(1.0, 1.0),
+            vec2(2.0, 1.0),
+            vec2(2.0, 0.0),
+        ];
+        let triangles = triangulate(&points).unwrap();
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(triangles_area(&points, &triangles), 3.0);
+        assert!(contains(&points, vec2(0.5, 1.5)));
+        assert!(!contains(&points, vec2(1.5, 1.5)));
+    }
+
+    #[test]
+    fn test_triangulate_pentagram() {
+        // a star drawn in one stroke, every edge crosses two others
+        let points: Vec<Vec2> = (0..5)
+            .map(|i| Vec2::from_angle(i as f32 * 4.0 * std::f32::consts::PI / 5.0) * 10.0)
+            .collect();
+        assert!(self_intersecting(&points));
+        assert!(triangulate(&points).is_none());
+        let pentagon: Vec<Vec2> = (0..5)
+            .map(|i| Vec2::from_angle(i as f32 * 2.0 * std::f32::consts::PI / 5.0) * 10.0)
+            .collect();
+        assert!(!self_intersecting(&pentagon));
+        assert!(triangulate(&pentagon).is_some());
+    }
+
+    #[test]
+    fn test_triangulate_degenerate() {
+        assert!(triangulate(&[vec2(0.0, 0.0), vec2(1.0, 1.0)]).is_none());
+        assert!(triangulate(&[vec2(0.0, 0.0), vec2(1.0, 1.0), vec2(2.0, 2.0)]).is_none());
+    }
+}
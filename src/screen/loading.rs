@@ -7,9 +7,16 @@ use super::Screen;
 use crate::{
     data::{
         config::{GameConfig, GameConfigHandle},
-        level::{LevelData, LevelDataHandle},
+        level::{LevelData, LevelDataHandle, LEVEL_PATH},
+    },
+    game::{
+        assets::{
+            HandleMap, ImageKey, SfxKey, SoundtrackKey, SoundtrackManifest,
+            SoundtrackManifestHandle, SoundtrackStems, SOUNDTRACK_MANIFEST_PATH,
+        },
+        background::BackgroundImages,
+        prefab::Prefabs,
     },
-    game::assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
     ui::prelude::*,
 };
 
@@ -20,19 +27,31 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Loading), enter_loading);
     app.add_systems(
         Update,
-        (complete_config, complete_level_data).run_if(in_state(Screen::Loading)),
+        (
+            complete_config,
+            complete_level_data,
+            complete_soundtracks,
+            load_backgrounds
+                .run_if(resource_exists::<GameConfig>.and_then(resource_exists::<LevelData>)),
+        )
+            .run_if(in_state(Screen::Loading)),
     );
     app.add_systems(
         Update,
-        continue_to_title.run_if(in_state(Screen::Loading).and_then(all_assets_loaded)),
+        continue_to_title
+            .after(load_backgrounds)
+            .run_if(in_state(Screen::Loading).and_then(all_assets_loaded)),
     );
 }
 
 fn enter_loading(mut cmd: Commands, asset_server: Res<AssetServer>) {
     let game_config_handle = GameConfigHandle(asset_server.load("game.config.toml"));
-    let level_data_handle = LevelDataHandle(asset_server.load("game.level.ron"));
+    let level_data_handle = LevelDataHandle(asset_server.load(LEVEL_PATH));
     cmd.insert_resource(game_config_handle);
     cmd.insert_resource(level_data_handle);
+    cmd.insert_resource(SoundtrackManifestHandle(
+        asset_server.load(SOUNDTRACK_MANIFEST_PATH),
+    ));
     cmd.ui_center_root()
         .insert(StateScoped(Screen::Loading))
         .with_children(|children| {
@@ -47,7 +66,8 @@ fn complete_level_data(
     mut level_datas: ResMut<Assets<LevelData>>,
 ) {
     if asset_server.is_loaded_with_dependencies(&level_data_handle.0) {
-        if let Some(level_data) = level_datas.remove(level_data_handle.0.id()) {
+        if let Some(mut level_data) = level_datas.remove(level_data_handle.0.id()) {
+            level_data.migrate();
             cmd.insert_resource(level_data);
         }
     }
@@ -68,18 +88,53 @@ fn complete_config(
     }
 }
 
+fn complete_soundtracks(
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+    manifest_handle: Res<SoundtrackManifestHandle>,
+    mut manifests: ResMut<Assets<SoundtrackManifest>>,
+) {
+    if asset_server.is_loaded_with_dependencies(&manifest_handle.0) {
+        if let Some(manifest) = manifests.remove(manifest_handle.0.id()) {
+            cmd.insert_resource(HandleMap::<SoundtrackKey>::from_manifest(
+                &manifest,
+                &asset_server,
+            ));
+            cmd.insert_resource(SoundtrackStems::from_manifest(&manifest, &asset_server));
+        }
+    }
+}
+
+fn load_backgrounds(
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<BackgroundImages>,
+    config: Res<GameConfig>,
+    level: Res<LevelData>,
+) {
+    images.load(&asset_server, &config.backgrounds);
+    if let Some(backgrounds) = &level.backgrounds {
+        images.load(&asset_server, backgrounds);
+    }
+}
+
 fn all_assets_loaded(
     // mut cmd: Commands,
     asset_server: Res<AssetServer>,
     image_handles: Res<HandleMap<ImageKey>>,
     sfx_handles: Res<HandleMap<SfxKey>>,
-    soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    soundtrack_handles: Option<Res<HandleMap<SoundtrackKey>>>,
+    soundtrack_stems: Option<Res<SoundtrackStems>>,
     config: Option<Res<GameConfig>>,
+    prefabs: Res<Prefabs>,
+    backgrounds: Res<BackgroundImages>,
 ) -> bool {
     image_handles.all_loaded(&asset_server)
         && sfx_handles.all_loaded(&asset_server)
-        && soundtrack_handles.all_loaded(&asset_server)
+        && soundtrack_handles.is_some_and(|handles| handles.all_loaded(&asset_server))
+        && soundtrack_stems.is_some_and(|stems| stems.all_loaded(&asset_server))
         && config.is_some()
+        && prefabs.loaded
+        && backgrounds.all_loaded(&asset_server)
 }
 
 #[cfg(feature = "dev")]
@@ -3,8 +3,9 @@
 use bevy::prelude::*;
 
 use super::Screen;
-use crate::game::{
-    assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack, spawn::level::SpawnLevel,
+use crate::{
+    data::{config::GameConfig, level::LevelData},
+    game::{assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack, spawn::level::SpawnLevel},
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -26,8 +27,15 @@ pub enum StartPlaying {
     Continue,
 }
 
-fn enter_playing(mut commands: Commands) {
-    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::MainSong));
+fn enter_playing(mut commands: Commands, level: Res<LevelData>, config: Res<GameConfig>) {
+    let soundtrack = level
+        .soundtrack
+        .clone()
+        .unwrap_or_else(|| config.audio.soundtrack.clone());
+    commands.trigger(PlaySoundtrack::Key(SoundtrackKey(soundtrack)));
+    commands.trigger(PlaySoundtrack::Ambient(
+        level.ambient.clone().map(SoundtrackKey),
+    ));
 }
 
 fn exit_playing(mut cmd: Commands, q_entities: Query<Entity>) {
@@ -0,0 +1,165 @@
+//! Geometry of curved track pieces, in the space of the track with its center at the origin.
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+
+use crate::data::level::TrackData;
+
+/// Longest segment of the outer edge, the collider and mesh are smooth enough below that.
+pub const SEGMENT_LENGTH: f32 = 0.5;
+/// How far the wheel has to be from the band before a loop lets it in again, enough for the
+/// whole robot to be out of the band.
+const LOOP_CLEARANCE: f32 = 5.0;
+/// Both halves of a loop cover this much of the track past its middle, the wheel switches
+/// halves there so it is never left without ground under it.
+const LOOP_OVERLAP: f32 = 0.05;
+
+fn direction(angle: f32) -> Vec2 {
+    Vec2::new(angle.cos(), angle.sin())
+}
+
+/// Outline of the band between the fractions `from` and `to` of the track, the inner edge
+/// from `from` to `to` followed by the outer edge back.
+pub fn outline(track: &TrackData, from: f32, to: f32) -> Vec<Vec2> {
+    let span = track.span() * (to - from);
+    let outer = track.radius + track.thickness;
+    let segments = ((span.abs() * outer / SEGMENT_LENGTH).ceil() as usize).max(1);
+    let angle =
+        |i: usize| track.start_angle + track.span() * from + span * i as f32 / segments as f32;
+    let inner_edge = (0..=segments).map(|i| direction(angle(i)) * track.radius);
+    let outer_edge = (0..=segments).rev().map(|i| direction(angle(i)) * outer);
+    inner_edge.chain(outer_edge).collect()
+}
+
+/// Counter-clockwise triangles covering an [`outline`] of `len` points.
+pub fn outline_triangles(track: &TrackData, len: usize) -> Vec<[usize; 3]> {
+    let segments = len / 2 - 1;
+    (0..segments)
+        .flat_map(|i| {
+            let (inner, next_inner) = (i, i + 1);
+            let (outer, next_outer) = (len - 1 - i, len - 2 - i);
+            [[inner, outer, next_outer], [inner, next_outer, next_inner]]
+        })
+        .map(|[a, b, c]| {
+            if track.span() < 0.0 {
+                [a, c, b]
+            } else {
+                [a, b, c]
+            }
+        })
+        .collect()
+}
+
+/// How far along the track `point` is, from 0 at the start to 1 at the end, `None` when it is
+/// outside of the angles covered by the track.
+pub fn progress(track: &TrackData, point: Vec2) -> Option<f32> {
+    let span = track.span();
+    if span == 0.0 {
+        return None;
+    }
+    let offset = ((point.y.atan2(point.x) - track.start_angle) * span.signum()).rem_euclid(TAU);
+    (offset <= span.abs()).then(|| offset / span.abs())
+}
+
+/// Whether `point` is on the band.
+pub fn contains(track: &TrackData, point: Vec2) -> bool {
+    let distance = point.length();
+    distance >= track.radius
+        && distance <= track.radius + track.thickness
+        && progress(track, point).is_some()
+}
+
+/// The halves of a loop, only one of them is solid at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Component)]
+pub enum LoopHalf {
+    /// From the start to the top, solid until the robot goes over the top so it can get in
+    /// through the exit half.
+    #[default]
+    Entry,
+    /// From the top to the end, solid once the robot went over the top so it can get out
+    /// through the entry half.
+    Exit,
+}
+
+/// Which half of a loop should be solid with the wheel at `wheel`, `half` being the solid one.
+pub fn next_loop_half(track: &TrackData, half: LoopHalf, wheel: Vec2) -> LoopHalf {
+    let distance = wheel.length();
+    match half {
+        LoopHalf::Entry
+            if distance < track.radius
+                && progress(track, wheel).is_some_and(|t| (t - 0.5).abs() < LOOP_OVERLAP) =>
+        {
+            LoopHalf::Exit
+        }
+        LoopHalf::Exit if distance > track.radius + track.thickness + LOOP_CLEARANCE => {
+            LoopHalf::Entry
+        }
+        _ => half,
+    }
+}
+
+/// The fractions of the track covered by `half`.
+pub fn loop_half_range(half: LoopHalf) -> (f32, f32) {
+    match half {
+        LoopHalf::Entry => (0.0, 0.5 + LOOP_OVERLAP),
+        LoopHalf::Exit => (0.5 - LOOP_OVERLAP, 1.0),
+    }
+}
+
+/// A loop starting at the bottom and ridden to the right, for tests and new tracks.
+pub fn default_loop(center: Vec2, radius: f32) -> TrackData {
+    TrackData {
+        center: center.into(),
+        radius,
+        start_angle: -PI / 2.0,
+        end_angle: 1.5 * PI,
+        thickness: 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polygon::signed_area;
+    use bevy::math::vec2;
+
+    #[test]
+    fn test_outline_triangles_cover_band() {
+        let mut track = default_loop(Vec2::ZERO, 10.0);
+        track.end_angle = 0.0;
+        let points = outline(&track, 0.0, 1.0);
+        let area: f32 = outline_triangles(&track, points.len())
+            .iter()
+            .map(|t| signed_area(&[points[t[0]], points[t[1]], points[t[2]]]))
+            .sum();
+        // a quarter of the ring, a bit less because of the segments
+        let ring = PI * (11.0f32.powi(2) - 10.0f32.powi(2)) / 4.0;
+        assert!(area > 0.0 && (area - ring).abs() < 0.1, "{area} vs {ring}");
+        assert!(contains(&track, vec2(7.5, -7.5)));
+        assert!(!contains(&track, vec2(-7.5, -7.5)));
+        assert!(!contains(&track, vec2(5.0, -5.0)));
+    }
+
+    #[test]
+    fn test_ride_through_loop() {
+        let track = default_loop(Vec2::ZERO, 10.0);
+        let mut half = LoopHalf::Entry;
+        // coming from the left through the exit half, around, and out through the entry half
+        let ride = [
+            (vec2(-15.0, -9.0), LoopHalf::Entry),
+            (vec2(-7.0, -7.0), LoopHalf::Entry),
+            (vec2(0.0, -9.0), LoopHalf::Entry),
+            (vec2(9.0, 0.0), LoopHalf::Entry),
+            (vec2(0.0, 9.0), LoopHalf::Exit),
+            (vec2(-9.0, 0.0), LoopHalf::Exit),
+            (vec2(0.0, -9.0), LoopHalf::Exit),
+            (vec2(13.0, -9.0), LoopHalf::Exit),
+            (vec2(18.0, -9.0), LoopHalf::Entry),
+        ];
+        for (wheel, expected) in ride {
+            half = next_loop_half(&track, half, wheel);
+            assert_eq!(half, expected, "at {wheel}");
+        }
+    }
+}
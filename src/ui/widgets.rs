@@ -185,6 +185,8 @@ pub trait Containers {
     /// and centers its content horizontally and vertically.
     fn ui_center_root(&mut self) -> EntityCommands;
     fn ui_top_root(&mut self) -> EntityCommands;
+    /// Spawns a column along the right edge of the screen.
+    fn ui_right_root(&mut self) -> EntityCommands;
 }
 
 impl Containers for Commands<'_, '_> {
@@ -223,6 +225,25 @@ impl Containers for Commands<'_, '_> {
             },
         ))
     }
+    fn ui_right_root(&mut self) -> EntityCommands {
+        self.spawn((
+            Name::new("Right UI Root"),
+            NodeBundle {
+                style: Style {
+                    height: Percent(100.0),
+                    right: Px(0.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Stretch,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Px(10.0),
+                    padding: UiRect::all(Val::Vh(1.0)),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+    }
 }
 
 /// An internal trait for types that can spawn entities.